@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::replay::Playback;
+use crate::{best_score, storage, FlapEvent, GameMode, GameState, Score, ScoreEvent, FIXED_TICK_RATE};
+
+const STATS_KEY: &str = "flappy-bird.stats";
+
+const TOAST_WIDTH: f32 = 220.;
+const TOAST_SLIDE_DURATION: f32 = 0.3;
+const TOAST_DURATION: f32 = 3.;
+const TOAST_SPACING: f32 = 48.;
+
+const GLIDE_SECONDS: f64 = 3.;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+enum Achievement {
+    ScoreTen,
+    ThousandPipes,
+    Glider
+}
+
+impl Achievement {
+    const ALL: [Achievement; 3] = [Achievement::ScoreTen, Achievement::ThousandPipes, Achievement::Glider];
+
+    fn title(self) -> &'static str {
+        match self {
+            Achievement::ScoreTen => "Getting Good",
+            Achievement::ThousandPipes => "Frequent Flyer",
+            Achievement::Glider => "Glider"
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Achievement::ScoreTen => "Score 10 in a single run",
+            Achievement::ThousandPipes => "Fly through 1000 pipes",
+            Achievement::Glider => "Stay airborne 3s without flapping"
+        }
+    }
+}
+
+/// Lifetime statistics, persisted across sessions.
+#[derive(Resource, Serialize, Deserialize, Default)]
+struct PlayerStats {
+    pipes_passed: u64,
+    runs: u32,
+    unlocked: Vec<Achievement>
+}
+
+impl PlayerStats {
+    fn unlock(&mut self, achievement: Achievement) -> bool {
+        if self.unlocked.contains(&achievement) {
+            return false;
+        }
+
+        self.unlocked.push(achievement);
+        true
+    }
+}
+
+/// Fixed ticks since the bird last flapped during the current run.
+#[derive(Resource, Default)]
+struct GlideTicks(u64);
+
+#[derive(Event)]
+struct Unlocked(Achievement);
+
+#[derive(Component)]
+struct Toast {
+    timer: Timer
+}
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<PlayerStats>(STATS_KEY).unwrap_or_default())
+            .init_resource::<GlideTicks>()
+            .add_event::<Unlocked>()
+            .add_systems(OnEnter(GameState::Playing), |mut glide: ResMut<GlideTicks>| glide.0 = 0)
+            .add_systems(
+                FixedUpdate,
+                glide_system
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_equals(GameMode::Single).and(not(resource_exists::<Playback>)))
+            )
+            .add_systems(Update, (
+                (pipe_stats_system, score_achievement_system)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_equals(GameMode::Single).and(not(resource_exists::<Playback>))),
+                unlock_toast_system,
+                toast_system
+            ))
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                save_stats.run_if(resource_equals(GameMode::Single).and(not(resource_exists::<Playback>)))
+            )
+            .add_systems(OnEnter(GameState::Achievements), spawn_achievement_list)
+            .add_systems(Update, achievement_list_input_system.run_if(in_state(GameState::Achievements)));
+    }
+}
+
+fn glide_system(
+    mut flaps: EventReader<FlapEvent>,
+    mut glide: ResMut<GlideTicks>,
+    mut stats: ResMut<PlayerStats>,
+    mut unlocked: EventWriter<Unlocked>
+) {
+    if flaps.read().last().is_some() {
+        glide.0 = 0;
+        return;
+    }
+
+    glide.0 += 1;
+    if glide.0 as f64 >= GLIDE_SECONDS * FIXED_TICK_RATE && stats.unlock(Achievement::Glider) {
+        unlocked.send(Unlocked(Achievement::Glider));
+    }
+}
+
+fn pipe_stats_system(
+    mut score_events: EventReader<ScoreEvent>,
+    mut stats: ResMut<PlayerStats>,
+    mut unlocked: EventWriter<Unlocked>
+) {
+    let passed = score_events.read().filter(|score_event| matches!(score_event, ScoreEvent::PipePassed { .. })).count() as u64;
+    if passed == 0 {
+        return;
+    }
+
+    stats.pipes_passed += passed;
+    if stats.pipes_passed >= 1000 && stats.unlock(Achievement::ThousandPipes) {
+        unlocked.send(Unlocked(Achievement::ThousandPipes));
+    }
+}
+
+fn score_achievement_system(
+    score_query: Query<&Score>,
+    mut stats: ResMut<PlayerStats>,
+    mut unlocked: EventWriter<Unlocked>
+) {
+    if best_score(&score_query) >= 10 && stats.unlock(Achievement::ScoreTen) {
+        unlocked.send(Unlocked(Achievement::ScoreTen));
+    }
+}
+
+fn save_stats(mut stats: ResMut<PlayerStats>) {
+    stats.runs += 1;
+    storage::save(STATS_KEY, &*stats);
+}
+
+fn unlock_toast_system(
+    mut commands: Commands,
+    mut unlocked: EventReader<Unlocked>,
+    toast_query: Query<(), With<Toast>>,
+    stats: Res<PlayerStats>
+) {
+    let first_slot = toast_query.iter().count();
+
+    for (slot, Unlocked(achievement)) in (first_slot..).zip(unlocked.read()) {
+        storage::save(STATS_KEY, &*stats);
+
+        commands
+            .spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10. + slot as f32 * TOAST_SPACING),
+                    right: Val::Px(-TOAST_WIDTH),
+                    width: Val::Px(TOAST_WIDTH),
+                    padding: UiRect::all(Val::Px(6.)),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0., 0., 0., 0.8)),
+                Toast { timer: Timer::from_seconds(TOAST_DURATION, TimerMode::Once) }
+            ))
+            .with_children(|toast| {
+                toast.spawn((
+                    Text::new(format!("Achievement: {}", achievement.title())),
+                    TextFont {
+                        font_size: 14.,
+                        ..default()
+                    }
+                ));
+                toast.spawn((
+                    Text::new(achievement.description()),
+                    TextFont {
+                        font_size: 11.,
+                        ..default()
+                    }
+                ));
+            });
+    }
+}
+
+fn toast_system(mut commands: Commands, time: Res<Time>, mut toast_query: Query<(Entity, &mut Toast, &mut Node)>) {
+    for (entity, mut toast, mut node) in toast_query.iter_mut() {
+        if toast.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let elapsed = toast.timer.elapsed_secs();
+        let remaining = toast.timer.remaining_secs();
+        let shown = (elapsed.min(remaining) / TOAST_SLIDE_DURATION).min(1.);
+        node.right = Val::Px(-TOAST_WIDTH + (TOAST_WIDTH + 10.) * shown);
+    }
+}
+
+fn spawn_achievement_list(mut commands: Commands, stats: Res<PlayerStats>) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(8.),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.7)),
+            StateScoped(GameState::Achievements)
+        ))
+        .with_children(|list| {
+            list.spawn((
+                Text::new(format!("Achievements\nPipes flown: {}  Runs: {}", stats.pipes_passed, stats.runs)),
+                TextFont {
+                    font_size: 16.,
+                    ..default()
+                },
+                TextLayout::new_with_justify(JustifyText::Center)
+            ));
+
+            for achievement in Achievement::ALL {
+                let unlocked = stats.unlocked.contains(&achievement);
+                list.spawn((
+                    Text::new(format!(
+                        "[{}] {}\n{}",
+                        if unlocked { "x" } else { " " },
+                        achievement.title(),
+                        achievement.description()
+                    )),
+                    TextFont {
+                        font_size: 12.,
+                        ..default()
+                    },
+                    TextColor(if unlocked { Color::WHITE } else { Color::srgb(0.6, 0.6, 0.6) })
+                ));
+            }
+
+            list.spawn((
+                Text::new("Press Escape to go back"),
+                TextFont {
+                    font_size: 12.,
+                    ..default()
+                }
+            ));
+        });
+}
+
+fn achievement_list_input_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.any_just_pressed([KeyCode::Escape, KeyCode::Space]) {
+        next_state.set(GameState::Menu);
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+use crate::{BirdHitEvent, GameState, LoadingAssets};
+
+const MUSIC_FADE_SPEED: f32 = 0.8;
+const DUCK_VOLUME: f32 = 0.25;
+const DUCK_DURATION: f32 = 1.5;
+
+#[derive(Resource)]
+struct GameAudio {
+    menu_music: Handle<AudioSource>,
+    game_music: Handle<AudioSource>,
+    death: Handle<AudioSource>
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum MusicTrack {
+    Menu,
+    Game
+}
+
+/// A looping audio source whose volume is tweened towards `target`.
+#[derive(Component)]
+struct AudioChannel {
+    volume: f32,
+    target: f32
+}
+
+/// Temporarily lowers every channel while a sound effect plays over the music.
+#[derive(Resource, Default)]
+struct Ducking(Option<Timer>);
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ducking>()
+            .add_systems(Startup, load_audio)
+            .add_systems(OnExit(GameState::Loading), spawn_music)
+            .add_systems(OnEnter(GameState::Menu), fade_music_to(MusicTrack::Menu))
+            .add_systems(OnEnter(GameState::Playing), fade_music_to(MusicTrack::Game))
+            .add_systems(Update, (play_death_sound, channel_volume_system));
+    }
+}
+
+fn load_audio(mut commands: Commands, asset_server: Res<AssetServer>, mut loading_assets: ResMut<LoadingAssets>) {
+    let game_audio = GameAudio {
+        menu_music: asset_server.load("music-menu.wav"),
+        game_music: asset_server.load("music-game.wav"),
+        death: asset_server.load("sfx-death.wav")
+    };
+
+    loading_assets.0.extend([
+        game_audio.menu_music.clone().untyped(),
+        game_audio.game_music.clone().untyped(),
+        game_audio.death.clone().untyped()
+    ]);
+
+    commands.insert_resource(game_audio);
+}
+
+fn spawn_music(mut commands: Commands, game_audio: Res<GameAudio>) {
+    for (track, source) in [
+        (MusicTrack::Menu, &game_audio.menu_music),
+        (MusicTrack::Game, &game_audio.game_music)
+    ] {
+        commands.spawn((
+            AudioPlayer::new(source.clone()),
+            PlaybackSettings::LOOP.with_volume(Volume::ZERO),
+            AudioChannel { volume: 0., target: 0. },
+            track
+        ));
+    }
+}
+
+fn fade_music_to(track: MusicTrack) -> impl FnMut(Query<(&MusicTrack, &mut AudioChannel)>) {
+    move |mut channel_query| {
+        for (channel_track, mut channel) in channel_query.iter_mut() {
+            channel.target = if *channel_track == track { 1. } else { 0. };
+        }
+    }
+}
+
+fn play_death_sound(
+    mut commands: Commands,
+    mut hits: EventReader<BirdHitEvent>,
+    game_audio: Res<GameAudio>,
+    settings: Res<Settings>,
+    mut ducking: ResMut<Ducking>
+) {
+    // Both racers can crash on the same tick; one death sound covers it.
+    if hits.read().count() == 0 {
+        return;
+    }
+
+    commands.spawn((
+        AudioPlayer::new(game_audio.death.clone()),
+        PlaybackSettings::DESPAWN.with_volume(Volume::new(settings.sfx_volume))
+    ));
+    ducking.0 = Some(Timer::from_seconds(DUCK_DURATION, TimerMode::Once));
+}
+
+fn channel_volume_system(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut ducking: ResMut<Ducking>,
+    mut channel_query: Query<(&mut AudioChannel, &AudioSink)>
+) {
+    let duck = match ducking.0.as_mut() {
+        Some(timer) => DUCK_VOLUME + (1. - DUCK_VOLUME) * timer.tick(time.delta()).fraction(),
+        None => 1.
+    };
+
+    if ducking.0.as_ref().is_some_and(Timer::finished) {
+        ducking.0 = None;
+    }
+
+    let step = MUSIC_FADE_SPEED * time.delta_secs();
+
+    for (mut channel, sink) in channel_query.iter_mut() {
+        channel.volume += (channel.target - channel.volume).clamp(-step, step);
+        sink.set_volume(channel.volume * duck * settings.music_volume);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{save_to_disk, Screenshot, ScreenshotCaptured};
+use bevy::tasks::IoTaskPool;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{self, FilterType};
+use image::{Delay, Frame, RgbaImage};
+
+const CAPTURE_KEY: KeyCode = KeyCode::F12;
+const SCREENSHOT_DIR: &str = "screenshots";
+/// How long the key must stay down before a tap turns into a GIF recording.
+const HOLD_THRESHOLD: f32 = 0.5;
+const GIF_FPS: u32 = 10;
+const GIF_SECONDS: u32 = 5;
+/// Frames wider than this are scaled down to keep long recordings small.
+const GIF_MAX_WIDTH: u32 = 288;
+
+/// Rolling window of the most recent frames captured while the key is held.
+#[derive(Resource, Default)]
+struct GifRecording {
+    held: Option<Timer>,
+    frame_timer: Timer,
+    frames: VecDeque<RgbaImage>
+}
+
+/// Saves a PNG of the current frame on F12, or the last few seconds as a GIF while F12 is held.
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GifRecording {
+            frame_timer: Timer::from_seconds(1. / GIF_FPS as f32, TimerMode::Repeating),
+            ..default()
+        })
+        .add_systems(Update, capture_input_system);
+    }
+}
+
+fn capture_input_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut recording: ResMut<GifRecording>
+) {
+    if keys.just_pressed(CAPTURE_KEY) {
+        recording.held = Some(Timer::from_seconds(HOLD_THRESHOLD, TimerMode::Once));
+        recording.frames.clear();
+        commands.spawn(Screenshot::primary_window()).observe(save_to_disk(capture_path("png")));
+        return;
+    }
+
+    if keys.just_released(CAPTURE_KEY) {
+        recording.held = None;
+        let frames: Vec<RgbaImage> = recording.frames.drain(..).collect();
+        if !frames.is_empty() {
+            save_gif(frames, capture_path("gif"));
+        }
+        return;
+    }
+
+    let Some(held) = recording.held.as_mut() else {
+        return;
+    };
+    if !held.tick(time.delta()).finished() {
+        return;
+    }
+
+    if recording.frame_timer.tick(time.delta()).just_finished() {
+        commands.spawn(Screenshot::primary_window()).observe(record_frame);
+    }
+}
+
+fn record_frame(trigger: Trigger<ScreenshotCaptured>, mut recording: ResMut<GifRecording>) {
+    // A frame that lands after the key was released belongs to no recording.
+    if recording.held.is_none() {
+        return;
+    }
+
+    let Ok(image) = trigger.event().0.clone().try_into_dynamic() else {
+        return;
+    };
+
+    let mut frame = image.to_rgba8();
+    if frame.width() > GIF_MAX_WIDTH {
+        let height = frame.height() * GIF_MAX_WIDTH / frame.width();
+        frame = imageops::resize(&frame, GIF_MAX_WIDTH, height, FilterType::Nearest);
+    }
+
+    recording.frames.push_back(frame);
+    while recording.frames.len() > (GIF_FPS * GIF_SECONDS) as usize {
+        recording.frames.pop_front();
+    }
+}
+
+fn capture_path(extension: &str) -> PathBuf {
+    if let Err(error) = std::fs::create_dir_all(SCREENSHOT_DIR) {
+        warn!("could not create {SCREENSHOT_DIR}: {error}");
+    }
+
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    Path::new(SCREENSHOT_DIR).join(format!("flappy-bird-{millis}.{extension}"))
+}
+
+/// Encodes on the IO pool; quantizing a few seconds of frames takes too long for the main thread.
+fn save_gif(frames: Vec<RgbaImage>, path: PathBuf) {
+    IoTaskPool::get()
+        .spawn(async move {
+            let result = std::fs::File::create(&path)
+                .map_err(|error| error.to_string())
+                .and_then(|file| {
+                    let mut encoder = GifEncoder::new_with_speed(file, 10);
+                    encoder.set_repeat(Repeat::Infinite).map_err(|error| error.to_string())?;
+
+                    let delay = Delay::from_saturating_duration(Duration::from_secs(1) / GIF_FPS);
+                    encoder
+                        .encode_frames(frames.into_iter().map(|frame| Frame::from_parts(frame, 0, 0, delay)))
+                        .map_err(|error| error.to_string())
+                });
+
+            match result {
+                Ok(()) => info!("GIF saved to {}", path.display()),
+                Err(error) => error!("could not save GIF to {}: {error}", path.display())
+            }
+        })
+        .detach();
+}
@@ -0,0 +1,116 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::LoadingAssets;
+
+const CONFIG_PATH: &str = "config.ron";
+
+#[derive(Asset, Resource, TypePath, Deserialize, Clone)]
+pub struct GameConfig {
+    pub gravity: f32,
+    pub jump_force: f32,
+    pub pipe_speed: f32,
+    pub pipe_spawn_interval: f32,
+    pub gap_height: f32,
+    pub gap_range: f32,
+    pub wind_min_score: u32,
+    pub wind_interval: f32,
+    pub wind_warning: f32,
+    pub wind_duration: f32,
+    pub wind_strength: f32,
+    #[cfg(feature = "leaderboard")]
+    #[serde(default)]
+    pub leaderboard_url: Option<String>,
+    #[cfg(feature = "leaderboard")]
+    #[serde(default = "default_player_name")]
+    pub player_name: String,
+}
+
+#[cfg(feature = "leaderboard")]
+fn default_player_name() -> String {
+    "Player".to_string()
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            gravity: -8.,
+            jump_force: 5.,
+            pipe_speed: -3.,
+            pipe_spawn_interval: 2.0,
+            gap_height: 100.,
+            gap_range: 100.,
+            wind_min_score: 10,
+            wind_interval: 8.,
+            wind_warning: 1.,
+            wind_duration: 3.,
+            wind_strength: 3.,
+            #[cfg(feature = "leaderboard")]
+            leaderboard_url: None,
+            #[cfg(feature = "leaderboard")]
+            player_name: default_player_name(),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct GameConfigHandle(Handle<GameConfig>);
+
+#[derive(Default)]
+struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    type Asset = GameConfig;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .init_resource::<GameConfig>()
+            .add_systems(Startup, load_config)
+            .add_systems(PreUpdate, apply_config_system);
+    }
+}
+
+fn load_config(mut commands: Commands, asset_server: Res<AssetServer>, mut loading_assets: ResMut<LoadingAssets>) {
+    let handle = asset_server.load(CONFIG_PATH);
+    loading_assets.0.push(handle.clone().untyped());
+    commands.insert_resource(GameConfigHandle(handle));
+}
+
+fn apply_config_system(
+    mut events: EventReader<AssetEvent<GameConfig>>,
+    handle: Res<GameConfigHandle>,
+    configs: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>
+) {
+    for event in events.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            if let Some(loaded) = configs.get(&handle.0) {
+                *config = loaded.clone();
+            }
+        }
+    }
+}
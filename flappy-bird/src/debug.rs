@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use crate::{Bird, Pipe, PipeTimer, PlayArea, BIRD_HEIGHT, BIRD_WIDTH, PIPE_HEIGHT, PIPE_WIDTH};
+
+const HITBOX_COLOR: Color = Color::srgb(1., 0.2, 0.2);
+const VELOCITY_COLOR: Color = Color::srgb(0.2, 1., 0.2);
+const TIMER_COLOR: Color = Color::srgb(1., 1., 0.2);
+const VELOCITY_SCALE: f32 = 10.;
+
+#[derive(Resource, Default)]
+struct DebugOverlay(bool);
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .add_systems(Update, (
+                toggle_overlay_system,
+                draw_overlay_system.run_if(|overlay: Res<DebugOverlay>| overlay.0)
+            ).chain());
+    }
+}
+
+fn toggle_overlay_system(keys: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keys.just_pressed(KeyCode::F3) {
+        overlay.0 = !overlay.0;
+    }
+}
+
+fn draw_overlay_system(
+    mut gizmos: Gizmos,
+    bird_query: Query<(&Transform, &Bird)>,
+    pipe_query: Query<&Transform, With<Pipe>>,
+    pipe_timer: Option<Res<PipeTimer>>,
+    play_area: Res<PlayArea>
+) {
+    for (transform, bird) in bird_query.iter() {
+        let position = transform.translation.truncate();
+        gizmos.rect_2d(Isometry2d::from_translation(position), Vec2::new(BIRD_WIDTH, BIRD_HEIGHT), HITBOX_COLOR);
+        gizmos.arrow_2d(position, position + bird.velocity * VELOCITY_SCALE, VELOCITY_COLOR);
+    }
+
+    for transform in pipe_query.iter() {
+        let position = transform.translation.truncate();
+        gizmos.rect_2d(Isometry2d::from_translation(position), Vec2::new(PIPE_WIDTH, PIPE_HEIGHT), HITBOX_COLOR);
+    }
+
+    if let Some(pipe_timer) = pipe_timer {
+        let start = Vec2::new(-play_area.half_size().x, play_area.half_size().y - 2.);
+        let end = start + Vec2::X * play_area.size.x * pipe_timer.0.fraction();
+        gizmos.line_2d(start, end, TIMER_COLOR);
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use crate::config::GameConfig;
+use crate::replay::ReplaySet;
+use crate::{
+    update_bird_system, Bird, FixedTick, FlapEvent, GameMode, GameState, Pipe, PlayArea, ScoreGate, BIRD_HEIGHT,
+    BIRD_WIDTH, FIXED_TICK_RATE, PIPE_WIDTH
+};
+
+const DEMO_IDLE_SECONDS: f32 = 10.;
+/// Extra room the bot keeps between its bird and the pipes.
+const BOT_SAFETY_MARGIN: f32 = 2.;
+/// How many fixed ticks before reaching a gap the bot starts planning its way through.
+const BOT_PLAN_HORIZON: f32 = 90.;
+
+/// Marks a bird flown by the demo bot instead of a player, with the next gap it has planned for and
+/// the ticks to flap on, or `None` when no flaps it tried got it through.
+#[derive(Component, Default)]
+pub struct AiControlled {
+    gate: Option<Entity>,
+    flaps: Option<Vec<u64>>
+}
+
+/// Time spent on the menu without any key being pressed.
+#[derive(Resource)]
+struct MenuIdle(Timer);
+
+/// Starts a bot-flown run after the menu sits idle, and returns to the menu on any key.
+pub struct DemoPlugin;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MenuIdle(Timer::from_seconds(DEMO_IDLE_SECONDS, TimerMode::Once)))
+            .add_systems(OnEnter(GameState::Menu), |mut idle: ResMut<MenuIdle>| idle.0.reset())
+            .add_systems(Update, menu_idle_system.run_if(in_state(GameState::Menu)))
+            .add_systems(
+                FixedUpdate,
+                bot_system
+                    .after(ReplaySet)
+                    .before(update_bird_system)
+                    .run_if(in_state(GameState::Playing).and(resource_equals(GameMode::Demo)))
+            )
+            .add_systems(OnEnter(GameState::Playing), spawn_demo_banner.run_if(resource_equals(GameMode::Demo)))
+            .add_systems(
+                Update,
+                demo_exit_system.run_if(in_state(GameState::Playing).and(resource_equals(GameMode::Demo)))
+            )
+            .add_systems(OnEnter(GameState::GameOver), return_to_menu.run_if(resource_equals(GameMode::Demo)));
+    }
+}
+
+fn menu_idle_system(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut idle: ResMut<MenuIdle>,
+    mut game_mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>
+) {
+    if keys.get_just_pressed().next().is_some() {
+        idle.0.reset();
+        return;
+    }
+
+    if idle.0.tick(time.delta()).just_finished() {
+        *game_mode = GameMode::Demo;
+        next_state.set(GameState::Playing);
+    }
+}
+
+/// The next pipe pair as seen by the bot, in fixed ticks from now.
+struct UpcomingGap {
+    enter: f32,
+    exit: f32,
+    bottom: f32,
+    top: f32
+}
+
+/// Flaps whenever the bird falls below the next gap's center, as asked of the demo bot. A flap lifts
+/// the bird by nearly the whole height of a gap though, so on its own that clips a pipe every few
+/// gaps and the demo would keep ending. Each gap is planned for once as it comes within
+/// [`BOT_PLAN_HORIZON`] instead, and the bot only bobs about the center when the plan found nothing.
+fn bot_system(
+    mut bird_query: Query<(&Transform, &Bird, &mut AiControlled)>,
+    gate_query: Query<(Entity, &Transform, &Pipe, &ScoreGate)>,
+    tick: Res<FixedTick>,
+    config: Res<GameConfig>,
+    play_area: Res<PlayArea>,
+    mut flaps: EventWriter<FlapEvent>
+) {
+    // A flap always lifts the bird by the same height, so bobbing aims half of it below the target.
+    let jump_height = config.jump_force.powi(2) * FIXED_TICK_RATE as f32 / (2. * config.gravity.abs());
+
+    for (bird_transform, bird, mut bot) in bird_query.iter_mut() {
+        let bird_pos = bird_transform.translation;
+
+        // Gates stay until every bird has passed, so the closest one this bird hasn't is the next gap.
+        let next_gate = gate_query
+            .iter()
+            .filter(|(.., gate)| !gate.passed[bird.player])
+            .min_by(|(_, a, ..), (_, b, ..)| a.translation.x.total_cmp(&b.translation.x));
+
+        let flap = match next_gate {
+            Some((gate_entity, gate_transform, pipe, gate)) => {
+                let speed = pipe.velocity.x.abs().max(f32::EPSILON);
+                let distance = gate_transform.translation.x - bird_pos.x;
+                let gap = UpcomingGap {
+                    enter: (distance - (PIPE_WIDTH + BIRD_WIDTH) / 2.) / speed,
+                    exit: (distance + (PIPE_WIDTH + BIRD_WIDTH) / 2.) / speed,
+                    bottom: gate.gap_bottom,
+                    top: gate.gap_top
+                };
+
+                if bot.gate != Some(gate_entity) && gap.enter <= BOT_PLAN_HORIZON {
+                    let plan = plan_flaps(bird_pos.y, bird.velocity.y, &gap, &config, &play_area);
+                    bot.gate = Some(gate_entity);
+                    bot.flaps = plan.map(|plan| plan.into_iter().map(|offset| tick.0 + offset as u64).collect());
+                }
+
+                match &bot.flaps {
+                    Some(flaps) if bot.gate == Some(gate_entity) => flaps.contains(&tick.0),
+                    _ => bird.velocity.y <= 0. && bird_pos.y < (gap.bottom + gap.top - jump_height) / 2.
+                }
+            }
+            None => bird.velocity.y <= 0. && bird_pos.y < -jump_height / 2.
+        };
+
+        if flap {
+            flaps.send(FlapEvent { player: bird.player });
+        }
+    }
+}
+
+/// Looks for at most two flaps, in ticks from now, that carry the bird through `gap`.
+fn plan_flaps(y: f32, velocity: f32, gap: &UpcomingGap, config: &GameConfig, play_area: &PlayArea) -> Option<Vec<u32>> {
+    let survives = |flaps: &[u32]| survives_flaps(y, velocity, flaps, gap, config, play_area);
+    if survives(&[]) {
+        return Some(Vec::new());
+    }
+
+    let exit = gap.exit.max(0.) as u32;
+    for last in 0..=exit {
+        if survives(&[last]) {
+            return Some(vec![last]);
+        }
+
+        // The first flap only has to set up the second, so a coarser search is enough.
+        if let Some(first) = (0..last).step_by(2).find(|&first| survives(&[first, last])) {
+            return Some(vec![first, last]);
+        }
+    }
+
+    None
+}
+
+/// Simulates the bird flapping on the given ticks until it has cleared `gap`, mirroring the fixed-tick physics.
+fn survives_flaps(
+    mut y: f32,
+    mut velocity: f32,
+    flaps: &[u32],
+    gap: &UpcomingGap,
+    config: &GameConfig,
+    play_area: &PlayArea
+) -> bool {
+    let half_height = BIRD_HEIGHT / 2. + BOT_SAFETY_MARGIN;
+    let dt = 1. / FIXED_TICK_RATE as f32;
+
+    for tick in 0..=gap.exit.max(0.) as u32 {
+        velocity += config.gravity * dt;
+        y += velocity;
+        if flaps.contains(&tick) {
+            velocity = config.jump_force;
+        }
+
+        let elapsed = (tick + 1) as f32;
+        if y.abs() + half_height >= play_area.half_size().y {
+            return false;
+        }
+        if elapsed >= gap.enter && (y - half_height <= gap.bottom || y + half_height >= gap.top) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn spawn_demo_banner(mut commands: Commands, play_area: Res<PlayArea>) {
+    commands.spawn((
+        Text2d::new("DEMO\nPress any key"),
+        TextFont {
+            font_size: 20.,
+            ..default()
+        },
+        Transform::from_xyz(0., -play_area.size.y / 4., 0.5),
+        StateScoped(GameState::Playing)
+    ));
+}
+
+fn demo_exit_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.get_just_pressed().next().is_some() {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+/// A demo run has no results to show, so game over leads straight back to the menu.
+fn return_to_menu(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Menu);
+}
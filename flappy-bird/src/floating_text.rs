@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+const FLOATING_TEXT_DURATION: f32 = 1.;
+const FLOATING_TEXT_RISE_SPEED: f32 = 40.;
+
+/// Text that drifts upwards and fades out before despawning itself.
+#[derive(Component)]
+pub struct FloatingText {
+    timer: Timer,
+    color: Color
+}
+
+pub struct FloatingTextPlugin;
+
+impl Plugin for FloatingTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, floating_text_system);
+    }
+}
+
+pub fn spawn_floating_text(commands: &mut Commands, text: impl Into<String>, position: Vec2, color: Color) {
+    commands.spawn((
+        Text2d::new(text),
+        TextFont {
+            font_size: 16.,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_translation(position.extend(0.6)),
+        FloatingText {
+            timer: Timer::from_seconds(FLOATING_TEXT_DURATION, TimerMode::Once),
+            color
+        }
+    ));
+}
+
+fn floating_text_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut text_query: Query<(Entity, &mut FloatingText, &mut Transform, &mut TextColor)>
+) {
+    for (entity, mut floating_text, mut transform, mut text_color) in text_query.iter_mut() {
+        if floating_text.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += FLOATING_TEXT_RISE_SPEED * time.delta_secs();
+        text_color.0 = floating_text.color.with_alpha(floating_text.timer.fraction_remaining());
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, IoTaskPool, Task};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::replay::Playback;
+use crate::{best_score, GameMode, GameState, PlayArea, Score};
+
+const LEADERBOARD_SIZE: usize = 10;
+
+#[derive(Serialize, Deserialize)]
+struct LeaderboardEntry {
+    name: String,
+    score: u32,
+}
+
+type LeaderboardResult = Result<Vec<LeaderboardEntry>, String>;
+
+#[derive(Resource)]
+struct LeaderboardTask(Task<LeaderboardResult>);
+
+#[derive(Component)]
+struct LeaderboardText;
+
+/// Submits finished single-player runs to `GameConfig::leaderboard_url` and
+/// lists the global top scores on the game-over screen.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            submit_score
+                .run_if(resource_equals(GameMode::Single))
+                .run_if(not(resource_exists::<Playback>))
+        )
+        .add_systems(Update, poll_leaderboard_system.run_if(resource_exists::<LeaderboardTask>))
+        .add_systems(OnExit(GameState::GameOver), |mut commands: Commands| {
+            commands.remove_resource::<LeaderboardTask>();
+        });
+    }
+}
+
+fn submit_score(
+    mut commands: Commands,
+    score_query: Query<&Score>,
+    config: Res<GameConfig>,
+    play_area: Res<PlayArea>
+) {
+    let Some(url) = config.leaderboard_url.clone() else {
+        return;
+    };
+
+    let entry = LeaderboardEntry { name: config.player_name.clone(), score: best_score(&score_query) };
+    let task = IoTaskPool::get().spawn(async move { submit_and_fetch(&url, &entry) });
+    commands.insert_resource(LeaderboardTask(task));
+
+    commands.spawn((
+        Text2d::new("Loading leaderboard..."),
+        TextFont {
+            font_size: 14.,
+            ..default()
+        },
+        Transform::from_xyz(0., play_area.size.y / 8., 0.5),
+        LeaderboardText,
+        StateScoped(GameState::GameOver)
+    ));
+}
+
+fn submit_and_fetch(url: &str, entry: &LeaderboardEntry) -> LeaderboardResult {
+    let scores_url = format!("{}/scores", url.trim_end_matches('/'));
+
+    ureq::post(&scores_url)
+        .send_json(entry)
+        .map_err(|error| error.to_string())?;
+
+    ureq::get(&scores_url)
+        .query("limit", &LEADERBOARD_SIZE.to_string())
+        .call()
+        .map_err(|error| error.to_string())?
+        .into_json()
+        .map_err(|error| error.to_string())
+}
+
+fn poll_leaderboard_system(
+    mut commands: Commands,
+    mut task: ResMut<LeaderboardTask>,
+    mut text_query: Query<&mut Text2d, With<LeaderboardText>>
+) {
+    let Some(result) = block_on(future::poll_once(&mut task.0)) else {
+        return;
+    };
+
+    commands.remove_resource::<LeaderboardTask>();
+
+    let contents = match result {
+        Ok(entries) => std::iter::once("Top scores".to_string())
+            .chain(entries.iter().take(LEADERBOARD_SIZE).enumerate().map(|(rank, entry)| {
+                format!("{:>2}. {:<12} {:>4}", rank + 1, entry.name, entry.score)
+            }))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(error) => {
+            warn!("leaderboard request failed: {error}");
+            "Leaderboard unavailable".to_string()
+        }
+    };
+
+    for mut text in text_query.iter_mut() {
+        text.0 = contents.clone();
+    }
+}
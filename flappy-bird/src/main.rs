@@ -1,19 +1,89 @@
+mod achievements;
+mod audio;
+#[cfg(not(target_arch = "wasm32"))]
+mod capture;
+mod config;
+mod debug;
+mod demo;
+mod floating_text;
+#[cfg(feature = "leaderboard")]
+mod leaderboard;
+mod replay;
+mod settings;
+mod storage;
+mod wind;
+#[cfg(test)]
+mod tests;
+
+use achievements::AchievementsPlugin;
+use audio::GameAudioPlugin;
+use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
-use rand::Rng;
+use bevy::render::camera::{ClearColorConfig, ScalingMode, Viewport};
+use bevy::render::view::RenderLayers;
+use bevy::window::{MonitorSelection, PrimaryWindow, WindowMode, WindowResized};
+use config::{ConfigPlugin, GameConfig};
+use debug::DebugPlugin;
+use demo::{AiControlled, DemoPlugin};
+use floating_text::{spawn_floating_text, FloatingTextPlugin};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(not(target_arch = "wasm32"))]
+use replay::Recording;
+use replay::{Playback, Replay, ReplayPlugin, ReplaySet};
+use settings::{Settings, SettingsPlugin};
+use std::time::Duration;
+use wind::WindPlugin;
 
 const WINDOW_RESOLUTION: Vec2 = Vec2::new(288., 512.);
 
 const BIRD_WIDTH: f32 = 24.;
 const BIRD_HEIGHT: f32 = 32.;
-const GRAVITY: Vec2 = Vec2::new(0., -8.);
-const JUMP_FORCE: Vec2 = Vec2::new(0., 5.);
 const MIN_ROTATION: f32 = -std::f32::consts::FRAC_PI_3;
 const MAX_ROTATION: f32 = std::f32::consts::FRAC_PI_3;
 
 const PIPE_WIDTH: f32 = 52.;
 const PIPE_HEIGHT: f32 = 320.;
-const PIPE_SPAWN_INTERVAL: f32 = 2.0;
-const GAP_HEIGHT: f32 = 100.;
+
+const NEAR_MISS_DISTANCE: f32 = 6.;
+const NEAR_MISS_BONUS: u32 = 2;
+const NEAR_MISS_COLOR: Color = Color::srgb(1., 0.85, 0.2);
+
+const COMBO_MAX_FLAPS: u32 = 2;
+const COMBO_PIPES_PER_LEVEL: u32 = 3;
+const COMBO_MAX_MULTIPLIER: u32 = 5;
+const COMBO_PULSE_DURATION: f32 = 0.3;
+const COMBO_PULSE_SCALE: f32 = 0.6;
+
+const INVULNERABILITY_DURATION: f32 = 1.5;
+const INVULNERABILITY_BLINK_RATE: f32 = 10.;
+
+const PLAYER_COUNT: usize = 2;
+const PLAYER_TWO_FLAP_KEY: KeyCode = KeyCode::KeyW;
+
+const FIXED_TICK_RATE: f64 = 60.;
+
+const THEME_SWITCH_SCORE: u32 = 10;
+const THEME_FADE_DURATION: f32 = 1.5;
+
+struct Palette {
+    background: &'static str,
+    background_tint: Color,
+    pipe_tint: Color,
+}
+
+const PALETTES: [Palette; 2] = [
+    Palette {
+        background: "background.png",
+        background_tint: Color::WHITE,
+        pipe_tint: Color::WHITE,
+    },
+    Palette {
+        background: "background-night.png",
+        background_tint: Color::WHITE,
+        pipe_tint: Color::srgb(0.55, 0.6, 0.8),
+    },
+];
 
 struct Rect {
     min: Vec2,
@@ -38,16 +108,103 @@ impl Rect {
 
 #[derive(Component)]
 struct Bird {
-    velocity: Vec2
+    velocity: Vec2,
+    player: usize
 }
 
+/// Lets a respawned practice bird pass through pipes until the timer runs out.
+#[derive(Component)]
+struct Invulnerable(Timer);
+
 #[derive(Component)]
 struct Pipe {
     velocity: Vec2
 }
 
+/// Marks the lower pipe of a pair until every bird has passed it, tracking how
+/// close each player's bird came to the edges of the gap on the way through.
+#[derive(Component)]
+struct ScoreGate {
+    gap_bottom: f32,
+    gap_top: f32,
+    clearance: [f32; PLAYER_COUNT],
+    passed: [bool; PLAYER_COUNT]
+}
+
+impl ScoreGate {
+    fn new(gap_y: f32, gap_height: f32) -> Self {
+        Self {
+            gap_bottom: gap_y - gap_height / 2.,
+            gap_top: gap_y + gap_height / 2.,
+            clearance: [f32::INFINITY; PLAYER_COUNT],
+            passed: [false; PLAYER_COUNT]
+        }
+    }
+}
+
+/// A flap requested by a player's input or by replay playback; applied on the next fixed tick.
+#[derive(Event)]
+struct FlapEvent {
+    player: usize
+}
+
+/// Points earned by a bird; [`apply_score_system`] adds them to its [`Score`].
+#[derive(Event)]
+enum ScoreEvent {
+    PipePassed { bird: Entity, multiplier: u32 },
+    NearMiss { bird: Entity, position: Vec2 }
+}
+
+impl ScoreEvent {
+    fn bird(&self) -> Entity {
+        match self {
+            ScoreEvent::PipePassed { bird, .. } | ScoreEvent::NearMiss { bird, .. } => *bird
+        }
+    }
+
+    fn points(&self) -> u32 {
+        match self {
+            ScoreEvent::PipePassed { multiplier, .. } => *multiplier,
+            ScoreEvent::NearMiss { .. } => NEAR_MISS_BONUS
+        }
+    }
+}
+
+/// A bird touched a pipe or left the play area.
+#[derive(Event)]
+struct BirdHitEvent {
+    bird: Entity,
+    player: usize
+}
+
+/// Shows the score of the player's bird.
+#[derive(Component)]
+struct ScoreText(usize);
+
+/// Shows the player's combo multiplier beside their score and swells briefly whenever it goes up.
+#[derive(Component)]
+struct ComboText {
+    player: usize,
+    shown: u32,
+    pulse: Timer
+}
+
+#[derive(Component)]
+struct PlayerCamera(usize);
+
+/// Entities belonging to a single run, despawned when returning to the menu.
+#[derive(Component)]
+struct RunEntity;
+
+#[derive(Component)]
+struct Background;
+
+#[derive(Component)]
+struct BackgroundFade;
+
 #[derive(Resource)]
 struct GameTextures {
+    background: Handle<Image>,
     pipe: Handle<Image>,
     bird_down: Handle<Image>,
     bird_up: Handle<Image>
@@ -56,131 +213,760 @@ struct GameTextures {
 #[derive(Resource)]
 struct PipeTimer(Timer);
 
+#[derive(Resource)]
+struct PlayArea {
+    size: Vec2
+}
+
+impl PlayArea {
+    fn half_size(&self) -> Vec2 {
+        self.size / 2.
+    }
+}
+
+#[derive(Resource)]
+struct GameRng {
+    seed: u64,
+    rng: StdRng
+}
+
+impl GameRng {
+    fn from_seed(seed: u64) -> Self {
+        Self { seed, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// Moves on to the next run's seed, drawn from this one's so a whole session started from
+    /// `--seed` plays out the same, without depending on how far the run drew from its stream.
+    fn advance(&mut self) {
+        *self = Self::from_seed(StdRng::seed_from_u64(self.seed).random());
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default)]
+enum GameMode {
+    #[default]
+    Single,
+    Versus,
+    Practice,
+    Demo
+}
+
+impl GameMode {
+    fn player_count(self) -> usize {
+        match self {
+            GameMode::Single | GameMode::Practice | GameMode::Demo => 1,
+            GameMode::Versus => PLAYER_COUNT
+        }
+    }
+}
+
+/// Players whose bird crashed during the current run, in crash order.
+#[derive(Resource, Default)]
+struct Crashed(Vec<usize>);
+
+/// The most recently passed pipe, where practice birds respawn after a crash.
+#[derive(Resource, Default)]
+struct Checkpoint(Option<(Entity, f32)>);
+
+/// Pipes a bird cleared in a row with at most [`COMBO_MAX_FLAPS`] flaps each, and the flaps spent on the current gap.
+#[derive(Component, Default)]
+struct Combo {
+    streak: u32,
+    flaps: u32
+}
+
+impl Combo {
+    fn multiplier(&self) -> u32 {
+        (1 + self.streak / COMBO_PIPES_PER_LEVEL).min(COMBO_MAX_MULTIPLIER)
+    }
+}
+
+/// Number of fixed ticks simulated since the current run started.
+#[derive(Resource, Default)]
+struct FixedTick(u64);
+
+#[derive(Resource)]
+struct WindowedResolution(Vec2);
+
+#[derive(Resource, Default)]
+struct LoadingAssets(Vec<UntypedHandle>);
+
+#[derive(Component)]
+struct LoadingBarFill;
+
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+enum GameState {
+    #[default]
+    Loading,
+    Menu,
+    Achievements,
+    Settings,
+    Playing,
+    GameOver
+}
+
+/// Points a bird has earned during the current run.
+#[derive(Component, Default)]
+struct Score(u32);
+
+/// The highest score among the birds of the current run.
+fn best_score<'a>(scores: impl IntoIterator<Item = &'a Score>) -> u32 {
+    scores.into_iter().map(|score| score.0).max().unwrap_or(0)
+}
+
+#[derive(Resource)]
+struct HighScore(u32);
+
+#[derive(Resource)]
+struct Theme {
+    current: usize,
+    previous: usize,
+    fade: Option<Timer>
+}
+
+impl Theme {
+    fn palette(&self) -> &'static Palette {
+        &PALETTES[self.current]
+    }
+}
+
 fn main() {
-    App::new()
+    #[cfg(not(target_arch = "wasm32"))]
+    let playback = arg_value("--replay").and_then(|path| Replay::load(&path));
+    #[cfg(target_arch = "wasm32")]
+    let playback: Option<Replay> = None;
+    let seed = match &playback {
+        Some(replay) => replay.seed,
+        None => arg_value("--seed").and_then(|seed| seed.parse().ok()).unwrap_or_else(rand::random)
+    };
+
+    let mut profile = storage::load_profile();
+    if let Some(replay) = &playback {
+        profile.settings.difficulty = replay.difficulty;
+    }
+
+    let mut app = App::new();
+
+    if let Some(replay) = playback {
+        app.insert_resource(Playback(replay));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = arg_value("--record") {
+        app.insert_resource(Recording::new(path));
+    }
+
+    app
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         title: "Flappy Bird".into(), 
                         resolution: WINDOW_RESOLUTION.into(),
-                        resizable: false,
+                        resizable: true,
+                        canvas: Some("#bevy".into()),
+                        fit_canvas_to_parent: true,
+                        prevent_default_event_handling: false,
                         ..default()
                     }),
                     ..default()
                 })
+                .set(AssetPlugin {
+                    meta_check: AssetMetaCheck::Never,
+                    ..default()
+                })
                 .set(ImagePlugin::default_nearest())
         )
+        .add_plugins((
+            ConfigPlugin,
+            ReplayPlugin,
+            GameplayPlugin,
+            DebugPlugin,
+            FloatingTextPlugin,
+            AchievementsPlugin,
+            GameAudioPlugin,
+            SettingsPlugin,
+            WindPlugin,
+            DemoPlugin
+        ))
+        .enable_state_scoped_entities::<GameState>()
+        .insert_resource(ClearColor(Color::BLACK))
+        .insert_resource(PlayArea { size: WINDOW_RESOLUTION })
+        .insert_resource(WindowedResolution(WINDOW_RESOLUTION))
+        .insert_resource(GameRng::from_seed(seed))
+        .insert_resource(HighScore(profile.high_score))
+        .insert_resource(profile.settings)
+        .init_resource::<LoadingAssets>()
         .add_systems(Startup, setup)
-        .add_systems(Update, 
+        .add_systems(Update, (fullscreen_toggle_system, letterbox_system))
+        .add_systems(OnEnter(GameState::Loading), spawn_loading_screen)
+        .add_systems(Update, loading_system.run_if(in_state(GameState::Loading)))
+        .add_systems(OnExit(GameState::Loading), spawn_world)
+        .add_systems(OnEnter(GameState::Menu), spawn_menu)
+        .add_systems(Update, menu_input_system.run_if(in_state(GameState::Menu)))
+        .add_systems(OnEnter(GameState::Playing), spawn_players)
+        .add_systems(
+            OnEnter(GameState::GameOver),
             (
-                update_bird_system, 
-                input_system, 
-                spawn_pipes_system, 
-                move_pipes_system,
-                despawn_pipes_system,
-                bird_collision_system
+                save_high_score.run_if(resource_equals(GameMode::Single)),
+                spawn_game_over_screen.run_if(not(resource_equals(GameMode::Demo)))
             )
         )
-        .run();
+        .add_systems(Update, game_over_input_system.run_if(in_state(GameState::GameOver)))
+        .add_systems(OnExit(GameState::GameOver), reset_run)
+        .add_systems(Update, 
+            (
+                input_system.run_if(not(resource_exists::<Playback>).and(not(resource_equals(GameMode::Demo)))),
+                score_text_system,
+                combo_text_system,
+                near_miss_popup_system,
+                theme_system,
+                (theme_fade_system, pipe_tint_system).chain(),
+                invulnerability_blink_system
+            ).run_if(in_state(GameState::Playing))
+        )
+        .add_systems(
+            Update,
+            practice_exit_system.run_if(in_state(GameState::Playing).and(resource_equals(GameMode::Practice)))
+        );
+
+    #[cfg(feature = "leaderboard")]
+    app.add_plugins(leaderboard::LeaderboardPlugin);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    app.add_plugins(capture::CapturePlugin);
+
+    app.run();
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let background = asset_server.load("background.png");
-    let pipe = asset_server.load("pipe.png");
-    let bird_down = asset_server.load("bird-down.png");
-    let bird_up = asset_server.load("bird-up.png");
+/// Simulation resources and fixed-tick systems, kept free of windowing and
+/// rendering so the game can be driven headlessly.
+struct GameplayPlugin;
 
-    commands.insert_resource(GameTextures {
-        pipe: pipe.clone(),
-        bird_down: bird_down.clone(),
-        bird_up: bird_up.clone()
-    });
-    
-    commands.insert_resource(PipeTimer(Timer::from_seconds(PIPE_SPAWN_INTERVAL, TimerMode::Repeating)));
-    
-    commands.spawn(Camera2d);
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .insert_resource(Time::<Fixed>::from_hz(FIXED_TICK_RATE))
+            .init_resource::<FixedTick>()
+            .init_resource::<GameMode>()
+            .init_resource::<Settings>()
+            .init_resource::<Crashed>()
+            .init_resource::<Checkpoint>()
+            .add_event::<FlapEvent>()
+            .add_event::<ScoreEvent>()
+            .add_event::<BirdHitEvent>()
+            .insert_resource(Theme { current: 0, previous: 0, fade: None })
+            .add_systems(OnEnter(GameState::Playing), start_run)
+            .add_systems(FixedUpdate, 
+                (
+                    update_bird_system, 
+                    flap_system, 
+                    combo_flap_system,
+                    spawn_pipes_system, 
+                    move_pipes_system,
+                    despawn_pipes_system,
+                    bird_collision_system,
+                    crash_system.run_if(not(resource_equals(GameMode::Practice))),
+                    practice_respawn_system.run_if(resource_equals(GameMode::Practice)),
+                    combo_break_system,
+                    near_miss_system,
+                    score_system,
+                    apply_score_system,
+                    invulnerability_system,
+                    advance_tick_system
+                ).chain().after(ReplaySet).run_if(in_state(GameState::Playing))
+            )
+            .add_systems(Update, pipe_timer_config_system.run_if(in_state(GameState::Playing)));
+    }
+}
+
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    play_area: Res<PlayArea>,
+    game_rng: Res<GameRng>,
+    mut loading_assets: ResMut<LoadingAssets>
+) {
+    info!("pipe layout seed: {}", game_rng.seed);
+
+    let game_textures = GameTextures {
+        background: asset_server.load("background.png"),
+        pipe: asset_server.load("pipe.png"),
+        bird_down: asset_server.load("bird-down.png"),
+        bird_up: asset_server.load("bird-up.png")
+    };
+
+    loading_assets.0.extend([
+        game_textures.background.clone().untyped(),
+        game_textures.pipe.clone().untyped(),
+        game_textures.bird_down.clone().untyped(),
+        game_textures.bird_up.clone().untyped()
+    ]);
+    // Loaded up front so the first cross-fade to another palette has its background to show.
+    loading_assets.0.extend(PALETTES.iter().map(|palette| asset_server.load::<Image>(palette.background).untyped()));
+
+    commands.insert_resource(game_textures);
     
+    spawn_player_camera(&mut commands, &play_area, 0);
+}
+
+fn spawn_player_camera(commands: &mut Commands, play_area: &PlayArea, player: usize) -> Entity {
+    commands
+        .spawn((
+            Camera2d,
+            Camera {
+                order: player as isize,
+                clear_color: if player == 0 { ClearColorConfig::Default } else { ClearColorConfig::None },
+                ..default()
+            },
+            OrthographicProjection {
+                scaling_mode: ScalingMode::Fixed {
+                    width: play_area.size.x,
+                    height: play_area.size.y
+                },
+                ..OrthographicProjection::default_2d()
+            },
+            RenderLayers::from_layers(&[0, player_layer(player)]),
+            PlayerCamera(player)
+        ))
+        .id()
+}
+
+/// Render layer holding the entities only `player`'s camera should see.
+fn player_layer(player: usize) -> usize {
+    player + 1
+}
+
+fn fullscreen_toggle_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut windowed_resolution: ResMut<WindowedResolution>
+) {
+    let alt_enter = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) && keys.just_pressed(KeyCode::Enter);
+    if !keys.just_pressed(KeyCode::F11) && !alt_enter {
+        return;
+    }
+
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+
+    if window.mode == WindowMode::Windowed {
+        windowed_resolution.0 = window.resolution.size();
+        window.mode = WindowMode::BorderlessFullscreen(MonitorSelection::Current);
+    } else {
+        window.mode = WindowMode::Windowed;
+        window.resolution.set(windowed_resolution.0.x, windowed_resolution.0.y);
+    }
+}
+
+/// Splits the window into one column per player camera and letterboxes the
+/// play area inside each column.
+fn letterbox_system(
+    mut resize_events: EventReader<WindowResized>,
+    added_cameras: Query<(), Added<PlayerCamera>>,
+    mut removed_cameras: RemovedComponents<PlayerCamera>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    play_area: Res<PlayArea>,
+    mut camera_query: Query<(&mut Camera, &PlayerCamera)>
+) {
+    let resized = resize_events.read().last().is_some();
+    let removed = removed_cameras.read().last().is_some();
+    if !resized && !removed && added_cameras.is_empty() {
+        return;
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let columns = camera_query.iter().count().max(1) as f32;
+    let column_size = window.physical_size().as_vec2() / Vec2::new(columns, 1.);
+    let scale = (column_size / play_area.size).min_element();
+    let viewport_size = (play_area.size * scale).max(Vec2::ONE);
+
+    for (mut camera, player_camera) in camera_query.iter_mut() {
+        let column_offset = Vec2::new(column_size.x * player_camera.0 as f32, 0.);
+        let viewport_position = column_offset + (column_size - viewport_size) / 2.;
+
+        camera.viewport = Some(Viewport {
+            physical_position: viewport_position.as_uvec2(),
+            physical_size: viewport_size.as_uvec2(),
+            ..default()
+        });
+    }
+}
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.),
+                ..default()
+            },
+            StateScoped(GameState::Loading)
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Loading..."),
+                TextFont {
+                    font_size: 20.,
+                    ..default()
+                }
+            ));
+
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(200.),
+                        height: Val::Px(12.),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.2, 0.2, 0.2))
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            width: Val::Percent(0.),
+                            height: Val::Percent(100.),
+                            ..default()
+                        },
+                        BackgroundColor(Color::WHITE),
+                        LoadingBarFill
+                    ));
+                });
+        });
+}
+
+fn loading_system(
+    asset_server: Res<AssetServer>,
+    loading_assets: Res<LoadingAssets>,
+    mut fill_query: Query<&mut Node, With<LoadingBarFill>>,
+    playback: Option<Res<Playback>>,
+    mut next_state: ResMut<NextState<GameState>>
+) {
+    let loaded = loading_assets.0
+        .iter()
+        .filter(|handle| asset_server.is_loaded_with_dependencies(handle.id()))
+        .count();
+    let progress = loaded as f32 / loading_assets.0.len().max(1) as f32;
+
+    for mut fill in fill_query.iter_mut() {
+        fill.width = Val::Percent(progress * 100.);
+    }
+
+    if loaded == loading_assets.0.len() {
+        next_state.set(if playback.is_some() { GameState::Playing } else { GameState::Menu });
+    }
+}
+
+fn spawn_world(mut commands: Commands, game_textures: Res<GameTextures>) {
     commands.spawn((
-        Sprite::from_image(background),
-        Transform::from_xyz(0., 0., 0.)
+        Sprite::from_image(game_textures.background.clone()),
+        Transform::from_xyz(0., 0., 0.),
+        Background
     ));
 
     commands.spawn((
-        Sprite::from_image(bird_down),
-        Transform::from_xyz(0., 0., 0.1),
-        Bird { velocity: Vec2::new(0., 0.) }
+        Sprite {
+            image: game_textures.background.clone(),
+            color: Color::NONE,
+            ..default()
+        },
+        Transform::from_xyz(0., 0., 0.05),
+        BackgroundFade
+    ));
+}
+
+fn spawn_players(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    game_mode: Res<GameMode>,
+    play_area: Res<PlayArea>
+) {
+    for player in 0..game_mode.player_count() {
+        let bird = commands.spawn((
+            Sprite::from_image(game_textures.bird_down.clone()),
+            Transform::from_xyz(0., 0., 0.1),
+            Bird { velocity: Vec2::ZERO, player },
+            Score::default(),
+            Combo::default(),
+            RenderLayers::layer(player_layer(player)),
+            RunEntity
+        )).id();
+
+        commands.spawn((
+            Text2d::new("0"),
+            TextFont {
+                font_size: 40.,
+                ..default()
+            },
+            Transform::from_xyz(0., play_area.half_size().y - 50., 0.5),
+            ScoreText(player),
+            RenderLayers::layer(player_layer(player)),
+            RunEntity
+        ));
+
+        commands.spawn((
+            Text2d::default(),
+            TextFont {
+                font_size: 20.,
+                ..default()
+            },
+            TextColor(NEAR_MISS_COLOR),
+            Transform::from_xyz(60., play_area.half_size().y - 50., 0.5),
+            ComboText {
+                player,
+                shown: 1,
+                pulse: Timer::from_seconds(COMBO_PULSE_DURATION, TimerMode::Once)
+            },
+            RenderLayers::layer(player_layer(player)),
+            RunEntity
+        ));
+
+        if *game_mode == GameMode::Demo {
+            commands.entity(bird).insert(AiControlled::default());
+        }
+
+        if *game_mode == GameMode::Versus {
+            commands.spawn((
+                Text2d::new(format!("P{}", player + 1)),
+                TextFont {
+                    font_size: 20.,
+                    ..default()
+                },
+                Transform::from_xyz(-play_area.half_size().x + 20., play_area.half_size().y - 20., 0.5),
+                RenderLayers::layer(player_layer(player)),
+                RunEntity
+            ));
+
+            if player > 0 {
+                let camera = spawn_player_camera(&mut commands, &play_area, player);
+                commands.entity(camera).insert(RunEntity);
+            }
+        }
+    }
+}
+
+fn spawn_menu(mut commands: Commands, high_score: Res<HighScore>, play_area: Res<PlayArea>) {
+    commands.spawn((
+        Text2d::new(format!("Best: {}\nPress Space to start\nPress V for a 2P race\nPress P for practice\nPress A for achievements\nPress S for settings", high_score.0)),
+        TextFont {
+            font_size: 20.,
+            ..default()
+        },
+        Transform::from_xyz(0., -play_area.size.y / 4., 0.5),
+        StateScoped(GameState::Menu)
     ));
 }
 
-fn input_system(
+fn menu_input_system(
     keys: Res<ButtonInput<KeyCode>>,
-    mut bird_query: Query<(&mut Bird, &mut Sprite)>,
-    game_textures: Res<GameTextures>
+    mut game_mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        *game_mode = GameMode::Single;
+        next_state.set(GameState::Playing);
+    } else if keys.just_pressed(KeyCode::KeyV) {
+        *game_mode = GameMode::Versus;
+        next_state.set(GameState::Playing);
+    } else if keys.just_pressed(KeyCode::KeyP) {
+        *game_mode = GameMode::Practice;
+        next_state.set(GameState::Playing);
+    } else if keys.just_pressed(KeyCode::KeyA) {
+        next_state.set(GameState::Achievements);
+    } else if keys.just_pressed(KeyCode::KeyS) {
+        next_state.set(GameState::Settings);
+    }
+}
+
+fn save_high_score(score_query: Query<&Score>, settings: Res<Settings>, mut high_score: ResMut<HighScore>) {
+    let score = best_score(&score_query);
+    if score > high_score.0 {
+        high_score.0 = score;
+        storage::save_profile(high_score.0, *settings);
+    }
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    score_query: Query<(&Bird, &Score)>,
+    high_score: Res<HighScore>,
+    game_mode: Res<GameMode>,
+    crashed: Res<Crashed>,
+    play_area: Res<PlayArea>
 ) {
-    let Ok((mut bird, mut bird_sprite)) = bird_query.get_single_mut() else { 
-        return; 
+    let headline = match *game_mode {
+        GameMode::Single | GameMode::Demo => "Game Over".to_string(),
+        GameMode::Practice => "Practice Over".to_string(),
+        GameMode::Versus => match (0..PLAYER_COUNT).find(|player| !crashed.0.contains(player)) {
+            Some(winner) => format!("Player {} wins!", winner + 1),
+            None => "Draw!".to_string()
+        }
     };
 
+    let mut scores: Vec<(usize, u32)> = score_query.iter().map(|(bird, score)| (bird.player, score.0)).collect();
+    scores.sort_unstable();
+    let scores = scores.iter().map(|(_, score)| score.to_string()).collect::<Vec<_>>().join(" - ");
+
+    commands.spawn((
+        Text2d::new(format!("{headline}\nScore: {scores}\nBest: {}\nPress Space", high_score.0)),
+        TextFont {
+            font_size: 20.,
+            ..default()
+        },
+        Transform::from_xyz(0., -play_area.size.y / 4., 0.5),
+        StateScoped(GameState::GameOver)
+    ));
+}
+
+fn game_over_input_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
     if keys.just_pressed(KeyCode::Space) {
-        bird.velocity.y = JUMP_FORCE.y;
-        bird_sprite.image = game_textures.bird_up.clone();
+        next_state.set(GameState::Menu);
     }
 }
 
+fn reset_run(
+    mut commands: Commands,
+    run_query: Query<Entity, With<RunEntity>>,
+    mut crashed: ResMut<Crashed>,
+    mut game_rng: ResMut<GameRng>
+) {
+    for entity in run_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    crashed.0.clear();
+    game_rng.advance();
+}
+
+fn start_run(
+    mut commands: Commands,
+    mut tick: ResMut<FixedTick>,
+    mut flaps: ResMut<Events<FlapEvent>>,
+    mut checkpoint: ResMut<Checkpoint>,
+    config: Res<GameConfig>
+) {
+    tick.0 = 0;
+    flaps.clear();
+    checkpoint.0 = None;
+    commands.insert_resource(PipeTimer(Timer::from_seconds(config.pipe_spawn_interval, TimerMode::Repeating)));
+}
+
+fn input_system(keys: Res<ButtonInput<KeyCode>>, settings: Res<Settings>, mut flaps: EventWriter<FlapEvent>) {
+    for (player, key) in [settings.flap_key, PLAYER_TWO_FLAP_KEY].into_iter().enumerate() {
+        if keys.just_pressed(key) {
+            flaps.send(FlapEvent { player });
+        }
+    }
+}
+
+fn flap_system(
+    mut flaps: EventReader<FlapEvent>,
+    mut bird_query: Query<(&mut Bird, &mut Sprite)>,
+    game_textures: Res<GameTextures>,
+    config: Res<GameConfig>
+) {
+    let mut flapping = [false; PLAYER_COUNT];
+    for flap in flaps.read() {
+        flapping[flap.player] = true;
+    }
+
+    for (mut bird, mut bird_sprite) in bird_query.iter_mut() {
+        if flapping[bird.player] {
+            bird.velocity.y = config.jump_force;
+            bird_sprite.image = game_textures.bird_up.clone();
+        }
+    }
+}
+
+fn advance_tick_system(mut tick: ResMut<FixedTick>) {
+    tick.0 += 1;
+}
+
 fn update_bird_system(
     time: Res<Time>,
     mut bird_query: Query<(&mut Bird, &mut Sprite, &mut Transform)>,
-    game_textures: Res<GameTextures>
+    game_textures: Res<GameTextures>,
+    config: Res<GameConfig>
 ) {
     let dt = time.delta_secs();
 
-    let Ok((mut bird, mut bird_sprite, mut bird_transform)) = bird_query.get_single_mut() else { 
-        return; 
-    };
-
-    bird.velocity += GRAVITY * dt;
-    bird_transform.translation += bird.velocity.extend(0.);
-    bird_sprite.image = game_textures.bird_down.clone();
-    
-    let tilt_angle = bird.velocity.y * 0.05;
-    let clamped_angle = tilt_angle.clamp(MIN_ROTATION, MAX_ROTATION);
-    bird_transform.rotation = Quat::from_rotation_z(clamped_angle);
+    for (mut bird, mut bird_sprite, mut bird_transform) in bird_query.iter_mut() {
+        bird.velocity.y += config.gravity * dt;
+        bird_transform.translation += bird.velocity.extend(0.);
+        bird_sprite.image = game_textures.bird_down.clone();
+        
+        let tilt_angle = bird.velocity.y * 0.05;
+        let clamped_angle = tilt_angle.clamp(MIN_ROTATION, MAX_ROTATION);
+        bird_transform.rotation = Quat::from_rotation_z(clamped_angle);
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_pipes_system(
     mut commands: Commands,
     time: Res<Time>,
     mut pipe_timer: ResMut<PipeTimer>,
-    game_textures: Res<GameTextures>
+    game_textures: Res<GameTextures>,
+    theme: Res<Theme>,
+    config: Res<GameConfig>,
+    play_area: Res<PlayArea>,
+    settings: Res<Settings>,
+    mut game_rng: ResMut<GameRng>
 ) {
     if pipe_timer.0.tick(time.delta()).just_finished() {
-        let mut rng = rand::rng();
-
-        let gap_y = rng.random_range(-100.0 .. 100.0);
+        let gap_y = game_rng.rng.random_range(-config.gap_range .. config.gap_range);
+        let gap_height = config.gap_height * settings.difficulty.gap_scale();
+        let pipe_velocity = Vec2::new(config.pipe_speed * settings.difficulty.speed_scale(), 0.);
         
-        let pipe_x = WINDOW_RESOLUTION.x / 2. + PIPE_WIDTH / 2. + 200.;
-        let inf_pipe_y = gap_y - GAP_HEIGHT / 2. - PIPE_HEIGHT / 2.;
-        let sup_pipe_y = gap_y + GAP_HEIGHT / 2. + PIPE_HEIGHT / 2.;
+        let pipe_x = play_area.half_size().x + PIPE_WIDTH / 2. + 200.;
+        let inf_pipe_y = gap_y - gap_height / 2. - PIPE_HEIGHT / 2.;
+        let sup_pipe_y = gap_y + gap_height / 2. + PIPE_HEIGHT / 2.;
+
+        let pipe_sprite = Sprite {
+            image: game_textures.pipe.clone(),
+            color: theme.palette().pipe_tint,
+            ..default()
+        };
 
         commands.spawn((
-            Sprite::from_image(game_textures.pipe.clone()),
+            pipe_sprite.clone(),
             Transform::from_xyz(pipe_x, inf_pipe_y, 0.1),
-            Pipe { velocity: Vec2::new(-3., 0.) }
+            Pipe { velocity: pipe_velocity },
+            ScoreGate::new(gap_y, gap_height),
+            RunEntity
         ));
         
         commands.spawn((
-            Sprite::from_image(game_textures.pipe.clone()),
+            pipe_sprite,
             Transform {
                 translation: Vec3::new(pipe_x, sup_pipe_y, 0.1),
                 rotation: Quat::from_rotation_z(std::f32::consts::PI),
                 ..default()
             },
-            Pipe { velocity: Vec2::new(-3., 0.) }
+            Pipe { velocity: pipe_velocity },
+            RunEntity
         ));
     }
 }
 
+fn pipe_timer_config_system(config: Res<GameConfig>, mut pipe_timer: ResMut<PipeTimer>) {
+    if config.is_changed() {
+        pipe_timer.0.set_duration(std::time::Duration::from_secs_f32(config.pipe_spawn_interval));
+    }
+}
+
 fn move_pipes_system(mut pipe_query: Query<(&mut Transform, &Pipe)>) {
     for (mut pipe_transform, pipe) in pipe_query.iter_mut() {
         pipe_transform.translation += pipe.velocity.extend(0.);
@@ -190,39 +976,328 @@ fn move_pipes_system(mut pipe_query: Query<(&mut Transform, &Pipe)>) {
 fn despawn_pipes_system(
     mut commands: Commands,
     pipe_query: Query<(Entity, &Transform), With<Pipe>>,
+    play_area: Res<PlayArea>
 ) {
     for (entity, transform) in pipe_query.iter() {
-        if transform.translation.x < -WINDOW_RESOLUTION.x / 2. - PIPE_WIDTH / 2. {
+        if transform.translation.x < -play_area.half_size().x - PIPE_WIDTH / 2. {
             commands.entity(entity).despawn();
         }
     }
 }
 
 fn bird_collision_system(
-    bird_query: Query<&Transform, With<Bird>>,
+    bird_query: Query<(Entity, &Transform, &Bird), Without<Invulnerable>>,
     pipe_query: Query<&Transform, With<Pipe>>,
-    mut exit: EventWriter<AppExit>
+    play_area: Res<PlayArea>,
+    mut hits: EventWriter<BirdHitEvent>
 ) {
-    let Ok(bird_transform) = bird_query.get_single() else {
-        return;
-    };
-    
     let bird_size = Vec2::new(BIRD_WIDTH, BIRD_HEIGHT);
-    let bird_pos = bird_transform.translation.truncate();
-    let bird_rect = Rect::from_center_size(bird_pos, bird_size);
 
-    for pipe_transform in pipe_query.iter() {
-        let pipe_rect = {
+    for (entity, bird_transform, bird) in bird_query.iter() {
+        let bird_pos = bird_transform.translation.truncate();
+        let bird_rect = Rect::from_center_size(bird_pos, bird_size);
+
+        let hit_pipe = pipe_query.iter().any(|pipe_transform| {
             let pipe_size = Vec2::new(PIPE_WIDTH, PIPE_HEIGHT);
             let pipe_pos = pipe_transform.translation.truncate();
-            Rect::from_center_size(pipe_pos, pipe_size)
+            bird_rect.overlaps(&Rect::from_center_size(pipe_pos, pipe_size))
+        });
+
+        let out_of_bounds = bird_pos.y - bird_size.y / 2. <= -play_area.half_size().y
+            || bird_pos.y + bird_size.y / 2. >= play_area.half_size().y;
+
+        if hit_pipe || out_of_bounds {
+            hits.send(BirdHitEvent { bird: entity, player: bird.player });
+        }
+    }
+}
+
+fn crash_system(
+    mut hits: EventReader<BirdHitEvent>,
+    mut crashed: ResMut<Crashed>,
+    mut next_state: ResMut<NextState<GameState>>
+) {
+    for hit in hits.read() {
+        crashed.0.push(hit.player);
+        next_state.set(GameState::GameOver);
+    }
+}
+
+/// Rewinds the pipes so a crashed practice bird restarts just past the last pipe it cleared.
+fn practice_respawn_system(
+    mut commands: Commands,
+    mut hits: EventReader<BirdHitEvent>,
+    mut bird_query: Query<(&mut Transform, &mut Bird)>,
+    mut pipe_query: Query<(&mut Transform, &Pipe), Without<Bird>>,
+    checkpoint: Res<Checkpoint>,
+    mut pipe_timer: ResMut<PipeTimer>
+) {
+    for hit in hits.read() {
+        let Ok((mut bird_transform, mut bird)) = bird_query.get_mut(hit.bird) else {
+            continue;
+        };
+
+        let mut respawn_y = 0.;
+
+        if let Some((checkpoint_pipe, gap_y)) = checkpoint.0 {
+            let checkpoint_pipe = pipe_query
+                .get(checkpoint_pipe)
+                .map(|(transform, pipe)| (transform.translation.x, pipe.velocity.x))
+                .ok();
+
+            if let Some((checkpoint_x, pipe_speed)) = checkpoint_pipe {
+                let target_x = bird_transform.translation.x - (BIRD_WIDTH + PIPE_WIDTH) / 2. - 1.;
+                let shift = target_x - checkpoint_x;
+                let rewound_ticks = shift / pipe_speed.abs().max(f32::EPSILON);
+
+                for (mut pipe_transform, _) in pipe_query.iter_mut() {
+                    pipe_transform.translation.x += shift;
+                }
+
+                // Hold back the next spawn by as long as the pipes were rewound to keep the spacing even.
+                let rewound = Duration::from_secs_f32(rewound_ticks / FIXED_TICK_RATE as f32);
+                let elapsed = pipe_timer.0.elapsed().saturating_sub(rewound);
+                pipe_timer.0.set_elapsed(elapsed);
+            }
+
+            respawn_y = gap_y;
+        }
+
+        bird_transform.translation.y = respawn_y;
+        bird.velocity = Vec2::ZERO;
+
+        commands.entity(hit.bird)
+            .insert(Invulnerable(Timer::from_seconds(INVULNERABILITY_DURATION, TimerMode::Once)));
+    }
+}
+
+fn invulnerability_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bird_query: Query<(Entity, &mut Invulnerable)>
+) {
+    for (entity, mut invulnerable) in bird_query.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+fn invulnerability_blink_system(
+    mut bird_query: Query<(&mut Visibility, Option<&Invulnerable>), With<Bird>>
+) {
+    for (mut visibility, invulnerable) in bird_query.iter_mut() {
+        *visibility = match invulnerable {
+            Some(invulnerable) if (invulnerable.0.elapsed_secs() * INVULNERABILITY_BLINK_RATE) as u32 % 2 == 1 => Visibility::Hidden,
+            _ => Visibility::Inherited
         };
+    }
+}
+
+fn practice_exit_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn near_miss_system(
+    bird_query: Query<(&Transform, &Bird)>,
+    mut gate_query: Query<(&Transform, &mut ScoreGate)>
+) {
+    for (bird_transform, bird) in bird_query.iter() {
+        let bird_pos = bird_transform.translation.truncate();
+
+        for (gate_transform, mut gate) in gate_query.iter_mut() {
+            if (gate_transform.translation.x - bird_pos.x).abs() > (PIPE_WIDTH + BIRD_WIDTH) / 2. {
+                continue;
+            }
+
+            let clearance = (bird_pos.y - BIRD_HEIGHT / 2. - gate.gap_bottom)
+                .min(gate.gap_top - bird_pos.y - BIRD_HEIGHT / 2.);
+            gate.clearance[bird.player] = gate.clearance[bird.player].min(clearance);
+        }
+    }
+}
+
+/// Credits each bird with the gates it has cleared, dropping a gate once every bird is past it.
+fn score_system(
+    mut commands: Commands,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut bird_query: Query<(Entity, &Transform, &Bird, &mut Combo)>,
+    mut gate_query: Query<(Entity, &Transform, &mut ScoreGate)>
+) {
+    for (entity, gate_transform, mut gate) in gate_query.iter_mut() {
+        let gate_right = gate_transform.translation.x + PIPE_WIDTH / 2.;
+
+        for (bird_entity, bird_transform, bird, mut combo) in bird_query.iter_mut() {
+            if gate.passed[bird.player] || gate_right >= bird_transform.translation.x - BIRD_WIDTH / 2. {
+                continue;
+            }
+
+            gate.passed[bird.player] = true;
+            if combo.flaps <= COMBO_MAX_FLAPS {
+                combo.streak += 1;
+            }
+            combo.flaps = 0;
 
-        if bird_rect.overlaps(&pipe_rect) ||
-           bird_pos.y - bird_size.y / 2. <= -WINDOW_RESOLUTION.y / 2. || 
-           bird_pos.y + bird_size.y / 2. >= WINDOW_RESOLUTION.y / 2.
-        {
-            exit.send(AppExit::Success);
+            score_events.send(ScoreEvent::PipePassed { bird: bird_entity, multiplier: combo.multiplier() });
+            checkpoint.0 = Some((entity, (gate.gap_bottom + gate.gap_top) / 2.));
+
+            if (0. ..NEAR_MISS_DISTANCE).contains(&gate.clearance[bird.player]) {
+                score_events.send(ScoreEvent::NearMiss {
+                    bird: bird_entity,
+                    position: bird_transform.translation.truncate()
+                });
+            }
+        }
+
+        if bird_query.iter().all(|(_, _, bird, _)| gate.passed[bird.player]) {
+            commands.entity(entity).remove::<ScoreGate>();
         }
     }
 }
+
+fn combo_flap_system(mut flaps: EventReader<FlapEvent>, mut bird_query: Query<(&Bird, &mut Combo)>) {
+    for flap in flaps.read() {
+        for (bird, mut combo) in bird_query.iter_mut() {
+            if bird.player != flap.player {
+                continue;
+            }
+
+            combo.flaps += 1;
+            if combo.flaps > COMBO_MAX_FLAPS {
+                combo.streak = 0;
+            }
+        }
+    }
+}
+
+fn combo_break_system(mut hits: EventReader<BirdHitEvent>, mut combo_query: Query<&mut Combo>) {
+    for hit in hits.read() {
+        if let Ok(mut combo) = combo_query.get_mut(hit.bird) {
+            *combo = Combo::default();
+        }
+    }
+}
+
+fn apply_score_system(mut score_events: EventReader<ScoreEvent>, mut score_query: Query<&mut Score>) {
+    for score_event in score_events.read() {
+        if let Ok(mut score) = score_query.get_mut(score_event.bird()) {
+            score.0 += score_event.points();
+        }
+    }
+}
+
+fn near_miss_popup_system(mut commands: Commands, mut score_events: EventReader<ScoreEvent>) {
+    for score_event in score_events.read() {
+        if let ScoreEvent::NearMiss { position, .. } = score_event {
+            spawn_floating_text(
+                &mut commands,
+                format!("+{NEAR_MISS_BONUS} Close!"),
+                *position + Vec2::Y * BIRD_HEIGHT,
+                NEAR_MISS_COLOR
+            );
+        }
+    }
+}
+
+fn combo_text_system(
+    time: Res<Time>,
+    combo_query: Query<(&Bird, &Combo)>,
+    mut text_query: Query<(&mut Text2d, &mut ComboText, &mut Transform)>
+) {
+    for (mut text, mut combo_text, mut transform) in text_query.iter_mut() {
+        let multiplier = combo_query
+            .iter()
+            .find(|(bird, _)| bird.player == combo_text.player)
+            .map_or(1, |(_, combo)| combo.multiplier());
+
+        if multiplier != combo_text.shown {
+            if multiplier > combo_text.shown {
+                combo_text.pulse.reset();
+            }
+            combo_text.shown = multiplier;
+            text.0 = if multiplier > 1 { format!("x{multiplier}") } else { String::new() };
+        }
+
+        combo_text.pulse.tick(time.delta());
+        transform.scale = Vec3::splat(1. + COMBO_PULSE_SCALE * combo_text.pulse.fraction_remaining());
+    }
+}
+
+fn score_text_system(
+    score_query: Query<(&Bird, &Score), Changed<Score>>,
+    mut text_query: Query<(&mut Text2d, &ScoreText)>
+) {
+    for (bird, score) in score_query.iter() {
+        for (mut text, score_text) in text_query.iter_mut() {
+            if score_text.0 == bird.player {
+                text.0 = score.0.to_string();
+            }
+        }
+    }
+}
+
+fn theme_system(
+    score_query: Query<&Score>,
+    mut theme: ResMut<Theme>,
+    asset_server: Res<AssetServer>,
+    mut fade_query: Query<&mut Sprite, With<BackgroundFade>>
+) {
+    if theme.fade.is_some() {
+        return;
+    }
+
+    let target = (best_score(&score_query) / THEME_SWITCH_SCORE) as usize % PALETTES.len();
+    if target == theme.current {
+        return;
+    }
+
+    let Ok(mut fade_sprite) = fade_query.get_single_mut() else {
+        return;
+    };
+
+    fade_sprite.image = asset_server.load(PALETTES[target].background);
+    fade_sprite.color = PALETTES[target].background_tint.with_alpha(0.);
+
+    theme.previous = theme.current;
+    theme.current = target;
+    theme.fade = Some(Timer::from_seconds(THEME_FADE_DURATION, TimerMode::Once));
+}
+
+fn theme_fade_system(
+    time: Res<Time>,
+    mut theme: ResMut<Theme>,
+    mut background_query: Query<&mut Sprite, (With<Background>, Without<BackgroundFade>)>,
+    mut fade_query: Query<&mut Sprite, With<BackgroundFade>>
+) {
+    let current = theme.palette();
+
+    let Some(fade) = theme.fade.as_mut() else {
+        return;
+    };
+
+    let (Ok(mut background_sprite), Ok(mut fade_sprite)) = (background_query.get_single_mut(), fade_query.get_single_mut()) else {
+        return;
+    };
+
+    if fade.tick(time.delta()).finished() {
+        background_sprite.image = fade_sprite.image.clone();
+        background_sprite.color = current.background_tint;
+        fade_sprite.color = Color::NONE;
+        theme.fade = None;
+    } else {
+        fade_sprite.color = current.background_tint.with_alpha(fade.fraction());
+    }
+}
+
+fn pipe_tint_system(theme: Res<Theme>, mut pipe_query: Query<&mut Sprite, With<Pipe>>) {
+    let progress = theme.fade.as_ref().map_or(1., Timer::fraction);
+    let (previous, current) = (&PALETTES[theme.previous], theme.palette());
+    let pipe_tint = previous.pipe_tint.mix(&current.pipe_tint, progress);
+
+    for mut pipe_sprite in pipe_query.iter_mut() {
+        pipe_sprite.color = pipe_tint;
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Difficulty;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::settings::Settings;
+use crate::{FixedTick, FlapEvent, GameState};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{GameMode, GameRng};
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub flaps: Vec<u64>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Replay {
+    pub fn load(path: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(path)
+            .inspect_err(|error| error!("could not read replay {path}: {error}"))
+            .ok()?;
+        ron::from_str(&contents)
+            .inspect_err(|error| error!("could not parse replay {path}: {error}"))
+            .ok()
+    }
+
+    fn save(&self, path: &str) {
+        let contents = match ron::to_string(self) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!("could not serialize replay: {error}");
+                return;
+            }
+        };
+
+        if let Err(error) = std::fs::write(path, contents) {
+            warn!("could not save replay {path}: {error}");
+        }
+    }
+}
+
+/// Present while a `--replay` file drives the bird instead of the keyboard.
+#[derive(Resource)]
+pub struct Playback(pub Replay);
+
+/// Present when `--record` names the file each single-player run's replay is written to.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Resource)]
+pub struct Recording {
+    path: String,
+    replay: Option<Replay>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Recording {
+    pub fn new(path: String) -> Self {
+        Self { path, replay: None }
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            playback_input_system
+                .run_if(resource_exists::<Playback>)
+                .in_set(ReplaySet)
+                .run_if(in_state(GameState::Playing))
+        )
+        .add_systems(OnEnter(GameState::GameOver), exit_after_playback.run_if(resource_exists::<Playback>));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            start_recording
+                .run_if(resource_exists::<Recording>)
+                .run_if(resource_equals(GameMode::Single).and(not(resource_exists::<Playback>)))
+        )
+        .add_systems(
+            FixedUpdate,
+            record_input_system
+                .run_if(resource_exists::<Recording>)
+                .in_set(ReplaySet)
+                .run_if(in_state(GameState::Playing))
+        )
+        .add_systems(OnEnter(GameState::GameOver), save_recording.run_if(resource_exists::<Recording>));
+    }
+}
+
+/// Runs before the bird consumes [`FlapEvent`]s each fixed tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReplaySet;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn start_recording(mut recording: ResMut<Recording>, game_rng: Res<GameRng>, settings: Res<Settings>) {
+    recording.replay = Some(Replay {
+        seed: game_rng.seed,
+        difficulty: settings.difficulty,
+        flaps: Vec::new()
+    });
+}
+
+fn playback_input_system(playback: Res<Playback>, tick: Res<FixedTick>, mut flaps: EventWriter<FlapEvent>) {
+    if playback.0.flaps.binary_search(&tick.0).is_ok() {
+        flaps.send(FlapEvent { player: 0 });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn record_input_system(mut recording: ResMut<Recording>, tick: Res<FixedTick>, mut flaps: EventReader<FlapEvent>) {
+    let flapped = flaps.read().any(|flap| flap.player == 0);
+    if let (Some(replay), true) = (recording.replay.as_mut(), flapped) {
+        replay.flaps.push(tick.0);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_recording(mut recording: ResMut<Recording>) {
+    if let Some(replay) = recording.replay.take() {
+        replay.save(&recording.path);
+    }
+}
+
+fn exit_after_playback(mut exit: EventWriter<AppExit>) {
+    exit.send(AppExit::Success);
+}
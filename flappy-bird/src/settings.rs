@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{storage, GameState, HighScore};
+
+const VOLUME_STEP: f32 = 0.1;
+const SLIDER_WIDTH: usize = 10;
+
+const SELECTED_COLOR: Color = Color::srgb(1., 0.85, 0.2);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard
+}
+
+impl Difficulty {
+    /// Multiplier applied to the configured gap height.
+    pub fn gap_scale(self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.3,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 0.8
+        }
+    }
+
+    /// Multiplier applied to the configured pipe speed.
+    pub fn speed_scale(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.25
+        }
+    }
+
+    fn cycle(self, forward: bool) -> Self {
+        match (self, forward) {
+            (Difficulty::Easy, true) | (Difficulty::Hard, false) => Difficulty::Normal,
+            (Difficulty::Normal, true) | (Difficulty::Easy, false) => Difficulty::Hard,
+            (Difficulty::Hard, true) | (Difficulty::Normal, false) => Difficulty::Easy
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Copy)]
+pub struct Settings {
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub difficulty: Difficulty,
+    pub flap_key: KeyCode
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            music_volume: 0.8,
+            sfx_volume: 1.,
+            difficulty: Difficulty::Normal,
+            flap_key: KeyCode::Space
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SettingsRow {
+    MusicVolume,
+    SfxVolume,
+    Difficulty,
+    FlapKey
+}
+
+impl SettingsRow {
+    const ALL: [SettingsRow; 4] = [
+        SettingsRow::MusicVolume,
+        SettingsRow::SfxVolume,
+        SettingsRow::Difficulty,
+        SettingsRow::FlapKey
+    ];
+}
+
+#[derive(Resource, Default)]
+struct SettingsCursor {
+    row: usize,
+    rebinding: bool
+}
+
+#[derive(Component)]
+struct SettingsRowText(SettingsRow);
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsCursor>()
+            .add_systems(OnEnter(GameState::Settings), spawn_settings_screen)
+            .add_systems(
+                Update,
+                (settings_input_system, settings_text_system)
+                    .chain()
+                    .run_if(in_state(GameState::Settings))
+            )
+            .add_systems(OnExit(GameState::Settings), save_settings);
+    }
+}
+
+fn spawn_settings_screen(mut commands: Commands, mut cursor: ResMut<SettingsCursor>) {
+    *cursor = SettingsCursor::default();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(10.),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.7)),
+            StateScoped(GameState::Settings)
+        ))
+        .with_children(|screen| {
+            screen.spawn((
+                Text::new("Settings"),
+                TextFont {
+                    font_size: 20.,
+                    ..default()
+                }
+            ));
+
+            for row in SettingsRow::ALL {
+                screen.spawn((
+                    Text::default(),
+                    TextFont {
+                        font_size: 13.,
+                        ..default()
+                    },
+                    SettingsRowText(row)
+                ));
+            }
+
+            screen.spawn((
+                Text::new("Up/Down select, Left/Right change\nEnter rebinds, Escape saves"),
+                TextFont {
+                    font_size: 11.,
+                    ..default()
+                },
+                TextLayout::new_with_justify(JustifyText::Center)
+            ));
+        });
+}
+
+fn settings_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut cursor: ResMut<SettingsCursor>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<GameState>>
+) {
+    if cursor.rebinding {
+        if let Some(&key) = keys.get_just_pressed().next() {
+            if key != KeyCode::Escape {
+                settings.flap_key = key;
+            }
+            cursor.rebinding = false;
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Menu);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        cursor.row = (cursor.row + SettingsRow::ALL.len() - 1) % SettingsRow::ALL.len();
+    } else if keys.just_pressed(KeyCode::ArrowDown) {
+        cursor.row = (cursor.row + 1) % SettingsRow::ALL.len();
+    }
+
+    let step = match (keys.just_pressed(KeyCode::ArrowLeft), keys.just_pressed(KeyCode::ArrowRight)) {
+        (true, false) => -1.,
+        (false, true) => 1.,
+        _ => 0.
+    };
+
+    match SettingsRow::ALL[cursor.row] {
+        SettingsRow::MusicVolume => {
+            settings.music_volume = (settings.music_volume + step * VOLUME_STEP).clamp(0., 1.);
+        }
+        SettingsRow::SfxVolume => {
+            settings.sfx_volume = (settings.sfx_volume + step * VOLUME_STEP).clamp(0., 1.);
+        }
+        SettingsRow::Difficulty if step != 0. => {
+            settings.difficulty = settings.difficulty.cycle(step > 0.);
+        }
+        SettingsRow::FlapKey if keys.just_pressed(KeyCode::Enter) => {
+            cursor.rebinding = true;
+        }
+        _ => {}
+    }
+}
+
+fn slider(value: f32) -> String {
+    let filled = (value * SLIDER_WIDTH as f32).round() as usize;
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(SLIDER_WIDTH - filled))
+}
+
+fn settings_text_system(
+    cursor: Res<SettingsCursor>,
+    settings: Res<Settings>,
+    mut row_query: Query<(&SettingsRowText, &mut Text, &mut TextColor)>
+) {
+    if !cursor.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    for (row_text, mut text, mut color) in row_query.iter_mut() {
+        text.0 = match row_text.0 {
+            SettingsRow::MusicVolume => format!("Music  {}", slider(settings.music_volume)),
+            SettingsRow::SfxVolume => format!("SFX    {}", slider(settings.sfx_volume)),
+            SettingsRow::Difficulty => format!("Difficulty  < {:?} >", settings.difficulty),
+            SettingsRow::FlapKey if cursor.rebinding => "Flap key  [press a key]".to_string(),
+            SettingsRow::FlapKey => format!("Flap key  [{:?}]", settings.flap_key)
+        };
+
+        let selected = SettingsRow::ALL[cursor.row] == row_text.0;
+        color.0 = if selected { SELECTED_COLOR } else { Color::WHITE };
+    }
+}
+
+fn save_settings(settings: Res<Settings>, high_score: Res<HighScore>) {
+    storage::save_profile(high_score.0, *settings);
+}
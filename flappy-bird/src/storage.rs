@@ -0,0 +1,66 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+const PROFILE_KEY: &str = "flappy-bird.profile";
+
+/// The high score and settings, persisted together.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Profile {
+    pub high_score: u32,
+    #[serde(default)]
+    pub settings: Settings
+}
+
+pub fn load_profile() -> Profile {
+    load(PROFILE_KEY).unwrap_or_default()
+}
+
+pub fn save_profile(high_score: u32, settings: Settings) {
+    save(PROFILE_KEY, &Profile { high_score, settings });
+}
+
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    ron::from_str(&read(key)?)
+        .inspect_err(|error| bevy::log::warn!("could not parse {key}: {error}"))
+        .ok()
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match ron::to_string(value) {
+        Ok(contents) => write(key, &contents),
+        Err(error) => bevy::log::warn!("could not serialize {key}: {error}")
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(key).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(key: &str, value: &str) {
+    if let Err(error) = std::fs::write(key, value) {
+        bevy::log::warn!("could not save {key}: {error}");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(key: &str, value: &str) {
+    if let Some(storage) = local_storage() {
+        if storage.set_item(key, value).is_err() {
+            bevy::log::warn!("could not save {key} to localStorage");
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+
+use super::*;
+
+fn test_app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins((MinimalPlugins, StatesPlugin, GameplayPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1. / FIXED_TICK_RATE)))
+        .insert_resource(GameConfig::default())
+        .insert_resource(PlayArea { size: WINDOW_RESOLUTION })
+        .insert_resource(GameRng::from_seed(0))
+        .insert_resource(HighScore(u32::MAX))
+        .insert_resource(GameTextures {
+            background: Handle::default(),
+            pipe: Handle::default(),
+            bird_down: Handle::default(),
+            bird_up: Handle::default()
+        });
+
+    app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::Playing);
+    app.update();
+
+    app
+}
+
+fn spawn_bird(app: &mut App, position: Vec2, player: usize) -> Entity {
+    app.world_mut()
+        .spawn((
+            Sprite::default(),
+            Transform::from_translation(position.extend(0.1)),
+            Bird { velocity: Vec2::ZERO, player },
+            Score::default(),
+            Combo::default()
+        ))
+        .id()
+}
+
+/// Spawns the lower pipe of a pair whose gap is centered on `gap_y`.
+fn spawn_pipe(app: &mut App, x: f32, gap_y: f32) -> Entity {
+    let gap_height = app.world().resource::<GameConfig>().gap_height;
+    let pipe_y = gap_y - gap_height / 2. - PIPE_HEIGHT / 2.;
+
+    app.world_mut()
+        .spawn((
+            Sprite::default(),
+            Transform::from_xyz(x, pipe_y, 0.1),
+            Pipe { velocity: Vec2::ZERO },
+            ScoreGate::new(gap_y, gap_height)
+        ))
+        .id()
+}
+
+fn run_ticks(app: &mut App, ticks: u64) {
+    let target = app.world().resource::<FixedTick>().0 + ticks;
+    while app.world().resource::<FixedTick>().0 < target {
+        app.update();
+    }
+}
+
+#[test]
+fn bird_falls_under_gravity() {
+    let mut app = test_app();
+    let bird = spawn_bird(&mut app, Vec2::ZERO, 0);
+
+    run_ticks(&mut app, 10);
+
+    let world = app.world();
+    assert!(world.get::<Bird>(bird).unwrap().velocity.y < 0.);
+    assert!(world.get::<Transform>(bird).unwrap().translation.y < 0.);
+}
+
+#[test]
+fn flap_sets_upward_velocity() {
+    let mut app = test_app();
+    let bird = spawn_bird(&mut app, Vec2::ZERO, 0);
+
+    app.world_mut().send_event(FlapEvent { player: 0 });
+    run_ticks(&mut app, 1);
+
+    let jump_force = app.world().resource::<GameConfig>().jump_force;
+    assert_eq!(app.world().get::<Bird>(bird).unwrap().velocity.y, jump_force);
+
+    run_ticks(&mut app, 1);
+
+    assert!(app.world().get::<Bird>(bird).unwrap().velocity.y < jump_force);
+}
+
+#[test]
+fn passing_a_pipe_pair_increments_score() {
+    let mut app = test_app();
+    let bird = spawn_bird(&mut app, Vec2::ZERO, 0);
+    let pipe = spawn_pipe(&mut app, -PIPE_WIDTH, 0.);
+
+    run_ticks(&mut app, 1);
+
+    assert_eq!(app.world().get::<Score>(bird).unwrap().0, 1);
+    assert!(app.world().get::<ScoreGate>(pipe).is_none());
+
+    run_ticks(&mut app, 1);
+
+    assert_eq!(app.world().get::<Score>(bird).unwrap().0, 1);
+}
+
+#[test]
+fn overlapping_a_pipe_triggers_game_over() {
+    let mut app = test_app();
+    spawn_bird(&mut app, Vec2::ZERO, 0);
+    spawn_pipe(&mut app, 0., PIPE_HEIGHT);
+
+    run_ticks(&mut app, 1);
+    app.update();
+
+    assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::GameOver);
+}
+
+#[test]
+fn scraping_past_a_gap_edge_awards_near_miss_bonus() {
+    let mut app = test_app();
+    let gap_height = app.world().resource::<GameConfig>().gap_height;
+    let bird = spawn_bird(&mut app, Vec2::new(0., -gap_height / 2. + BIRD_HEIGHT / 2. + 2.), 0);
+    let pipe = spawn_pipe(&mut app, 0., 0.);
+
+    run_ticks(&mut app, 1);
+    app.world_mut().get_mut::<Transform>(pipe).unwrap().translation.x = -PIPE_WIDTH;
+    run_ticks(&mut app, 1);
+
+    assert_eq!(app.world().get::<Score>(bird).unwrap().0, 1 + NEAR_MISS_BONUS);
+}
+
+#[test]
+fn each_bird_is_credited_with_its_own_pass() {
+    let mut app = test_app();
+    let gap_height = app.world().resource::<GameConfig>().gap_height;
+    let scraping = spawn_bird(&mut app, Vec2::new(0., -gap_height / 2. + BIRD_HEIGHT / 2. + 2.), 0);
+    let centred = spawn_bird(&mut app, Vec2::ZERO, 1);
+    let pipe = spawn_pipe(&mut app, 0., 0.);
+
+    run_ticks(&mut app, 1);
+    app.world_mut().get_mut::<Transform>(pipe).unwrap().translation.x = -PIPE_WIDTH;
+    run_ticks(&mut app, 1);
+
+    assert_eq!(app.world().get::<Score>(scraping).unwrap().0, 1 + NEAR_MISS_BONUS);
+    assert_eq!(app.world().get::<Score>(centred).unwrap().0, 1);
+}
+
+#[test]
+fn crashing_in_practice_respawns_at_checkpoint() {
+    let mut app = test_app();
+    *app.world_mut().resource_mut::<GameMode>() = GameMode::Practice;
+    let bird = spawn_bird(&mut app, Vec2::ZERO, 0);
+    let cleared = spawn_pipe(&mut app, -PIPE_WIDTH, 20.);
+
+    run_ticks(&mut app, 1);
+    spawn_pipe(&mut app, 0., PIPE_HEIGHT);
+    run_ticks(&mut app, 1);
+
+    let world = app.world();
+    assert_eq!(*world.resource::<State<GameState>>().get(), GameState::Playing);
+    assert!(world.get::<Invulnerable>(bird).is_some());
+    assert_eq!(world.get::<Transform>(bird).unwrap().translation.y, 20.);
+    assert!(world.get::<Transform>(cleared).unwrap().translation.x < -(BIRD_WIDTH + PIPE_WIDTH) / 2.);
+}
+
+#[test]
+fn clean_passes_build_combo_and_flap_spam_breaks_it() {
+    let mut app = test_app();
+    let bird = spawn_bird(&mut app, Vec2::ZERO, 0);
+
+    for _ in 0..COMBO_PIPES_PER_LEVEL {
+        spawn_pipe(&mut app, -PIPE_WIDTH, 0.);
+        run_ticks(&mut app, 1);
+    }
+
+    assert_eq!(app.world().get::<Combo>(bird).unwrap().multiplier(), 2);
+    assert_eq!(app.world().get::<Score>(bird).unwrap().0, COMBO_PIPES_PER_LEVEL + 1);
+
+    for _ in 0..=COMBO_MAX_FLAPS {
+        app.world_mut().send_event(FlapEvent { player: 0 });
+    }
+    run_ticks(&mut app, 1);
+
+    assert_eq!(app.world().get::<Combo>(bird).unwrap().multiplier(), 1);
+}
+
+#[test]
+fn demo_bot_flies_through_pipes() {
+    let mut app = test_app();
+    app.add_plugins(demo::DemoPlugin).init_resource::<ButtonInput<KeyCode>>();
+    *app.world_mut().resource_mut::<GameMode>() = GameMode::Demo;
+    let bird = spawn_bird(&mut app, Vec2::ZERO, 0);
+    app.world_mut().entity_mut(bird).insert(AiControlled::default());
+
+    // Plain updates rather than `run_ticks`, which would spin forever once a crash stops the ticks.
+    for _ in 0..600 {
+        app.update();
+    }
+
+    assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Playing);
+    assert!(app.world().get::<Score>(bird).unwrap().0 >= 3);
+}
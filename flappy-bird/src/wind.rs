@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::config::GameConfig;
+use crate::{best_score, update_bird_system, Bird, GameRng, GameState, PlayArea, RunEntity, Score};
+
+/// Pulls a drifting bird back to its column once a sideways gust dies down.
+const WIND_RETURN_STIFFNESS: f32 = 0.15;
+const WIND_RETURN_DAMPING: f32 = 6.;
+const LEAF_SPAWN_RATE: f32 = 20.;
+const LEAF_SIZE: Vec2 = Vec2::new(5., 3.);
+const LEAF_SPEED: f32 = 180.;
+const LEAF_SPIN: f32 = 6.;
+const LEAF_COLORS: [Color; 3] = [
+    Color::srgb(0.45, 0.7, 0.25),
+    Color::srgb(0.8, 0.55, 0.2),
+    Color::srgb(0.65, 0.35, 0.15)
+];
+
+/// A single gust: leaves blow for `warning` seconds before the force reaches the birds.
+struct Gust {
+    force: Vec2,
+    warning: Timer,
+    blowing: Timer
+}
+
+/// Gust scheduling, seeded from the run seed so replays see the same wind.
+#[derive(Resource)]
+struct Wind {
+    rng: StdRng,
+    next_gust: Timer,
+    gust: Option<Gust>
+}
+
+impl Wind {
+    fn new(seed: u64, config: &GameConfig) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed.rotate_left(32));
+        let next_gust = Self::next_gust_timer(&mut rng, config);
+        Self { rng, next_gust, gust: None }
+    }
+
+    fn next_gust_timer(rng: &mut StdRng, config: &GameConfig) -> Timer {
+        Timer::from_seconds(config.wind_interval * rng.random_range(0.75..1.25), TimerMode::Once)
+    }
+
+    /// The force currently pushing the birds, zero while a gust is still being telegraphed.
+    fn force(&self) -> Vec2 {
+        match &self.gust {
+            Some(gust) if gust.warning.finished() => gust.force,
+            _ => Vec2::ZERO
+        }
+    }
+}
+
+#[derive(Component)]
+struct Leaf {
+    velocity: Vec2,
+    spin: f32
+}
+
+/// Periodic gusts that push the birds around once the score is high enough.
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), start_wind)
+            .add_systems(
+                FixedUpdate,
+                (wind_schedule_system, wind_force_system)
+                    .chain()
+                    .before(update_bird_system)
+                    .run_if(in_state(GameState::Playing))
+            )
+            .add_systems(Update, (leaf_spawn_system, leaf_system).run_if(in_state(GameState::Playing)));
+    }
+}
+
+fn start_wind(mut commands: Commands, game_rng: Res<GameRng>, config: Res<GameConfig>) {
+    commands.insert_resource(Wind::new(game_rng.seed, &config));
+}
+
+fn wind_schedule_system(
+    time: Res<Time>,
+    score_query: Query<&Score>,
+    config: Res<GameConfig>,
+    mut wind: ResMut<Wind>
+) {
+    let wind = &mut *wind;
+
+    if let Some(gust) = wind.gust.as_mut() {
+        if gust.warning.tick(time.delta()).finished() && gust.blowing.tick(time.delta()).finished() {
+            wind.gust = None;
+            wind.next_gust = Wind::next_gust_timer(&mut wind.rng, &config);
+        }
+        return;
+    }
+
+    if best_score(&score_query) < config.wind_min_score || !wind.next_gust.tick(time.delta()).finished() {
+        return;
+    }
+
+    let angle = wind.rng.random_range(0. ..std::f32::consts::TAU);
+    wind.gust = Some(Gust {
+        force: Vec2::from_angle(angle) * config.wind_strength,
+        warning: Timer::from_seconds(config.wind_warning, TimerMode::Once),
+        blowing: Timer::from_seconds(config.wind_duration, TimerMode::Once)
+    });
+}
+
+fn wind_force_system(time: Res<Time>, wind: Res<Wind>, mut bird_query: Query<(&mut Bird, &Transform)>) {
+    let dt = time.delta_secs();
+    let force = wind.force();
+
+    for (mut bird, bird_transform) in bird_query.iter_mut() {
+        let drift = bird_transform.translation.x;
+        let spring = -WIND_RETURN_STIFFNESS * drift - WIND_RETURN_DAMPING * bird.velocity.x;
+        bird.velocity += (force + Vec2::X * spring) * dt;
+    }
+}
+
+fn leaf_spawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    wind: Res<Wind>,
+    play_area: Res<PlayArea>
+) {
+    let Some(gust) = &wind.gust else {
+        return;
+    };
+
+    let mut rng = rand::rng();
+    if !rng.random_bool((LEAF_SPAWN_RATE * time.delta_secs()).min(1.) as f64) {
+        return;
+    }
+
+    // Leaves enter from the upwind edge so the gust's direction is readable before it hits.
+    let direction = gust.force.normalize_or(Vec2::X);
+    let half_size = play_area.half_size();
+    let position = if direction.x.abs() >= direction.y.abs() {
+        Vec2::new(-direction.x.signum() * half_size.x, rng.random_range(-half_size.y..half_size.y))
+    } else {
+        Vec2::new(rng.random_range(-half_size.x..half_size.x), -direction.y.signum() * half_size.y)
+    };
+
+    commands.spawn((
+        Sprite {
+            color: LEAF_COLORS[rng.random_range(0..LEAF_COLORS.len())],
+            custom_size: Some(LEAF_SIZE),
+            ..default()
+        },
+        Transform::from_translation(position.extend(0.4)),
+        Leaf {
+            velocity: direction * LEAF_SPEED * rng.random_range(0.8..1.2),
+            spin: rng.random_range(-LEAF_SPIN..LEAF_SPIN)
+        },
+        RunEntity
+    ));
+}
+
+fn leaf_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    play_area: Res<PlayArea>,
+    mut leaf_query: Query<(Entity, &Leaf, &mut Transform)>
+) {
+    let dt = time.delta_secs();
+    let bounds = play_area.half_size() + LEAF_SIZE;
+
+    for (entity, leaf, mut transform) in leaf_query.iter_mut() {
+        transform.translation += (leaf.velocity * dt).extend(0.);
+        transform.rotate_z(leaf.spin * dt);
+
+        if transform.translation.x.abs() > bounds.x || transform.translation.y.abs() > bounds.y {
+            commands.entity(entity).despawn();
+        }
+    }
+}
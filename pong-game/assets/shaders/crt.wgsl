@@ -0,0 +1,36 @@
+// The finished picture drawn again as if on an old CRT television: bulging out like the glass of
+// the tube, striped with dark scanlines and darker towards the corners.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+const PI: f32 = 3.14159265;
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct CrtSettings {
+    curvature: f32,
+    scanlines: f32,
+    vignette: f32,
+}
+@group(0) @binding(2) var<uniform> settings: CrtSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // From -1 to 1 across the screen, pushed further out the further it is from the middle.
+    var centered = in.uv * 2.0 - 1.0;
+    centered *= 1.0 + settings.curvature * centered.yx * centered.yx;
+    let uv = centered * 0.5 + 0.5;
+
+    let color = textureSample(screen_texture, texture_sampler, uv);
+
+    // A dark line every other row of pixels.
+    let row = uv.y * f32(textureDimensions(screen_texture).y);
+    let scanline = 1.0 - settings.scanlines * (0.5 + 0.5 * cos(row * PI));
+
+    let vignette = 1.0 - settings.vignette * dot(centered, centered) * 0.5;
+
+    // Off the bent edges of the picture is the black of the tube.
+    let on_screen = all(uv >= vec2(0.0)) && all(uv <= vec2(1.0));
+    return vec4(select(vec3(0.0), color.rgb * scanline * vignette, on_screen), 1.0);
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use crate::ball::{Ball, BALL_SIZE};
+use crate::court::Edge;
+use crate::paddle::{Paddle, PaddleSet};
+use crate::Velocity;
+
+/// How good the computer is at returning the ball.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    /// Seconds between looks at the ball.
+    fn reaction_delay(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.3,
+            Difficulty::Normal => 0.15,
+            Difficulty::Hard => 0.05,
+        }
+    }
+
+    fn max_speed(self) -> f32 {
+        match self {
+            Difficulty::Easy => 200.,
+            Difficulty::Normal => 300.,
+            Difficulty::Hard => 400.,
+        }
+    }
+
+    /// Whether it works out where the ball will cross its paddle instead of following the ball.
+    fn predicts(self) -> bool {
+        self == Difficulty::Hard
+    }
+}
+
+/// A computer paddle that re-reads the ball every reaction delay.
+#[derive(Component)]
+pub struct AiPaddle {
+    difficulty: Difficulty,
+    reaction: Timer,
+    target_x: f32,
+}
+
+impl AiPaddle {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            reaction: Timer::from_seconds(difficulty.reaction_delay(), TimerMode::Repeating),
+            target_x: 0.,
+        }
+    }
+}
+
+/// The computer opponent for single-player matches.
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, ai_paddle_system.in_set(PaddleSet::Control));
+    }
+}
+
+fn ai_paddle_system(
+    time: Res<Time>,
+    mut paddle_query: Query<(&Transform, &mut Velocity, &mut AiPaddle, &Paddle), Without<Ball>>,
+    ball_query: Query<(&Transform, &Velocity), With<Ball>>,
+) {
+    let dt = time.delta_secs();
+
+    for (transform, mut paddle_velocity, mut ai, paddle) in paddle_query.iter_mut() {
+        let edge = paddle.edge;
+        let position = edge.to_local(transform.translation.truncate());
+        let balls: Vec<(Vec2, Vec2)> = ball_query
+            .iter()
+            .map(|(ball_transform, velocity)| {
+                let ball = edge.to_local(ball_transform.translation.truncate());
+                (ball, edge.to_local(velocity.0.truncate()))
+            })
+            .collect();
+        let time_to_reach = |(ball, velocity): &(Vec2, Vec2)| (position.y - ball.y) / velocity.y;
+        // The ball that will reach it soonest, or any of them when none is heading its way.
+        let coming = balls
+            .iter()
+            .copied()
+            .filter(|ball| time_to_reach(ball) > 0.)
+            .min_by(|a, b| time_to_reach(a).total_cmp(&time_to_reach(b)));
+
+        if ai.reaction.tick(time.delta()).just_finished() {
+            if let Some((ball, velocity)) = coming.filter(|_| ai.difficulty.predicts()) {
+                ai.target_x = crossing_x(edge, ball, velocity, position.y);
+            } else if let Some((ball, _)) = coming.or_else(|| balls.first().copied()) {
+                ai.target_x = ball.x;
+            }
+        }
+
+        // As fast as it may, without going past the target in one frame.
+        let max_speed = ai.difficulty.max_speed();
+        let speed = ((ai.target_x - position.x) / dt.max(f32::EPSILON)).clamp(-max_speed, max_speed);
+        paddle_velocity.0 = (edge.along() * speed).extend(0.);
+    }
+}
+
+/// Where the ball crosses `y`, bouncing off the sides, in `edge`'s local frame.
+fn crossing_x(edge: Edge, ball: Vec2, velocity: Vec2, y: f32) -> f32 {
+    let x = ball.x + velocity.x * (y - ball.y) / velocity.y;
+
+    // Unfolds the bounces: the ball travels back and forth across a court `width` wide.
+    let half_width = edge.length() / 2. - BALL_SIZE.x / 2.;
+    let width = half_width * 2.;
+    let folded = (x + half_width).rem_euclid(width * 2.);
+    let folded = if folded > width { width * 2. - folded } else { folded };
+    folded - half_width
+}
@@ -0,0 +1,218 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, LoadedFolder};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::court::Orientation;
+use crate::{MatchState, PhysicsSet};
+
+const ARENA_FOLDER: &str = "arenas";
+
+const BLOCK_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+/// Obstacles that kick the ball away faster than it came stand out from the plain blocks.
+const BUMPER_COLOR: Color = Color::srgb(0.8, 0.35, 0.55);
+/// Behind the pickups, which can turn up on top of an obstacle.
+pub const OBSTACLE_DEPTH: f32 = -0.75;
+
+/// An obstacle in arena-file coordinates: along the paddles and out towards player 1.
+#[derive(Deserialize, Clone)]
+struct ObstacleData {
+    position: Vec2,
+    size: Vec2,
+    /// How much of its speed the ball keeps bouncing off it; more than 1 kicks it away faster.
+    bounciness: f32,
+    /// Swings it back and forth, for a moving bumper; a block left without one stays put.
+    #[serde(default)]
+    swing: Option<SwingData>,
+}
+
+#[derive(Deserialize, Clone)]
+struct SwingData {
+    /// How far it goes each way from its position.
+    travel: Vec2,
+    /// Seconds to swing out both ways and back.
+    period: f32,
+}
+
+/// Midfield obstacles, loaded from [`ARENA_FOLDER`].
+#[derive(Asset, TypePath, Deserialize)]
+pub struct Arena {
+    pub name: String,
+    obstacles: Vec<ObstacleData>,
+}
+
+#[derive(Default)]
+struct ArenaLoader;
+
+impl AssetLoader for ArenaLoader {
+    type Asset = Arena;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["arena.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct ArenaFolder(Handle<LoadedFolder>);
+
+/// Every [`Arena`] in [`ARENA_FOLDER`] that has loaded.
+#[derive(SystemParam)]
+pub struct Arenas<'w> {
+    folder: Res<'w, ArenaFolder>,
+    folders: Res<'w, Assets<LoadedFolder>>,
+    arenas: Res<'w, Assets<Arena>>,
+}
+
+impl Arenas<'_> {
+    /// The arenas, by name.
+    fn all(&self) -> Vec<&Arena> {
+        let Some(folder) = self.folders.get(&self.folder.0) else {
+            return Vec::new();
+        };
+
+        let mut arenas: Vec<&Arena> = folder
+            .handles
+            .iter()
+            .filter_map(|handle| handle.id().try_typed::<Arena>().ok())
+            .filter_map(|id| self.arenas.get(id))
+            .collect();
+        arenas.sort_by(|a, b| a.name.cmp(&b.name));
+        arenas
+    }
+
+    fn get(&self, selected: &SelectedArena) -> Option<&Arena> {
+        selected.0.and_then(|index| self.all().get(index).copied())
+    }
+
+    pub fn name(&self, selected: &SelectedArena) -> String {
+        self.get(selected).map_or_else(|| "Open court".to_string(), |arena| arena.name.clone())
+    }
+
+    /// The arena after `selected`, through every one and back to the open court.
+    pub fn next(&self, selected: &SelectedArena) -> SelectedArena {
+        let next = selected.0.map_or(0, |index| index + 1);
+        SelectedArena((next < self.all().len()).then_some(next))
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.folders.is_changed() || self.arenas.is_changed()
+    }
+}
+
+/// Index into the [`Arenas`], or `None` for the open court.
+#[derive(Resource, Default, PartialEq)]
+pub struct SelectedArena(pub Option<usize>);
+
+/// A block or bumper the balls bounce off, `size` across in the window.
+#[derive(Component)]
+pub struct Obstacle {
+    pub size: Vec2,
+    /// How much of its speed the ball keeps bouncing off it.
+    pub bounciness: f32,
+}
+
+/// Swings a moving bumper back and forth through `origin`, `travel` away at the furthest.
+#[derive(Component)]
+struct Swing {
+    origin: Vec2,
+    travel: Vec2,
+    period: f32,
+    elapsed: f32,
+}
+
+/// The [`Obstacle`]s of the arena picked on the menu, from files watched for changes.
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Arena>()
+            .init_asset_loader::<ArenaLoader>()
+            .init_resource::<SelectedArena>()
+            .add_systems(Startup, load_arenas)
+            .add_systems(Update, build_arena_system)
+            .add_systems(
+                FixedUpdate,
+                swing_system
+                    .before(PhysicsSet::Detect)
+                    .run_if(in_state(MatchState::Playing).or(in_state(MatchState::Serving))),
+            );
+    }
+}
+
+fn load_arenas(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ArenaFolder(asset_server.load_folder(ARENA_FOLDER)));
+}
+
+/// Rebuilds the obstacles when the arena, orientation or arena file changes.
+fn build_arena_system(
+    mut commands: Commands,
+    mut folder_events: EventReader<AssetEvent<LoadedFolder>>,
+    mut arena_events: EventReader<AssetEvent<Arena>>,
+    selected: Res<SelectedArena>,
+    orientation: Res<Orientation>,
+    arenas: Arenas,
+    obstacle_query: Query<Entity, With<Obstacle>>,
+) {
+    let loaded = folder_events.read().count() + arena_events.read().count() > 0;
+    if !loaded && !selected.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in obstacle_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    let Some(arena) = arenas.get(&selected) else {
+        return;
+    };
+
+    let edge = orientation.edge(1);
+    for obstacle in &arena.obstacles {
+        let position = edge.to_world(obstacle.position);
+        let size = edge.extent(obstacle.size);
+        let mut entity = commands.spawn((
+            obstacle_sprite(size, obstacle.bounciness),
+            Transform::from_translation(position.extend(OBSTACLE_DEPTH)),
+            Obstacle { size, bounciness: obstacle.bounciness },
+        ));
+        if let Some(swing) = &obstacle.swing {
+            entity.insert(Swing {
+                origin: position,
+                travel: edge.to_world(swing.travel),
+                period: swing.period,
+                elapsed: 0.,
+            });
+        }
+    }
+}
+
+/// A block, or a bumper if it kicks the ball away faster than it came.
+pub fn obstacle_sprite(size: Vec2, bounciness: f32) -> Sprite {
+    Sprite {
+        color: if bounciness > 1. { BUMPER_COLOR } else { BLOCK_COLOR },
+        custom_size: Some(size),
+        ..default()
+    }
+}
+
+fn swing_system(time: Res<Time>, mut query: Query<(&mut Transform, &mut Swing)>) {
+    for (mut transform, mut swing) in query.iter_mut() {
+        swing.elapsed = (swing.elapsed + time.delta_secs()) % swing.period;
+        let phase = swing.elapsed / swing.period * std::f32::consts::TAU;
+        let position = swing.origin + swing.travel * phase.sin();
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,211 @@
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+
+use crate::ai::{AiPaddle, Difficulty};
+use crate::ball::{ball_bundle, Ball, GoalEvent, LastHitBy, Rally, Serve, Spin, MAX_SERVE_ANGLE};
+use crate::menu::MenuScreen;
+use crate::network::RemotePaddle;
+use crate::paddle::{Eliminated, Paddle};
+use crate::pointer::PointerPaddle;
+use crate::rules::Rules;
+use crate::stats::MatchStats;
+use crate::{MatchState, PhysicsSet, Velocity};
+
+/// Seconds on the menu without any input before the computer starts playing itself behind it.
+const ATTRACT_DELAY: f32 = 15.;
+/// Seconds the ball waits in the middle before each of the computer's serves.
+const ATTRACT_SERVE_DELAY: f32 = 1.;
+/// Washed over the court while the computer plays, so the menu stays easy to read.
+const DIM_COLOR: Color = Color::srgba(0.9, 0.9, 0.9, 0.6);
+
+/// Whether the computer is playing itself behind the menu.
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attract {
+    #[default]
+    Off,
+    Running,
+}
+
+/// Time on the menu since the last input.
+#[derive(Resource)]
+struct IdleTimer(Timer);
+
+impl Default for IdleTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(ATTRACT_DELAY, TimerMode::Once))
+    }
+}
+
+/// The computer's next serve, to whoever was last scored against.
+#[derive(Resource)]
+struct AttractServe {
+    towards: u8,
+    countdown: Timer,
+}
+
+impl Default for AttractServe {
+    fn default() -> Self {
+        Self { towards: 1, countdown: Timer::from_seconds(ATTRACT_SERVE_DELAY, TimerMode::Once) }
+    }
+}
+
+/// Computer-vs-computer demo behind the idle menu.
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<Attract>()
+            .init_resource::<IdleTimer>()
+            .add_systems(OnEnter(MatchState::Menu), reset_idle_timer)
+            .add_systems(OnExit(MatchState::Menu), stop_attract)
+            .add_systems(Update, idle_system.run_if(in_state(MatchState::Menu)))
+            .add_systems(OnEnter(Attract::Running), start_attract)
+            .add_systems(OnExit(Attract::Running), end_attract)
+            .add_systems(
+                FixedUpdate,
+                (attract_goal_system.in_set(PhysicsSet::Respond), attract_serve_system.after(PhysicsSet::Respond))
+                    .run_if(in_state(Attract::Running)),
+            );
+    }
+}
+
+fn reset_idle_timer(mut idle: ResMut<IdleTimer>) {
+    idle.0.reset();
+}
+
+/// Starts the demo after [`ATTRACT_DELAY`] idle and stops it on any input.
+#[allow(clippy::too_many_arguments)]
+fn idle_system(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut idle: ResMut<IdleTimer>,
+    attract: Res<State<Attract>>,
+    mut next_attract: ResMut<NextState<Attract>>,
+) {
+    let moved = mouse_motion.read().count() > 0;
+    let pressed = keys.get_just_pressed().next().is_some() || mouse_buttons.get_just_pressed().next().is_some();
+    if moved || pressed || touches.any_just_pressed() {
+        idle.0.reset();
+        if *attract.get() == Attract::Running {
+            next_attract.set(Attract::Off);
+        }
+        return;
+    }
+
+    if idle.0.tick(time.delta()).just_finished() {
+        next_attract.set(Attract::Running);
+    }
+}
+
+fn stop_attract(mut next_attract: ResMut<NextState<Attract>>) {
+    next_attract.set(Attract::Off);
+}
+
+/// Hands every paddle in play to the computer, dims the menu and readies a single ball to serve.
+fn start_attract(
+    mut commands: Commands,
+    rules: Res<Rules>,
+    paddle_query: Query<Entity, (With<Paddle>, Without<Eliminated>)>,
+    ball_query: Query<Entity, With<Ball>>,
+    menu_query: Query<Entity, With<MenuScreen>>,
+) {
+    for entity in paddle_query.iter() {
+        commands
+            .entity(entity)
+            .remove::<(PointerPaddle, RemotePaddle)>()
+            .insert(AiPaddle::new(Difficulty::Normal));
+    }
+
+    for entity in ball_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.spawn((ball_bundle(Vec3::ZERO, Vec3::ZERO, &rules), LastHitBy(None)));
+
+    for entity in menu_query.iter() {
+        commands.entity(entity).insert(BackgroundColor(DIM_COLOR));
+    }
+    commands.init_resource::<AttractServe>();
+}
+
+/// Undoes the demo's changes to the balls and paddles.
+#[allow(clippy::too_many_arguments)]
+fn end_attract(
+    mut commands: Commands,
+    state: Res<State<MatchState>>,
+    rules: Res<Rules>,
+    mut rally: ResMut<Rally>,
+    mut serve: ResMut<Serve>,
+    mut stats: ResMut<MatchStats>,
+    paddle_query: Query<Entity, With<AiPaddle>>,
+    mut ball_query: Query<(&mut Transform, &mut Velocity, &mut Spin), With<Ball>>,
+    menu_query: Query<Entity, With<MenuScreen>>,
+) {
+    commands.remove_resource::<AttractServe>();
+    if *state.get() == MatchState::Menu {
+        for entity in paddle_query.iter() {
+            commands.entity(entity).remove::<AiPaddle>();
+        }
+    }
+    *serve = Serve::default();
+    rally.hits = 0;
+    rally.speed = rules.ball_speed;
+    *stats = MatchStats::default();
+
+    for (mut transform, mut velocity, mut spin) in ball_query.iter_mut() {
+        transform.translation = Vec3::ZERO;
+        velocity.0 = Vec3::ZERO;
+        *spin = Spin::default();
+    }
+    for entity in menu_query.iter() {
+        commands.entity(entity).insert(BackgroundColor(Color::NONE));
+    }
+}
+
+/// Holds the ball in the middle after a goal, ready to serve again.
+fn attract_goal_system(
+    mut goals: EventReader<GoalEvent>,
+    mut attract_serve: ResMut<AttractServe>,
+    mut ball_query: Query<&mut Velocity, With<Ball>>,
+) {
+    let Some(goal) = goals.read().last() else {
+        return;
+    };
+
+    attract_serve.towards = goal.against;
+    attract_serve.countdown.reset();
+    for mut velocity in ball_query.iter_mut() {
+        velocity.0 = Vec3::ZERO;
+    }
+}
+
+/// Serves from the thread rng, so a seeded match serves the same after a demo.
+fn attract_serve_system(
+    time: Res<Time>,
+    rules: Res<Rules>,
+    mut rally: ResMut<Rally>,
+    mut attract_serve: ResMut<AttractServe>,
+    paddle_query: Query<&Paddle, Without<Eliminated>>,
+    mut ball_query: Query<(&mut Velocity, &mut Spin, &mut LastHitBy), With<Ball>>,
+) {
+    if !attract_serve.countdown.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let towards = attract_serve.towards;
+    let Some(paddle) = paddle_query.iter().find(|paddle| paddle.player == towards).or(paddle_query.iter().next())
+    else {
+        return;
+    };
+    rally.hits = 0;
+    rally.speed = rules.ball_speed;
+    for (mut velocity, mut spin, mut last_hit) in ball_query.iter_mut() {
+        let angle = rand::random_range(-MAX_SERVE_ANGLE..=MAX_SERVE_ANGLE);
+        let direction = paddle.edge.to_world(Vec2::new(angle.sin(), angle.cos()));
+        velocity.0 = (direction * rally.speed).extend(0.);
+        *spin = Spin::default();
+        last_hit.0 = None;
+    }
+}
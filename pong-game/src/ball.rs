@@ -0,0 +1,474 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::arena::Obstacle;
+use crate::court::Edge;
+use crate::effects::TrailEmitter;
+use crate::network::plays_rollback;
+use crate::paddle::{paddle_size, Eliminated, Paddle};
+use crate::rules::Rules;
+use crate::{MatchState, PhysicsSet, Velocity};
+
+pub const BALL_COLOR: Color = Color::srgb(0.7, 0.3, 0.3);
+
+pub const BALL_SIZE: Vec2 = Vec2::new(10., 10.);
+/// The fastest a rally gets, relative to the serve.
+const MAX_BALL_SPEED_UP: f32 = 2.;
+/// How much of a paddle's sideways velocity the ball picks up when hit.
+const PADDLE_MOMENTUM_TRANSFER: f32 = 0.3;
+/// How much [`Spin`] the ball picks up per unit of the paddle's sideways velocity when hit.
+const SPIN_PER_PADDLE_VELOCITY: f32 = 0.5;
+/// The share of its [`Spin`] the ball keeps after a second.
+const SPIN_RETAINED_PER_SECOND: f32 = 0.4;
+
+pub const SERVE_COUNTDOWN: f32 = 3.;
+/// How far from straight at a paddle the ball can be served, either way.
+pub const MAX_SERVE_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+/// How far from straight back the ball bounces off the very edge of a paddle.
+const MAX_BOUNCE_ANGLE: f32 = std::f32::consts::FRAC_PI_3;
+
+/// A ball in play; power-ups can add more than the one served.
+#[derive(Component)]
+pub struct Ball;
+
+/// How many balls are served at once, picked on the menu.
+#[derive(Resource)]
+pub struct BallCount(pub usize);
+
+impl Default for BallCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// The player whose paddle the ball last bounced off, if any since it was served.
+#[derive(Component, Clone, Copy)]
+pub struct LastHitBy(pub Option<u8>);
+
+/// Sideways curve on the ball, in pixels per second squared.
+#[derive(Component, Default)]
+pub struct Spin(f32);
+
+/// Paddle hits since the last serve and the speed they built up.
+#[derive(Resource)]
+pub struct Rally {
+    pub hits: u32,
+    pub speed: f32,
+}
+
+impl Rally {
+    fn new(rules: &Rules) -> Self {
+        Self { hits: 0, speed: rules.ball_speed }
+    }
+}
+
+impl FromWorld for Rally {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(&world.get_resource_or_init::<Rules>())
+    }
+}
+
+/// The next serve: which way it goes and the countdown to it.
+#[derive(Resource)]
+pub struct Serve {
+    /// The player it is served towards.
+    towards: u8,
+    pub countdown: Timer,
+}
+
+impl Default for Serve {
+    fn default() -> Self {
+        Self { towards: 1, countdown: Timer::from_seconds(SERVE_COUNTDOWN, TimerMode::Once) }
+    }
+}
+
+/// Serve angles, seeded from `--seed` so a match can be replayed.
+#[derive(Resource)]
+pub struct ServeRng {
+    pub seed: u64,
+    pub rng: StdRng,
+}
+
+impl ServeRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+/// What a ball ran into.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Collider {
+    Paddle(Entity),
+    /// An edge of the court nobody defends.
+    Wall(Edge),
+    /// A block or bumper of the arena.
+    Obstacle(Entity),
+}
+
+/// A ball reached `collider` at `position`, going at `speed`.
+#[derive(Event)]
+pub struct CollisionEvent {
+    pub ball: Entity,
+    pub collider: Collider,
+    pub position: Vec2,
+    pub speed: f32,
+}
+
+/// A ball got past `against`'s paddle, scoring for `scorer` if anyone.
+#[derive(Event)]
+pub struct GoalEvent {
+    pub against: u8,
+    pub scorer: Option<u8>,
+}
+
+/// Serves, moves and bounces the balls.
+pub struct BallPlugin;
+
+impl Plugin for BallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CollisionEvent>()
+            .add_event::<GoalEvent>()
+            .init_resource::<Rally>()
+            .init_resource::<Serve>()
+            .init_resource::<BallCount>()
+            .add_systems(Startup, log_serve_seed)
+            .add_systems(FixedUpdate, (ball_movement_system, goal_system).chain().in_set(PhysicsSet::Detect))
+            .add_systems(FixedUpdate, (bounce_system, goal_serve_system).in_set(PhysicsSet::Respond))
+            .add_systems(OnExit(MatchState::Menu), spawn_balls)
+            .add_systems(OnEnter(MatchState::Serving), prepare_serve)
+            // A rollback match serves in its own simulation.
+            .add_systems(Update, serve_system.run_if(in_state(MatchState::Serving)).run_if(not(plays_rollback)));
+    }
+}
+
+fn log_serve_seed(serve_rng: Res<ServeRng>) {
+    info!("serve seed: {}", serve_rng.seed);
+}
+
+/// Puts as many balls as the [`BallCount`] asks for in the middle, ready for the first serve.
+fn spawn_balls(
+    mut commands: Commands,
+    rules: Res<Rules>,
+    ball_count: Res<BallCount>,
+    ball_query: Query<Entity, With<Ball>>,
+) {
+    for entity in ball_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for _ in 0..ball_count.0 {
+        commands.spawn((ball_bundle(Vec3::ZERO, Vec3::ZERO, &rules), LastHitBy(None)));
+    }
+}
+
+pub fn ball_bundle(translation: Vec3, velocity: Vec3, rules: &Rules) -> impl Bundle {
+    (
+        Sprite {
+            color: BALL_COLOR,
+            custom_size: Some(BALL_SIZE),
+            ..default()
+        },
+        Transform::from_translation(translation),
+        Ball,
+        Velocity(velocity),
+        Spin::default(),
+        TrailEmitter::new(rules.ball_speed),
+    )
+}
+
+/// Sweeps each ball up to the first thing it hits, so a fast ball can't pass through a paddle.
+#[allow(clippy::type_complexity)]
+fn ball_movement_system(
+    time: Res<Time>,
+    rules: Res<Rules>,
+    mut ball_query: Query<(Entity, &mut Transform, &mut Velocity, &mut Spin), With<Ball>>,
+    paddle_query: Query<(Entity, &Transform, &Paddle), (Without<Ball>, Without<Eliminated>)>,
+    obstacle_query: Query<(Entity, &Transform, &Obstacle), Without<Ball>>,
+    mut collisions: EventWriter<CollisionEvent>,
+) {
+    let dt = time.delta_secs();
+    let walls: Vec<Edge> = Edge::ALL
+        .into_iter()
+        .filter(|&edge| paddle_query.iter().all(|(_, _, paddle)| paddle.edge != edge))
+        .collect();
+
+    for (ball, mut ball_transform, mut velocity, mut spin) in ball_query.iter_mut() {
+        let mut curved = velocity.0.truncate();
+        spin.0 = curve(&mut curved, spin.0, dt);
+        velocity.0 = curved.extend(0.);
+
+        let position = ball_transform.translation.truncate();
+        let motion = velocity.0.truncate() * dt;
+
+        let wall_hit = walls
+            .iter()
+            .filter_map(|&edge| edge_hit(edge, position, motion).map(|t| (t, Collider::Wall(edge))));
+        let paddle_hit = paddle_query
+            .iter()
+            // Skip paddles the ball is moving away from.
+            .filter(|(_, paddle_transform, paddle)| {
+                let outward = paddle.edge.outward();
+                (paddle_transform.translation.truncate() - position).dot(outward) * motion.dot(outward) > 0.
+            })
+            .filter_map(|(entity, paddle_transform, paddle)| {
+                let half_size = (paddle.edge.extent(paddle_size(&rules, paddle_transform)) + BALL_SIZE) / 2.;
+                swept_hit(position, motion, paddle_transform.translation.truncate(), half_size)
+                    .map(|t| (t, Collider::Paddle(entity)))
+            });
+        let obstacle_hit = obstacle_query.iter().filter_map(|(entity, obstacle_transform, obstacle)| {
+            let center = obstacle_transform.translation.truncate();
+            let half_size = (obstacle.size + BALL_SIZE) / 2.;
+            // Skip an obstacle the ball is already leaving.
+            let offset = position - center;
+            let leaving = motion.dot(face_normal(offset, half_size)) >= 0.;
+            if leaving && offset.abs().cmple(half_size).all() {
+                return None;
+            }
+            swept_hit(position, motion, center, half_size).map(|t| (t, Collider::Obstacle(entity)))
+        });
+        let hit = wall_hit.chain(paddle_hit).chain(obstacle_hit).min_by(|a, b| a.0.total_cmp(&b.0));
+
+        let t = hit.map_or(1., |(t, _)| t);
+        let position = position + motion * t;
+        ball_transform.translation = position.extend(ball_transform.translation.z);
+        if let Some((_, collider)) = hit {
+            collisions.send(CollisionEvent { ball, collider, position, speed: velocity.0.length() });
+        }
+    }
+}
+
+/// Sends a [`GoalEvent`] for every ball past a paddle's edge of the court.
+fn goal_system(
+    ball_query: Query<(&Transform, &LastHitBy), With<Ball>>,
+    paddle_query: Query<&Paddle, Without<Eliminated>>,
+    mut goals: EventWriter<GoalEvent>,
+) {
+    let players = paddle_query.iter().count();
+
+    for (ball_transform, last_hit) in ball_query.iter() {
+        let position = ball_transform.translation.truncate();
+        let Some(against) = paddle_query
+            .iter()
+            .find(|paddle| position.dot(paddle.edge.outward()) > paddle.edge.distance() + BALL_SIZE.y / 2.)
+            .map(|paddle| paddle.player)
+        else {
+            continue;
+        };
+
+        let scorer = if players == 2 {
+            paddle_query.iter().map(|paddle| paddle.player).find(|&player| player != against)
+        } else {
+            last_hit.0.filter(|&player| player != against)
+        };
+        goals.send(GoalEvent { against, scorer });
+    }
+}
+
+/// Curves `velocity` sideways by `spin` over `dt` seconds, returning the spin left after.
+pub fn curve(velocity: &mut Vec2, spin: f32, dt: f32) -> f32 {
+    let sideways = velocity.perp().normalize_or_zero();
+    *velocity += sideways * spin * dt;
+    spin * SPIN_RETAINED_PER_SECOND.powf(dt)
+}
+
+/// Fraction of `motion` at which the ball reaches `edge`, if it does.
+pub fn edge_hit(edge: Edge, position: Vec2, motion: Vec2) -> Option<f32> {
+    let limit = edge.distance() - BALL_SIZE.x / 2.;
+    let towards = motion.dot(edge.outward());
+    if towards <= 0. {
+        return None;
+    }
+
+    let t = (limit - position.dot(edge.outward())) / towards;
+    (t <= 1.).then_some(t.max(0.))
+}
+
+/// Fraction of `motion` at which `start` enters the box around `center`, if it does.
+pub fn swept_hit(start: Vec2, motion: Vec2, center: Vec2, half_size: Vec2) -> Option<f32> {
+    let near = center - half_size - start;
+    let far = center + half_size - start;
+    let mut enter = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+
+    for axis in 0..2 {
+        if motion[axis] == 0. {
+            if near[axis] > 0. || far[axis] < 0. {
+                return None;
+            }
+            continue;
+        }
+
+        let (a, b) = (near[axis] / motion[axis], far[axis] / motion[axis]);
+        enter = enter.max(a.min(b));
+        exit = exit.min(a.max(b));
+    }
+
+    (enter <= exit && enter <= 1. && exit >= 0.).then_some(enter.max(0.))
+}
+
+/// Outward normal of the box face nearest `offset`.
+fn face_normal(offset: Vec2, half_size: Vec2) -> Vec2 {
+    let reach = offset / half_size;
+    if reach.x.abs() > reach.y.abs() {
+        Vec2::new(reach.x.signum(), 0.)
+    } else {
+        Vec2::new(0., reach.y.signum())
+    }
+}
+
+fn bounce_system(
+    rules: Res<Rules>,
+    mut rally: ResMut<Rally>,
+    mut collisions: EventReader<CollisionEvent>,
+    mut ball_query: Query<(&Transform, &mut Velocity, &mut Spin, &mut LastHitBy), With<Ball>>,
+    paddle_query: Query<(&Transform, &Velocity, &Paddle), Without<Ball>>,
+    obstacle_query: Query<(&Transform, &Obstacle), Without<Ball>>,
+) {
+    for event in collisions.read() {
+        let Ok((ball_transform, mut velocity, mut spin, mut last_hit)) = ball_query.get_mut(event.ball) else {
+            continue;
+        };
+
+        match event.collider {
+            Collider::Paddle(entity) => {
+                let Ok((paddle_transform, paddle_velocity, paddle)) = paddle_query.get(entity) else {
+                    continue;
+                };
+                bounce_off_paddle(
+                    &rules,
+                    &mut rally,
+                    &mut velocity,
+                    &mut spin,
+                    ball_transform.translation.truncate(),
+                    paddle,
+                    paddle_transform,
+                    paddle_velocity,
+                );
+                last_hit.0 = Some(paddle.player);
+            }
+            // Flip the spin so it curves away from the wall.
+            Collider::Wall(edge) => {
+                let outward = edge.outward().extend(0.);
+                let speed_out = velocity.0.dot(outward);
+                velocity.0 -= outward * 2. * speed_out;
+                spin.0 *= -1.;
+            }
+            Collider::Obstacle(entity) => {
+                let Ok((obstacle_transform, obstacle)) = obstacle_query.get(entity) else {
+                    continue;
+                };
+                let offset = (ball_transform.translation - obstacle_transform.translation).truncate();
+                let normal = face_normal(offset, (obstacle.size + BALL_SIZE) / 2.).extend(0.);
+                let speed_in = velocity.0.dot(normal).min(0.);
+                velocity.0 -= normal * 2. * speed_in;
+                velocity.0 = (velocity.0 * obstacle.bounciness).clamp_length_max(rules.ball_speed * MAX_BALL_SPEED_UP);
+                spin.0 *= -1.;
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bounce_off_paddle(
+    rules: &Rules,
+    rally: &mut Rally,
+    velocity: &mut Velocity,
+    spin: &mut Spin,
+    position: Vec2,
+    paddle: &Paddle,
+    paddle_transform: &Transform,
+    paddle_velocity: &Velocity,
+) {
+    let edge = paddle.edge;
+    let offset = edge.to_local(position - paddle_transform.translation.truncate());
+    let paddle_width = paddle_size(rules, paddle_transform).x;
+    let paddle_speed = paddle_velocity.0.truncate().dot(edge.along());
+
+    rally.hits += 1;
+    rally.speed = sped_up(rules, rally.speed);
+    let (rebound, rebound_spin) = paddle_rebound(edge, offset, paddle_width, paddle_speed, rally.speed);
+    velocity.0 = rebound.extend(0.);
+    spin.0 = rebound_spin;
+}
+
+/// A rally's speed after one more paddle hit, up to the fastest a rally gets.
+pub fn sped_up(rules: &Rules, speed: f32) -> f32 {
+    (speed * rules.speed_up).min(rules.ball_speed * MAX_BALL_SPEED_UP)
+}
+
+/// Velocity and spin off a paddle: steeper towards its ends, pushed and spun by its movement.
+pub fn paddle_rebound(edge: Edge, offset: Vec2, paddle_width: f32, paddle_speed: f32, speed: f32) -> (Vec2, f32) {
+    let reach = (paddle_width + BALL_SIZE.x) / 2.;
+    let angle = (offset.x / reach).clamp(-1., 1.) * MAX_BOUNCE_ANGLE;
+    let away = offset.y.signum();
+
+    let direction = Vec2::new(angle.sin(), angle.cos() * away);
+    let push = Vec2::new(paddle_speed * PADDLE_MOMENTUM_TRANSFER, 0.);
+    let velocity = edge.to_world(direction * speed + push);
+    let sideways = velocity.perp().normalize_or_zero();
+    let spin = (edge.along() * paddle_speed).dot(sideways) * SPIN_PER_PADDLE_VELOCITY;
+    (velocity, spin)
+}
+
+/// Recentres the balls after a goal, to serve towards the player scored against.
+fn goal_serve_system(
+    mut serve: ResMut<Serve>,
+    mut goals: EventReader<GoalEvent>,
+    mut ball_query: Query<&mut Transform, With<Ball>>,
+) {
+    let Some(goal) = goals.read().last() else {
+        return;
+    };
+
+    serve.towards = goal.against;
+    for mut ball_transform in ball_query.iter_mut() {
+        ball_transform.translation = Vec3::ZERO;
+    }
+}
+
+/// Restarts the countdown and rally, skipping eliminated players for the serve.
+fn prepare_serve(
+    mut serve: ResMut<Serve>,
+    mut rally: ResMut<Rally>,
+    rules: Res<Rules>,
+    paddle_query: Query<&Paddle, Without<Eliminated>>,
+) {
+    serve.countdown.reset();
+    *rally = Rally::new(&rules);
+
+    let in_play = |player: u8| paddle_query.iter().any(|paddle| paddle.player == player);
+    if let Some(next) = (0..4).map(|step| (serve.towards + step - 1) % 4 + 1).find(|&player| in_play(player)) {
+        serve.towards = next;
+    }
+}
+
+/// Launches the balls when the countdown ends, alternating directions.
+fn serve_system(
+    time: Res<Time>,
+    mut serve: ResMut<Serve>,
+    rally: Res<Rally>,
+    mut rng: ResMut<ServeRng>,
+    paddle_query: Query<&Paddle>,
+    mut ball_query: Query<(&mut Velocity, &mut Spin, &mut LastHitBy), With<Ball>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    if !serve.countdown.tick(time.delta()).finished() {
+        return;
+    }
+
+    let Some(paddle) = paddle_query.iter().find(|paddle| paddle.player == serve.towards) else {
+        return;
+    };
+    let edge = paddle.edge;
+    let mut towards = 1.;
+    for (mut velocity, mut spin, mut last_hit) in ball_query.iter_mut() {
+        let angle = rng.rng.random_range(-MAX_SERVE_ANGLE..=MAX_SERVE_ANGLE);
+        let direction = edge.to_world(Vec2::new(angle.sin(), angle.cos() * towards));
+        velocity.0 = (direction * rally.speed).extend(0.);
+        spin.0 = 0.;
+        last_hit.0 = None;
+        towards = -towards;
+    }
+
+    next_state.set(MatchState::Playing);
+}
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::menu::GameMode;
+use crate::paddle::{paddle_color, player_edge, PADDLE_OFFSET};
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+const LINE_COLOR: Color = Color::srgb(0.75, 0.75, 0.75);
+const LINE_THICKNESS: f32 = 4.;
+const DASH_LENGTH: f32 = 20.;
+const DASH_GAP: f32 = 15.;
+
+const HALF_ALPHA: f32 = 0.05;
+const GOAL_ZONE_ALPHA: f32 = 0.12;
+/// How far the goal zone reaches into the court past the paddle.
+const GOAL_ZONE_MARGIN: f32 = 20.;
+
+/// Below the pickups and everything else on the court.
+const HALF_DEPTH: f32 = -3.;
+const GOAL_ZONE_DEPTH: f32 = -2.;
+const LINE_DEPTH: f32 = -1.;
+
+/// An edge of the court, which a paddle can defend.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right];
+
+    /// Straight out of the court across this edge.
+    pub fn outward(self) -> Vec2 {
+        match self {
+            Edge::Top => Vec2::Y,
+            Edge::Bottom => Vec2::NEG_Y,
+            Edge::Left => Vec2::NEG_X,
+            Edge::Right => Vec2::X,
+        }
+    }
+
+    /// The way a paddle on this edge slides.
+    pub fn along(self) -> Vec2 {
+        self.outward().perp().abs()
+    }
+
+    /// How long this edge is.
+    pub fn length(self) -> f32 {
+        Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT).dot(self.along())
+    }
+
+    /// How far this edge is from the middle of the court.
+    pub fn distance(self) -> f32 {
+        Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT).dot(self.outward().abs()) / 2.
+    }
+
+    /// Turns something laid out along the x axis, like a paddle's sprite, to lie along this edge.
+    pub fn rotation(self) -> Quat {
+        Quat::from_rotation_arc_2d(Vec2::X, self.along())
+    }
+
+    /// A point or direction in the window as how far it is along this edge and out towards it.
+    pub fn to_local(self, world: Vec2) -> Vec2 {
+        Vec2::new(world.dot(self.along()), world.dot(self.outward()))
+    }
+
+    /// A point or direction along this edge and out towards it, in the window.
+    pub fn to_world(self, local: Vec2) -> Vec2 {
+        self.along() * local.x + self.outward() * local.y
+    }
+
+    /// A size along and across this edge, in the window.
+    pub fn extent(self, size: Vec2) -> Vec2 {
+        self.to_world(size).abs()
+    }
+}
+
+/// Which edges of the window the two players' paddles defend, picked on the menu.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum Orientation {
+    /// Player 1 along the top of the window, player 2 along the bottom.
+    #[default]
+    TopBottom,
+    /// The classic layout, player 1 on the left and player 2 on the right.
+    LeftRight,
+}
+
+impl Orientation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Orientation::TopBottom => "Top/bottom",
+            Orientation::LeftRight => "Left/right",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Orientation::TopBottom => Orientation::LeftRight,
+            Orientation::LeftRight => Orientation::TopBottom,
+        }
+    }
+
+    /// The edge `player` defends in a two-player match.
+    pub fn edge(self, player: u8) -> Edge {
+        match (self, player) {
+            (Orientation::TopBottom, 1) => Edge::Top,
+            (Orientation::TopBottom, _) => Edge::Bottom,
+            (Orientation::LeftRight, 1) => Edge::Left,
+            (Orientation::LeftRight, _) => Edge::Right,
+        }
+    }
+}
+
+/// Part of the court markings, all drawn again when another [`GameMode`] or [`Orientation`] is picked.
+#[derive(Component)]
+struct CourtMarking;
+
+/// Draws the court markings under the game.
+pub struct CourtPlugin;
+
+impl Plugin for CourtPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Orientation>()
+            .add_systems(Startup, spawn_court)
+            .add_systems(Update, redraw_court_system);
+    }
+}
+
+fn spawn_court(mut commands: Commands, game_mode: Res<GameMode>, orientation: Res<Orientation>) {
+    draw_court(&mut commands, *game_mode, *orientation);
+}
+
+fn redraw_court_system(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    orientation: Res<Orientation>,
+    marking_query: Query<Entity, With<CourtMarking>>,
+) {
+    if !game_mode.is_changed() && !orientation.is_changed() {
+        return;
+    }
+
+    for entity in marking_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    draw_court(&mut commands, *game_mode, *orientation);
+}
+
+/// Four players only get their goal zones, and no centre line.
+fn draw_court(commands: &mut Commands, game_mode: GameMode, orientation: Orientation) {
+    let mut mark = |color: Color, center: Vec2, extent: Vec2, depth: f32| {
+        commands.spawn((
+            Sprite {
+                color,
+                custom_size: Some(extent),
+                ..default()
+            },
+            Transform::from_translation(center.extend(depth)),
+            CourtMarking,
+        ));
+    };
+    let goal_zone_depth = PADDLE_OFFSET + GOAL_ZONE_MARGIN;
+    for player in 1..=game_mode.players() {
+        let edge = player_edge(game_mode, orientation, player);
+        let color = paddle_color(player);
+        if game_mode.players() == 2 {
+            let half = Vec2::new(edge.length(), edge.distance());
+            let half_center = edge.to_world(Vec2::new(0., half.y / 2.));
+            mark(color.with_alpha(HALF_ALPHA), half_center, edge.extent(half), HALF_DEPTH);
+        }
+        let goal_zone = Vec2::new(edge.length(), goal_zone_depth);
+        let goal_zone_center = edge.to_world(Vec2::new(0., edge.distance() - goal_zone_depth / 2.));
+        mark(color.with_alpha(GOAL_ZONE_ALPHA), goal_zone_center, edge.extent(goal_zone), GOAL_ZONE_DEPTH);
+    }
+
+    if game_mode.players() == 2 {
+        // Across the middle, parallel to the paddles.
+        let edge = orientation.edge(1);
+        let dashes = ((edge.length() + DASH_GAP) / (DASH_LENGTH + DASH_GAP)).floor() as usize;
+        let start = -((dashes as f32) * (DASH_LENGTH + DASH_GAP) - DASH_GAP) / 2. + DASH_LENGTH / 2.;
+        for dash in 0..dashes {
+            let x = start + dash as f32 * (DASH_LENGTH + DASH_GAP);
+            let extent = edge.extent(Vec2::new(DASH_LENGTH, LINE_THICKNESS));
+            mark(LINE_COLOR, edge.to_world(Vec2::new(x, 0.)), extent, LINE_DEPTH);
+        }
+    }
+
+    for edge in Edge::ALL {
+        let center = edge.to_world(Vec2::new(0., edge.distance() - LINE_THICKNESS / 2.));
+        mark(LINE_COLOR, center, edge.extent(Vec2::new(edge.length(), LINE_THICKNESS)), LINE_DEPTH);
+    }
+}
@@ -0,0 +1,198 @@
+use bevy::asset::DirectAssetAccessExt;
+use bevy::core_pipeline::core_2d::graph::{Core2d, Node2d};
+use bevy::core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state;
+use bevy::ecs::query::QueryItem;
+use bevy::image::BevyDefault;
+use bevy::prelude::*;
+use bevy::render::extract_component::{
+    ComponentUniforms, DynamicUniformIndex, ExtractComponentPlugin, UniformComponentPlugin,
+};
+use bevy::render::render_graph::{
+    NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+};
+use bevy::render::render_resource::binding_types::{sampler, texture_2d, uniform_buffer};
+use bevy::render::render_resource::{
+    BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId, ColorTargetState, ColorWrites,
+    FragmentState, MultisampleState, Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
+    TextureFormat, TextureSampleType,
+};
+use bevy::render::renderer::{RenderContext, RenderDevice};
+use bevy::render::view::ViewTarget;
+use bevy::render::RenderApp;
+use bevy::ui::graph::NodeUi;
+
+const CRT_SHADER: &str = "shaders/crt.wgsl";
+
+/// Whether the game is drawn like an old CRT television, toggled on the menu.
+#[derive(Resource, Default)]
+pub struct CrtScreen(pub bool);
+
+mod settings {
+    // The checks `ShaderType` derives for each field are never called, so would warn.
+    #![allow(dead_code)]
+
+    use bevy::prelude::*;
+    use bevy::render::extract_component::ExtractComponent;
+    use bevy::render::render_resource::ShaderType;
+
+    /// How strongly the camera it is on bends, stripes and darkens the finished picture, the menu
+    /// and scores included.
+    #[derive(Component, Clone, Copy, ExtractComponent, ShaderType)]
+    pub struct CrtSettings {
+        /// How far the corners are pulled out, as if the picture bulged like the glass of a tube.
+        curvature: f32,
+        /// How dark the scanlines are, from 0 for none to 1 for black.
+        scanlines: f32,
+        /// How much darker the corners are than the middle.
+        vignette: f32,
+    }
+
+    impl Default for CrtSettings {
+        fn default() -> Self {
+            Self { curvature: 0.04, scanlines: 0.25, vignette: 0.35 }
+        }
+    }
+}
+
+use settings::CrtSettings;
+
+/// A CRT post-process pass for the 2D camera.
+pub struct CrtPlugin;
+
+impl Plugin for CrtPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CrtScreen>()
+            .add_plugins((
+                ExtractComponentPlugin::<CrtSettings>::default(),
+                UniformComponentPlugin::<CrtSettings>::default(),
+            ))
+            .add_systems(Update, apply_crt_system.run_if(resource_changed::<CrtScreen>));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        // After the menu and scores are drawn too, before the picture goes to the window.
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<CrtNode>>(Core2d, CrtLabel)
+            .add_render_graph_edges(Core2d, (NodeUi::UiPass, CrtLabel, Node2d::Upscaling));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<CrtPipeline>();
+    }
+}
+
+fn apply_crt_system(mut commands: Commands, crt: Res<CrtScreen>, camera_query: Query<Entity, With<Camera2d>>) {
+    for entity in camera_query.iter() {
+        if crt.0 {
+            commands.entity(entity).insert(CrtSettings::default());
+        } else {
+            commands.entity(entity).remove::<CrtSettings>();
+        }
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct CrtLabel;
+
+#[derive(Resource)]
+struct CrtPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for CrtPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "crt_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<CrtSettings>(true),
+                ),
+            ),
+        );
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        let shader = world.load_asset(CRT_SHADER);
+        let pipeline_id = world.resource_mut::<PipelineCache>().queue_render_pipeline(RenderPipelineDescriptor {
+            label: Some("crt_pipeline".into()),
+            layout: vec![layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader,
+                shader_defs: vec![],
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: TextureFormat::bevy_default(),
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        });
+
+        Self { layout, sampler, pipeline_id }
+    }
+}
+
+#[derive(Default)]
+struct CrtNode;
+
+impl ViewNode for CrtNode {
+    type ViewQuery = (&'static ViewTarget, &'static DynamicUniformIndex<CrtSettings>);
+
+    /// Draws the picture so far again through the CRT shader, for cameras with [`CrtSettings`].
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let crt_pipeline = world.resource::<CrtPipeline>();
+        // The shader may still be loading.
+        let Some(pipeline) = world.resource::<PipelineCache>().get_render_pipeline(crt_pipeline.pipeline_id) else {
+            return Ok(());
+        };
+        let Some(settings_binding) = world.resource::<ComponentUniforms<CrtSettings>>().uniforms().binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "crt_bind_group",
+            &crt_pipeline.layout,
+            &BindGroupEntries::sequential((post_process.source, &crt_pipeline.sampler, settings_binding.clone())),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("crt_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::ball::{Collider, CollisionEvent, GoalEvent};
+use crate::paddle::paddle_color;
+use crate::rules::Rules;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+/// Seconds between the ghosts a [`TrailEmitter`] leaves.
+const TRAIL_INTERVAL: f32 = 0.02;
+/// How long a ghost lasts behind something moving at the emitter's base speed.
+const TRAIL_LIFETIME: f32 = 0.15;
+const TRAIL_ALPHA: f32 = 0.4;
+
+const SPARK_COLOR: Color = Color::srgb(0.95, 0.75, 0.3);
+const SPARK_SIZE: Vec2 = Vec2::new(3., 3.);
+const SPARK_LIFETIME: f32 = 0.35;
+/// Sparks in an impact burst at the serve speed.
+const SPARKS_PER_IMPACT: f32 = 6.;
+const SPARK_SPEED: f32 = 120.;
+/// The share of its speed a drifting particle keeps after a second.
+const DRIFT_RETAINED_PER_SECOND: f32 = 0.05;
+
+/// How much busier effects get at most, relative to the serve speed.
+const MAX_INTENSITY: f32 = 3.;
+
+const SQUASH_DURATION: f32 = 0.15;
+/// How far a paddle's sprite is flattened along the hit, and widened to make up for it, at most.
+const SQUASH_AMOUNT: f32 = 0.4;
+
+/// The [`CameraShake::trauma`] a paddle hit at the serve speed adds.
+const PADDLE_HIT_TRAUMA: f32 = 0.1;
+const SCORE_TRAUMA: f32 = 0.6;
+/// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.5;
+/// How far the camera is thrown at full trauma.
+const MAX_SHAKE_OFFSET: f32 = 12.;
+
+const FLASH_DURATION: f32 = 0.3;
+const FLASH_ALPHA: f32 = 0.35;
+/// Above everything else on the court.
+const FLASH_DEPTH: f32 = 10.;
+
+/// Fades its sprite out from `alpha` as the timer runs, then despawns it.
+#[derive(Component)]
+pub struct Fade {
+    timer: Timer,
+    alpha: f32,
+}
+
+impl Fade {
+    pub fn new(seconds: f32, alpha: f32) -> Self {
+        Self { timer: Timer::from_seconds(seconds, TimerMode::Once), alpha }
+    }
+}
+
+/// Moves a particle along, in pixels per second, slowing as it goes.
+#[derive(Component)]
+pub struct Drift(pub Vec2);
+
+/// Leaves fading ghosts behind its sprite, longer the faster it goes.
+#[derive(Component)]
+pub struct TrailEmitter {
+    base_speed: f32,
+    interval: Timer,
+    last_position: Option<Vec3>,
+}
+
+impl TrailEmitter {
+    pub fn new(base_speed: f32) -> Self {
+        Self {
+            base_speed,
+            interval: Timer::from_seconds(TRAIL_INTERVAL, TimerMode::Repeating),
+            last_position: None,
+        }
+    }
+}
+
+/// Sends `count` particles out in every direction from where it is spawned, then goes.
+#[derive(Component)]
+pub struct BurstEmitter {
+    pub count: u32,
+    pub speed: f32,
+    pub color: Color,
+    pub size: Vec2,
+    pub lifetime: f32,
+}
+
+/// Briefly flattens a paddle's sprite after a hit.
+#[derive(Component)]
+struct Squash(Timer);
+
+/// Camera shake by the square of `trauma`, which decays over time.
+#[derive(Component, Default)]
+pub struct CameraShake {
+    trauma: f32,
+}
+
+/// Trails, sparks, paddle squash, camera shake and goal flashes.
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (impact_burst_system, burst_emitter_system, trail_emitter_system, drift_system, fade_system).chain(),
+        )
+        .add_systems(
+            Update,
+            ((paddle_hit_juice_system, point_juice_system), squash_system, camera_shake_system).chain(),
+        );
+    }
+}
+
+/// Sparks off every paddle and wall bounce, more of them and faster the faster the ball was going.
+fn impact_burst_system(
+    mut commands: Commands,
+    rules: Res<Rules>,
+    mut collision_events: EventReader<CollisionEvent>,
+) {
+    for event in collision_events.read() {
+        let intensity = (event.speed / rules.ball_speed).clamp(1., MAX_INTENSITY);
+        commands.spawn((
+            BurstEmitter {
+                count: (SPARKS_PER_IMPACT * intensity).round() as u32,
+                speed: SPARK_SPEED * intensity,
+                color: SPARK_COLOR,
+                size: SPARK_SIZE,
+                lifetime: SPARK_LIFETIME,
+            },
+            Transform::from_translation(event.position.extend(1.)),
+        ));
+    }
+}
+
+fn burst_emitter_system(mut commands: Commands, emitter_query: Query<(Entity, &BurstEmitter, &Transform)>) {
+    let mut rng = rand::rng();
+
+    for (entity, emitter, transform) in emitter_query.iter() {
+        for _ in 0..emitter.count {
+            let direction = Vec2::from_angle(rng.random_range(0. ..std::f32::consts::TAU));
+            let speed = emitter.speed * rng.random_range(0.5..=1.);
+            commands.spawn((
+                Sprite {
+                    color: emitter.color,
+                    custom_size: Some(emitter.size),
+                    ..default()
+                },
+                *transform,
+                Drift(direction * speed),
+                Fade::new(emitter.lifetime, 1.),
+            ));
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+fn trail_emitter_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut emitter_query: Query<(&Transform, &Sprite, &mut TrailEmitter)>,
+) {
+    for (transform, sprite, mut emitter) in emitter_query.iter_mut() {
+        let intervals = emitter.interval.tick(time.delta()).times_finished_this_tick();
+        if intervals == 0 {
+            continue;
+        }
+
+        let position = transform.translation;
+        let Some(last_position) = emitter.last_position.replace(position) else {
+            continue;
+        };
+        // Nothing to trail behind something standing still, like a ball waiting to be served.
+        let speed = last_position.distance(position) / (emitter.interval.duration().as_secs_f32() * intervals as f32);
+        if speed <= f32::EPSILON {
+            continue;
+        }
+
+        let intensity = (speed / emitter.base_speed).min(MAX_INTENSITY);
+        commands.spawn((
+            Sprite {
+                color: sprite.color.with_alpha(TRAIL_ALPHA),
+                ..sprite.clone()
+            },
+            Transform::from_translation(position - Vec3::Z * 0.1),
+            Fade::new(TRAIL_LIFETIME * intensity, TRAIL_ALPHA),
+        ));
+    }
+}
+
+fn drift_system(time: Res<Time>, mut query: Query<(&mut Transform, &mut Drift)>) {
+    let dt = time.delta_secs();
+
+    for (mut transform, mut drift) in query.iter_mut() {
+        transform.translation += (drift.0 * dt).extend(0.);
+        drift.0 *= DRIFT_RETAINED_PER_SECOND.powf(dt);
+    }
+}
+
+fn fade_system(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Sprite, &mut Fade)>) {
+    for (entity, mut sprite, mut fade) in query.iter_mut() {
+        if fade.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_alpha(fade.alpha * fade.timer.fraction_remaining());
+        }
+    }
+}
+
+/// Squashes the hit paddle and shakes the camera with the ball's speed.
+fn paddle_hit_juice_system(
+    mut commands: Commands,
+    rules: Res<Rules>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut camera_query: Query<&mut CameraShake>,
+) {
+    for event in collision_events.read() {
+        let Collider::Paddle(paddle) = event.collider else {
+            continue;
+        };
+
+        commands.entity(paddle).insert(Squash(Timer::from_seconds(SQUASH_DURATION, TimerMode::Once)));
+
+        let intensity = (event.speed / rules.ball_speed).clamp(1., MAX_INTENSITY);
+        for mut shake in camera_query.iter_mut() {
+            shake.trauma = (shake.trauma + PADDLE_HIT_TRAUMA * intensity).min(1.);
+        }
+    }
+}
+
+/// Shakes the camera hard and flashes the court in the colour of whoever scored.
+fn point_juice_system(
+    mut commands: Commands,
+    mut goal_events: EventReader<GoalEvent>,
+    mut camera_query: Query<&mut CameraShake>,
+) {
+    for event in goal_events.read() {
+        for mut shake in camera_query.iter_mut() {
+            shake.trauma = (shake.trauma + SCORE_TRAUMA).min(1.);
+        }
+
+        // Nobody scores when a player misses a ball they hit last themselves.
+        let Some(scorer) = event.scorer else {
+            continue;
+        };
+        commands.spawn((
+            Sprite {
+                color: paddle_color(scorer).with_alpha(FLASH_ALPHA),
+                custom_size: Some(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
+                ..default()
+            },
+            Transform::from_xyz(0., 0., FLASH_DEPTH),
+            Fade::new(FLASH_DURATION, FLASH_ALPHA),
+        ));
+    }
+}
+
+fn squash_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    rules: Res<Rules>,
+    mut paddle_query: Query<(Entity, &mut Sprite, &mut Squash)>,
+) {
+    for (entity, mut sprite, mut squash) in paddle_query.iter_mut() {
+        // Squashed hardest on impact, springing back as the timer runs out.
+        let amount = SQUASH_AMOUNT * squash.0.tick(time.delta()).fraction_remaining();
+        sprite.custom_size = Some(rules.paddle_size() * Vec2::new(1. + amount / 2., 1. - amount));
+        if squash.0.finished() {
+            commands.entity(entity).remove::<Squash>();
+        }
+    }
+}
+
+fn camera_shake_system(time: Res<Time>, mut camera_query: Query<(&mut Transform, &mut CameraShake)>) {
+    let mut rng = rand::rng();
+
+    for (mut transform, mut shake) in camera_query.iter_mut() {
+        let offset = Vec2::from_angle(rng.random_range(0. ..std::f32::consts::TAU))
+            * MAX_SHAKE_OFFSET
+            * shake.trauma.powi(2);
+        transform.translation = offset.extend(transform.translation.z);
+        shake.trauma = (shake.trauma - TRAUMA_DECAY * time.delta_secs()).max(0.);
+    }
+}
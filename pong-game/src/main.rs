@@ -1,34 +1,99 @@
+mod ai;
+mod arena;
+mod attract;
+mod ball;
+mod court;
+mod crt;
+mod effects;
+mod menu;
+mod network;
+mod paddle;
+mod pointer;
+mod power_up;
+mod replay;
+#[cfg(feature = "net")]
+mod rollback;
+mod rules;
+mod score;
+mod sound;
+mod stats;
+mod tournament;
+mod ui;
+
+use ai::AiPlugin;
+use arena::ArenaPlugin;
+use attract::{Attract, AttractPlugin};
+use ball::{BallPlugin, ServeRng};
 use bevy::prelude::*;
+use court::CourtPlugin;
+use crt::CrtPlugin;
+use effects::{CameraShake, EffectsPlugin};
+use menu::MenuPlugin;
+use network::NetworkPlugin;
+use paddle::{PaddlePlugin, PaddleSet};
+use pointer::PointerPlugin;
+use power_up::PowerUpPlugin;
+use replay::ReplayPlugin;
+use rules::RulesPlugin;
+use score::ScorePlugin;
+use serde::{Deserialize, Serialize};
+use sound::SoundPlugin;
+use stats::StatsPlugin;
+use tournament::TournamentPlugin;
+use ui::UiPlugin;
 
 const WINDOW_WIDTH: f32 = 800.;
 const WINDOW_HEIGHT: f32 = 600.;
 
-const PADDLE_1_COLOR: Color = Color::srgb(0.3, 0.7, 0.3);
-const PADDLE_2_COLOR: Color = Color::srgb(0.3, 0.3, 0.7);
-
-const PADDLE_SIZE: Vec2 = Vec2::new(100., 10.);
-const PADDLE_OFFSET: f32 = 20.;
-const PADDLE_VELOCITY: Vec3 = Vec3::new(400., 0., 0.);
-
-const BALL_COLOR: Color = Color::srgb(0.7, 0.3, 0.3);
-
-const BALL_SIZE: Vec2 = Vec2::new(10., 10.);
-const BALL_VELOCITY: Vec3 = Vec3::new(300., 300., 0.);
+/// How the ball or a paddle moves, in pixels per second.
+#[derive(Component, Clone)]
+struct Velocity(Vec3);
 
-#[derive(Component)]
-struct Paddle {
-    player: u8
+/// Each fixed step: find what the balls hit, then respond to it.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PhysicsSet {
+    Detect,
+    Respond,
 }
 
-#[derive(Component)]
-struct Ball;
-
-#[derive(Component, Clone)]
-struct Velocity(Vec3);
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum MatchState {
+    /// Picking the [`GameMode`](menu::GameMode) on the main menu.
+    #[default]
+    Menu,
+    /// The ball waits in the middle while the countdown to the serve runs.
+    Serving,
+    Playing,
+    /// Stopped mid-match on Escape or when the window loses focus, under a dimmed overlay.
+    Paused,
+    /// Someone won a game but not yet the match; Enter starts the next game.
+    BetweenGames,
+    /// Someone won the match; Enter starts a rematch and Escape goes back to the menu.
+    GameOver,
+    /// Entering the players of a [`Tournament`](tournament::Tournament).
+    TournamentSetup,
+    /// Picking the [`Rules`](rules::Rules) to play by from the rule book.
+    PickingRules,
+    /// Hosting or joining a LAN game, until the other machine is
+    /// [`Connection`](network::Connection)ed.
+    Lobby,
+    /// The point just scored shown again in slow motion, before the serve.
+    InstantReplay,
+    /// Saving the last match and picking a saved one to watch.
+    Replays,
+    /// Watching a saved match from the replays screen.
+    WatchingReplay,
+}
 
 fn main() {
-    App::new()
-        .add_plugins(
+    let seed = std::env::args()
+        .skip_while(|arg| arg != "--seed")
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(rand::random);
+
+    let mut app = App::new();
+    app.add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
@@ -40,121 +105,45 @@ fn main() {
                     ..default()
                 })
         )
+        .add_plugins((
+            RulesPlugin,
+            ArenaPlugin,
+            PaddlePlugin,
+            BallPlugin,
+            ScorePlugin,
+            UiPlugin,
+            AiPlugin,
+            CourtPlugin,
+            EffectsPlugin,
+            MenuPlugin,
+            PointerPlugin,
+            PowerUpPlugin,
+            SoundPlugin,
+            StatsPlugin,
+            TournamentPlugin,
+        ))
+        .add_plugins((NetworkPlugin, ReplayPlugin, AttractPlugin, CrtPlugin))
         .insert_resource(ClearColor(Color::srgb(0.9, 0.9, 0.9)))
-        .add_systems(Startup, setup)
-        .add_systems(Update, (input_system, ball_movement_system, wall_collision_system, paddle_collision_system))
-        .run();
-}
-
-fn setup(mut commands: Commands) {
-    commands.spawn(Camera2d);
-
-    commands.spawn((
-        Sprite {
-            color: PADDLE_1_COLOR,
-            custom_size: Some(PADDLE_SIZE),
-            ..default()
-        },
-        Transform::from_xyz(0., WINDOW_HEIGHT/2. - PADDLE_OFFSET, 0.),
-        Paddle { player: 1 }
-    ));
-
-    commands.spawn((
-        Sprite {
-            color: PADDLE_2_COLOR,
-            custom_size: Some(PADDLE_SIZE),
-            ..default()
-        },
-        Transform::from_xyz(0., -WINDOW_HEIGHT/2. + PADDLE_OFFSET, 0.),
-        Paddle { player: 2 }
-    ));
-    
-    commands.spawn((
-        Sprite {
-            color: BALL_COLOR,
-            custom_size: Some(BALL_SIZE),
-            ..default()
-        },
-        Transform::from_xyz(0., 0., 0.),
-        Ball,
-        Velocity(BALL_VELOCITY),
-    ));
-}
-
-fn input_system(
-    time: Res<Time>,
-    keys: Res<ButtonInput<KeyCode>>, 
-    mut query: Query<(&mut Transform, &Paddle)>
-) {
-    let dt = time.delta_secs();
-
-    for (mut transform, paddle) in query.iter_mut() {
-        match paddle.player {
-            1 => {
-                if keys.pressed(KeyCode::KeyA) && transform.translation.x > -WINDOW_WIDTH / 2. + PADDLE_SIZE.x / 2. {
-                    transform.translation -= PADDLE_VELOCITY * dt;
-                } else if keys.pressed(KeyCode::KeyD) && transform.translation.x < WINDOW_WIDTH / 2. - PADDLE_SIZE.x / 2. {
-                    transform.translation += PADDLE_VELOCITY * dt;
-                }
-            },
-            2 => {
-                if keys.pressed(KeyCode::ArrowLeft) && transform.translation.x > -WINDOW_WIDTH / 2. + PADDLE_SIZE.x / 2. {
-                    transform.translation -= PADDLE_VELOCITY * dt;
-                } else if keys.pressed(KeyCode::ArrowRight) && transform.translation.x < WINDOW_WIDTH / 2. - PADDLE_SIZE.x / 2. {
-                    transform.translation += PADDLE_VELOCITY * dt;
-                }
-            },
-            _ => {}
-        }
-    }
-}
-
-fn ball_movement_system(
-    time: Res<Time>,
-    mut query: Query<(&mut Transform, &Velocity), With<Ball>>,
-) {
-    let dt = time.delta_secs();
-
-    for (mut transform, velocity) in query.iter_mut() {
-        transform.translation += velocity.0 * dt;
-    }
-}
-
-fn wall_collision_system(
-    mut ball_query: Query<(&mut Transform, &mut Velocity), With<Ball>>,
-) {
-    let (ball_transform, mut velocity) = ball_query.single_mut();
-
-    if ball_transform.translation.x < -WINDOW_WIDTH / 2. + BALL_SIZE.x / 2.
-        || ball_transform.translation.x > WINDOW_WIDTH / 2. - BALL_SIZE.x / 2.
-    {
-        velocity.0.x *= -1.;
-    }
-
-    if ball_transform.translation.y < -WINDOW_HEIGHT / 2. + BALL_SIZE.y / 2.
-        || ball_transform.translation.y > WINDOW_HEIGHT / 2. - BALL_SIZE.y / 2.
-    {
-        velocity.0.y *= -1.;
-    }
-}
+        .init_state::<MatchState>()
+        .enable_state_scoped_entities::<MatchState>()
+        .insert_resource(ServeRng::new(seed))
+        // Hosts and rollback sessions move the balls themselves.
+        .configure_sets(
+            FixedUpdate,
+            (PhysicsSet::Detect, PhysicsSet::Respond)
+                .chain()
+                .after(PaddleSet::Move)
+                .run_if(in_state(MatchState::Playing).or(in_state(Attract::Running)))
+                .run_if(network::runs_match),
+        )
+        .add_systems(Startup, setup);
 
-fn paddle_collision_system(
-    mut ball_query: Query<(&Transform, &mut Velocity), With<Ball>>,
-    paddle_query: Query<&Transform, With<Paddle>>,
-) {
-    let (ball_transform, mut velocity) = ball_query.single_mut();
+    #[cfg(feature = "net")]
+    app.add_plugins(rollback::RollbackPlugin);
 
-    for paddle_transform in paddle_query.iter() {
-        if aabb_collision(ball_transform.translation, BALL_SIZE, paddle_transform.translation, PADDLE_SIZE) {
-            velocity.0.y *= -1.;
-            return;
-        }
-    }
+    app.run();
 }
 
-fn aabb_collision(a_pos: Vec3, a_size: Vec2, b_pos: Vec3, b_size: Vec2) -> bool {
-    let collision_x = (a_pos.x - b_pos.x).abs() < (a_size.x + b_size.x) / 2.;
-    let collision_y = (a_pos.y - b_pos.y).abs() < (a_size.y + b_size.y) / 2.;
-    collision_x && collision_y
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2d, CameraShake::default()));
 }
-
@@ -0,0 +1,372 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::{AiPaddle, Difficulty};
+use crate::arena::{Arenas, SelectedArena};
+use crate::ball::BallCount;
+use crate::court::Orientation;
+use crate::crt::CrtScreen;
+use crate::network::RemotePaddle;
+use crate::paddle::Paddle;
+use crate::pointer::{PointerPaddle, PointerPlayers};
+use crate::rules::Rules;
+use crate::score::BestOf;
+use crate::ui::{SCORE_COLOR, SCORE_FONT_SIZE};
+use crate::MatchState;
+
+const BUTTON_SIZE: Vec2 = Vec2::new(240., 28.);
+const BUTTON_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.2, 0.2, 0.6);
+const BUTTON_TEXT_COLOR: Color = Color::srgb(0.95, 0.95, 0.95);
+
+/// The match lengths cycled through on the menu, in games.
+const BEST_OF_OPTIONS: [u32; 3] = [1, 3, 5];
+
+/// The master volumes cycled through on the menu, loudest first.
+const VOLUME_OPTIONS: [f32; 5] = [1., 0.75, 0.5, 0.25, 0.];
+
+/// Who drives each paddle.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Player 1 against the computer on paddle 2.
+    OnePlayer,
+    #[default]
+    TwoPlayers,
+    /// Two players at a time from a bracket of named players, both on the keyboard.
+    Tournament,
+    /// The computer on both paddles, until it is paused to go back to the menu.
+    Demo,
+    /// Against a player on another machine on the LAN, hosted by one of them. There are no pickups,
+    /// which only the host would see.
+    Lan,
+    /// Four players on the keyboard, one on each edge of the court, each with
+    /// [`Lives`](crate::score::Lives) to lose.
+    Quad,
+}
+
+impl GameMode {
+    /// How many paddles are in play.
+    pub fn players(self) -> u8 {
+        match self {
+            GameMode::Quad => 4,
+            _ => 2,
+        }
+    }
+}
+
+/// How good the computer is when it plays, cycled on the menu.
+#[derive(Resource)]
+struct SelectedDifficulty(Difficulty);
+
+impl Default for SelectedDifficulty {
+    fn default() -> Self {
+        Self(Difficulty::Normal)
+    }
+}
+
+/// Who steers with the mouse or a touch, cycled on the menu.
+#[derive(Resource)]
+struct SelectedPointer(PointerPlayers);
+
+impl Default for SelectedPointer {
+    fn default() -> Self {
+        Self(PointerPlayers::Neither)
+    }
+}
+
+/// Everything picked on the menu that a button shows.
+#[derive(SystemParam)]
+struct MenuSettings<'w> {
+    difficulty: Res<'w, SelectedDifficulty>,
+    best_of: Res<'w, BestOf>,
+    ball_count: Res<'w, BallCount>,
+    pointer: Res<'w, SelectedPointer>,
+    volume: Res<'w, GlobalVolume>,
+    rules: Res<'w, Rules>,
+    orientation: Res<'w, Orientation>,
+    arena: Res<'w, SelectedArena>,
+    arenas: Arenas<'w>,
+    crt: Res<'w, CrtScreen>,
+}
+
+impl MenuSettings<'_> {
+    fn is_changed(&self) -> bool {
+        self.difficulty.is_changed()
+            || self.best_of.is_changed()
+            || self.ball_count.is_changed()
+            || self.pointer.is_changed()
+            || self.volume.is_changed()
+            || self.rules.is_changed()
+            || self.orientation.is_changed()
+            || self.arena.is_changed()
+            || self.arenas.is_changed()
+            || self.crt.is_changed()
+    }
+}
+
+/// The main menu's screen, holding its title and buttons.
+#[derive(Component)]
+pub struct MenuScreen;
+
+/// What a button on the main menu does when clicked.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum MenuButton {
+    OnePlayer,
+    TwoPlayers,
+    Tournament,
+    WatchDemo,
+    Quad,
+    Lan,
+    CycleDifficulty,
+    CycleBestOf,
+    ToggleTwoBalls,
+    CyclePointer,
+    CycleVolume,
+    CycleOrientation,
+    CycleArena,
+    ToggleCrt,
+    PickRules,
+    Replays,
+}
+
+impl MenuButton {
+    const ALL: [MenuButton; 16] = [
+        MenuButton::OnePlayer,
+        MenuButton::TwoPlayers,
+        MenuButton::Tournament,
+        MenuButton::WatchDemo,
+        MenuButton::Quad,
+        MenuButton::Lan,
+        MenuButton::CycleDifficulty,
+        MenuButton::CycleBestOf,
+        MenuButton::ToggleTwoBalls,
+        MenuButton::CyclePointer,
+        MenuButton::CycleVolume,
+        MenuButton::CycleOrientation,
+        MenuButton::CycleArena,
+        MenuButton::ToggleCrt,
+        MenuButton::PickRules,
+        MenuButton::Replays,
+    ];
+
+    fn label(self, settings: &MenuSettings) -> String {
+        match self {
+            MenuButton::OnePlayer => "1 Player".to_string(),
+            MenuButton::TwoPlayers => "2 Players".to_string(),
+            MenuButton::Tournament => "Tournament".to_string(),
+            MenuButton::WatchDemo => "Watch Demo".to_string(),
+            MenuButton::Quad => "4-Player Quad".to_string(),
+            MenuButton::Lan => "LAN Game".to_string(),
+            MenuButton::CycleDifficulty => format!("Computer: {}", settings.difficulty.0.name()),
+            MenuButton::CycleBestOf => format!("Best of: {}", settings.best_of.0),
+            MenuButton::ToggleTwoBalls => {
+                format!("Two-ball chaos: {}", if settings.ball_count.0 > 1 { "On" } else { "Off" })
+            }
+            MenuButton::CyclePointer => format!("Mouse/touch: {}", settings.pointer.0.name()),
+            MenuButton::CycleVolume => format!("Volume: {:.0}%", settings.volume.volume.get() * 100.),
+            MenuButton::CycleOrientation => format!("Paddles: {}", settings.orientation.name()),
+            MenuButton::CycleArena => format!("Arena: {}", settings.arenas.name(&settings.arena)),
+            MenuButton::ToggleCrt => format!("CRT screen: {}", if settings.crt.0 { "On" } else { "Off" }),
+            MenuButton::PickRules => format!("Rules: {}", settings.rules.name),
+            MenuButton::Replays => "Replays".to_string(),
+        }
+    }
+}
+
+/// The main menu, and handing paddles to their controllers.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>()
+            .init_resource::<SelectedDifficulty>()
+            .init_resource::<SelectedPointer>()
+            .add_systems(OnEnter(MatchState::Menu), spawn_menu)
+            .add_systems(
+                Update,
+                (menu_button_system, menu_button_label_system).chain().run_if(in_state(MatchState::Menu)),
+            )
+            .add_systems(OnExit(MatchState::Menu), assign_controllers);
+    }
+}
+
+fn spawn_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.),
+                ..default()
+            },
+            StateScoped(MatchState::Menu),
+            MenuScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Pong"),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 3.,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+            ));
+
+            for button in MenuButton::ALL {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(BUTTON_SIZE.x),
+                            height: Val::Px(BUTTON_SIZE.y),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(BUTTON_COLOR),
+                        button,
+                    ))
+                    .with_child((
+                        Text::default(),
+                        TextFont {
+                            font_size: SCORE_FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(BUTTON_TEXT_COLOR),
+                    ));
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_button_system(
+    mut query: Query<(&Interaction, &MenuButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut game_mode: ResMut<GameMode>,
+    mut difficulty: ResMut<SelectedDifficulty>,
+    mut best_of: ResMut<BestOf>,
+    mut ball_count: ResMut<BallCount>,
+    mut pointer: ResMut<SelectedPointer>,
+    mut volume: ResMut<GlobalVolume>,
+    mut orientation: ResMut<Orientation>,
+    mut arena: ResMut<SelectedArena>,
+    arenas: Arenas,
+    mut crt: ResMut<CrtScreen>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    for (interaction, button, mut background) in query.iter_mut() {
+        *background = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        let mode = match button {
+            MenuButton::OnePlayer => GameMode::OnePlayer,
+            MenuButton::TwoPlayers => GameMode::TwoPlayers,
+            MenuButton::Tournament => GameMode::Tournament,
+            MenuButton::WatchDemo => GameMode::Demo,
+            MenuButton::Quad => GameMode::Quad,
+            MenuButton::Lan => GameMode::Lan,
+            MenuButton::CycleDifficulty => {
+                difficulty.0 = difficulty.0.next();
+                continue;
+            }
+            MenuButton::CycleBestOf => {
+                let current = BEST_OF_OPTIONS.iter().position(|&option| option == best_of.0).unwrap_or(0);
+                best_of.0 = BEST_OF_OPTIONS[(current + 1) % BEST_OF_OPTIONS.len()];
+                continue;
+            }
+            MenuButton::ToggleTwoBalls => {
+                ball_count.0 = if ball_count.0 > 1 { 1 } else { 2 };
+                continue;
+            }
+            MenuButton::CyclePointer => {
+                pointer.0 = pointer.0.next();
+                continue;
+            }
+            MenuButton::CycleVolume => {
+                let current = VOLUME_OPTIONS.iter().position(|&option| option == volume.volume.get()).unwrap_or(0);
+                *volume = GlobalVolume::new(VOLUME_OPTIONS[(current + 1) % VOLUME_OPTIONS.len()]);
+                continue;
+            }
+            MenuButton::CycleOrientation => {
+                *orientation = orientation.next();
+                continue;
+            }
+            MenuButton::CycleArena => {
+                *arena = arenas.next(&arena);
+                continue;
+            }
+            MenuButton::ToggleCrt => {
+                crt.0 = !crt.0;
+                continue;
+            }
+            MenuButton::PickRules => {
+                next_state.set(MatchState::PickingRules);
+                continue;
+            }
+            MenuButton::Replays => {
+                next_state.set(MatchState::Replays);
+                continue;
+            }
+        };
+        *game_mode = mode;
+        next_state.set(match mode {
+            GameMode::Tournament => MatchState::TournamentSetup,
+            GameMode::Lan => MatchState::Lobby,
+            _ => MatchState::Serving,
+        });
+    }
+}
+
+fn menu_button_label_system(
+    settings: MenuSettings,
+    button_query: Query<(Ref<MenuButton>, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (button, children) in button_query.iter() {
+        if !button.is_added() && !settings.is_changed() {
+            continue;
+        }
+
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0 = button.label(&settings);
+        }
+    }
+}
+
+/// Gives each paddle to the computer, a pointer or the keyboard.
+fn assign_controllers(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    difficulty: Res<SelectedDifficulty>,
+    pointer: Res<SelectedPointer>,
+    paddle_query: Query<(Entity, &Paddle)>,
+) {
+    for (entity, paddle) in paddle_query.iter() {
+        let computer = match *game_mode {
+            GameMode::OnePlayer => paddle.player == 2,
+            GameMode::TwoPlayers | GameMode::Tournament | GameMode::Lan | GameMode::Quad => false,
+            GameMode::Demo => true,
+        };
+
+        let mut paddle_commands = commands.entity(entity);
+        paddle_commands.remove::<(AiPaddle, PointerPaddle, RemotePaddle)>();
+        if computer {
+            paddle_commands.insert(AiPaddle::new(difficulty.0));
+        } else if pointer.0.controls(paddle.player) {
+            paddle_commands.insert(PointerPaddle);
+        }
+    }
+}
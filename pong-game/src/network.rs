@@ -0,0 +1,592 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::AiPaddle;
+use crate::arena::SelectedArena;
+use crate::ball::{ball_bundle, Ball, BallCount, LastHitBy, Rally, ServeRng};
+use crate::court::Orientation;
+use crate::paddle::{Paddle, PaddleSet};
+use crate::pointer::PointerPaddle;
+#[cfg(feature = "net")]
+use crate::rollback::PeerMessageEvent;
+use crate::rules::Rules;
+use crate::score::{BestOf, Games, PlayerNames, Score};
+use crate::ui::{SCORE_COLOR, SCORE_FONT_SIZE};
+use crate::{MatchState, PhysicsSet, Velocity};
+
+/// The port a host listens on.
+const LAN_PORT: u16 = 7777;
+/// Seconds between a guest's hellos until the host answers.
+const HELLO_INTERVAL: f32 = 0.5;
+/// Seconds without a word from the other machine before giving up on it.
+const CONNECTION_TIMEOUT: f32 = 5.;
+/// Longest message either machine sends, in bytes.
+const MAX_MESSAGE_SIZE: usize = 4096;
+const ADDRESS_MAX_LENGTH: usize = 21;
+/// Shown instead of hosting or joining when rollback couldn't play the settings picked.
+const ROLLBACK_SETTINGS: &str = "LAN games are one ball on the open court:\nturn off two-ball chaos and the arena";
+
+/// The host runs the match; the guest plays paddle 2 and shows it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Role {
+    Host,
+    Guest,
+}
+
+impl Role {
+    fn local_player(self) -> u8 {
+        match self {
+            Role::Host => 1,
+            Role::Guest => 2,
+        }
+    }
+}
+
+/// The host's match, as much of it as the guest needs to show it, sent every fixed step.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    state: MatchState,
+    score: [u32; 2],
+    games: [u32; 2],
+    rally_hits: u32,
+    rally_speed: f32,
+    /// Paddles 1 and 2.
+    paddles: [Vec2; 2],
+    /// Each ball's position and velocity.
+    balls: Vec<(Vec2, Vec2)>,
+}
+
+/// What the two machines say to each other, one message to a packet.
+#[derive(Serialize, Deserialize)]
+pub enum Message {
+    /// A guest asking to join, again and again until it is welcomed.
+    Hello,
+    /// The host taking a guest in, with the settings its match is played by and its serve seed.
+    Welcome { rules: Rules, best_of: u32, orientation: Orientation, arena: Option<usize>, seed: u64 },
+    /// Which way the sender's paddle is being steered, from -1 to 1.
+    Input { direction: f32 },
+    Snapshot(Snapshot),
+    /// The sender's inputs from `frame` on that the other machine hasn't acknowledged, and
+    /// acknowledging its inputs before `ack`.
+    #[cfg(feature = "net")]
+    Inputs { ack: u32, frame: u32, inputs: Vec<i8> },
+    /// The sender's match as it stood before `frame`, to check both are playing the same one.
+    #[cfg(feature = "net")]
+    Checksum { frame: u32, checksum: u64 },
+    /// The sender has left the game.
+    Bye,
+}
+
+/// The socket to the other machine of a LAN game.
+#[derive(Resource)]
+pub struct Connection {
+    socket: UdpSocket,
+    role: Role,
+    /// The other machine: where the guest was told to join, or where the host heard from.
+    peer: Option<SocketAddr>,
+    connected: bool,
+    /// Seconds since anything was heard from the peer.
+    silence: f32,
+    hello: Timer,
+}
+
+impl Connection {
+    fn new(socket: UdpSocket, role: Role, peer: Option<SocketAddr>) -> std::io::Result<Self> {
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            role,
+            peer,
+            connected: false,
+            silence: 0.,
+            hello: Timer::from_seconds(HELLO_INTERVAL, TimerMode::Repeating),
+        })
+    }
+
+    pub fn local_player(&self) -> u8 {
+        self.role.local_player()
+    }
+
+    pub fn send(&self, message: &Message) {
+        let Some(peer) = self.peer else {
+            return;
+        };
+
+        match ron::to_string(message) {
+            Ok(text) => {
+                if let Err(error) = self.socket.send_to(text.as_bytes(), peer) {
+                    warn!("couldn't send to {peer}: {error}");
+                }
+            }
+            Err(error) => warn!("couldn't encode a message: {error}"),
+        }
+    }
+
+    /// Every message waiting on the socket, with who sent it. Anything that doesn't read as a
+    /// message is dropped.
+    fn receive(&self) -> Vec<(Message, SocketAddr)> {
+        let mut messages = Vec::new();
+        let mut buffer = [0; MAX_MESSAGE_SIZE];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((length, from)) => match ron::de::from_bytes(&buffer[..length]) {
+                    Ok(message) => messages.push((message, from)),
+                    Err(error) => warn!("dropped a message from {from}: {error}"),
+                },
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    warn!("couldn't receive: {error}");
+                    break;
+                }
+            }
+        }
+        messages
+    }
+}
+
+/// Whether this machine is the guest of a LAN game.
+pub fn is_guest(connection: Option<Res<Connection>>) -> bool {
+    connection.is_some_and(|connection| connection.role == Role::Guest)
+}
+
+/// Whether this machine simulates the match itself rather than showing the host's or rollback's.
+pub fn runs_match(connection: Option<Res<Connection>>) -> bool {
+    connection.is_none_or(|connection| connection.role == Role::Host && !cfg!(feature = "net"))
+}
+
+/// Whether this machine is in a LAN game played by rollback, with the `net` feature.
+pub fn plays_rollback(connection: Option<Res<Connection>>) -> bool {
+    cfg!(feature = "net") && connection.is_some()
+}
+
+pub fn is_connected(connection: Option<Res<Connection>>) -> bool {
+    connection.is_some_and(|connection| connection.connected)
+}
+
+/// The other machine's paddle, steered by the inputs it sends.
+#[derive(Component)]
+pub struct RemotePaddle;
+
+/// Which way the other machine's player is steering.
+#[derive(Event)]
+struct RemoteInputEvent {
+    direction: f32,
+}
+
+/// The latest of the host's match, for the guest to show.
+#[derive(Event)]
+struct SnapshotEvent(Snapshot);
+
+/// The settings a host's match is played by, sent to the guest when it joins.
+#[derive(SystemParam)]
+struct MatchSettings<'w> {
+    rules: ResMut<'w, Rules>,
+    best_of: ResMut<'w, BestOf>,
+    orientation: ResMut<'w, Orientation>,
+    arena: ResMut<'w, SelectedArena>,
+    serve_rng: ResMut<'w, ServeRng>,
+}
+
+impl MatchSettings<'_> {
+    fn welcome(&self) -> Message {
+        Message::Welcome {
+            rules: self.rules.clone(),
+            best_of: self.best_of.0,
+            orientation: *self.orientation,
+            arena: self.arena.0,
+            seed: self.serve_rng.seed,
+        }
+    }
+}
+
+/// The address being typed in the lobby, and how hosting or joining is going.
+#[derive(Resource, Default)]
+struct LobbyEntry {
+    address: String,
+    status: String,
+}
+
+#[derive(Component)]
+struct LobbyText;
+
+/// Two-machine LAN games over UDP, joined by IP address.
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RemoteInputEvent>()
+            .add_event::<SnapshotEvent>()
+            .add_systems(OnEnter(MatchState::Lobby), spawn_lobby_screen)
+            .add_systems(
+                Update,
+                (lobby_input_system, handshake_system, lobby_text_system)
+                    .chain()
+                    .run_if(in_state(MatchState::Lobby)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    receive_system.before(PaddleSet::Control),
+                    (
+                        apply_snapshot_system.after(receive_system).before(PaddleSet::Control).run_if(is_guest),
+                        remote_input_system.after(receive_system).in_set(PaddleSet::Control),
+                        send_system.after(PaddleSet::Control).after(PhysicsSet::Respond),
+                    )
+                        .run_if(not(plays_rollback)),
+                )
+                    .run_if(is_connected),
+            )
+            .add_systems(Update, timeout_system.run_if(is_connected))
+            .add_systems(OnEnter(MatchState::Menu), disconnect);
+    }
+}
+
+fn spawn_lobby_screen(mut commands: Commands) {
+    commands.insert_resource(LobbyEntry::default());
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            StateScoped(MatchState::Lobby),
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: SCORE_FONT_SIZE,
+                ..default()
+            },
+            TextColor(SCORE_COLOR),
+            TextLayout::new_with_justify(JustifyText::Center),
+            LobbyText,
+        ));
+}
+
+/// Hosts on H, or joins the address typed on Enter, and goes back to the menu on Escape.
+fn lobby_input_system(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut entry: ResMut<LobbyEntry>,
+    connection: Option<Res<Connection>>,
+    arena: Res<SelectedArena>,
+    ball_count: Res<BallCount>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    // Rollback plays one ball on the open court, and a guest plays on the host's court.
+    let too_many_balls = cfg!(feature = "net") && ball_count.0 > 1;
+    let arena_picked = cfg!(feature = "net") && arena.0.is_some();
+
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &event.logical_key {
+            Key::Escape => {
+                next_state.set(MatchState::Menu);
+                return;
+            }
+            // Already hosting or joining.
+            _ if connection.is_some() => {}
+            Key::Character(text) if text.eq_ignore_ascii_case("h") && (too_many_balls || arena_picked) => {
+                entry.status = ROLLBACK_SETTINGS.to_string();
+            }
+            Key::Enter if too_many_balls => entry.status = ROLLBACK_SETTINGS.to_string(),
+            Key::Character(text) if text.eq_ignore_ascii_case("h") => {
+                let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, LAN_PORT));
+                match socket.and_then(|socket| Connection::new(socket, Role::Host, None)) {
+                    Ok(connection) => {
+                        let address = local_ip().map_or(format!("port {LAN_PORT}"), |ip| format!("{ip}:{LAN_PORT}"));
+                        entry.status = format!("Hosting on {address}, waiting for a player to join...");
+                        commands.insert_resource(connection);
+                    }
+                    Err(error) => entry.status = format!("Couldn't host on port {LAN_PORT}: {error}"),
+                }
+            }
+            Key::Character(text) => {
+                let address_characters =
+                    text.chars().filter(|&character| character.is_ascii_digit() || ".:".contains(character));
+                for character in address_characters {
+                    if entry.address.len() < ADDRESS_MAX_LENGTH {
+                        entry.address.push(character);
+                    }
+                }
+            }
+            Key::Backspace => {
+                entry.address.pop();
+            }
+            Key::Enter => {
+                // A bare IP address joins on the usual port.
+                let peer = entry.address.parse::<SocketAddr>().or_else(|_| {
+                    entry.address.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, LAN_PORT))
+                });
+                let Ok(peer) = peer else {
+                    entry.status = format!("\"{}\" isn't an IP address", entry.address);
+                    continue;
+                };
+
+                let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0));
+                match socket.and_then(|socket| Connection::new(socket, Role::Guest, Some(peer))) {
+                    Ok(connection) => {
+                        entry.status = format!("Joining {peer}...");
+                        commands.insert_resource(connection);
+                    }
+                    Err(error) => entry.status = format!("Couldn't join {peer}: {error}"),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// This machine's LAN address, found by connecting a UDP socket without sending.
+fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(192, 168, 0, 1), LAN_PORT)).ok()?;
+    socket.local_addr().ok().map(|address| address.ip())
+}
+
+/// Exchanges hello and welcome until both machines are in the match.
+fn handshake_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    connection: Option<ResMut<Connection>>,
+    mut settings: MatchSettings,
+    mut names: ResMut<PlayerNames>,
+    paddle_query: Query<(Entity, &Paddle)>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    let Some(mut connection) = connection else {
+        return;
+    };
+
+    if connection.role == Role::Guest && connection.hello.tick(time.delta()).just_finished() {
+        connection.send(&Message::Hello);
+    }
+
+    for (message, from) in connection.receive() {
+        match (connection.role, message) {
+            (Role::Host, Message::Hello) => {
+                connection.peer = Some(from);
+                connection.send(&settings.welcome());
+            }
+            (Role::Guest, Message::Welcome { rules, best_of, orientation, arena, seed })
+                if Some(from) == connection.peer =>
+            {
+                *settings.rules = rules;
+                settings.best_of.0 = best_of;
+                *settings.orientation = orientation;
+                settings.arena.0 = arena;
+                *settings.serve_rng = ServeRng::new(seed);
+            }
+            _ => continue,
+        }
+
+        info!("LAN game with {from} as the {:?}", connection.role);
+        connection.connected = true;
+        names.0[0] = "Host".to_string();
+        names.0[1] = "Guest".to_string();
+        let remote_player = 3 - connection.local_player();
+        for (entity, paddle) in paddle_query.iter() {
+            if paddle.player == remote_player {
+                commands.entity(entity).remove::<(AiPaddle, PointerPaddle)>().insert(RemotePaddle);
+            }
+        }
+        next_state.set(MatchState::Serving);
+        return;
+    }
+}
+
+fn lobby_text_system(entry: Res<LobbyEntry>, mut query: Query<&mut Text, With<LobbyText>>) {
+    if !entry.is_changed() {
+        return;
+    }
+
+    let lines = [
+        "LAN Game".to_string(),
+        String::new(),
+        "Press H to host, or type the host's IP address and press Enter to join".to_string(),
+        format!("Host: {}_", entry.address),
+        String::new(),
+        entry.status.clone(),
+        String::new(),
+        "The host plays paddle 1 and the guest paddle 2, on their usual keys".to_string(),
+        "Press Escape for the menu".to_string(),
+    ];
+    for mut text in query.iter_mut() {
+        text.0 = lines.join("\n");
+    }
+}
+
+/// Reads the peer's messages, re-welcoming a guest and leaving when the peer does.
+fn receive_system(
+    mut connection: ResMut<Connection>,
+    settings: MatchSettings,
+    mut inputs: EventWriter<RemoteInputEvent>,
+    mut snapshots: EventWriter<SnapshotEvent>,
+    #[cfg(feature = "net")] mut rollback_messages: EventWriter<PeerMessageEvent>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    for (message, from) in connection.receive() {
+        if Some(from) != connection.peer {
+            continue;
+        }
+
+        connection.silence = 0.;
+        match message {
+            Message::Hello if connection.role == Role::Host => connection.send(&settings.welcome()),
+            Message::Input { direction } => {
+                inputs.send(RemoteInputEvent { direction: direction.clamp(-1., 1.) });
+            }
+            Message::Snapshot(snapshot) if connection.role == Role::Guest => {
+                snapshots.send(SnapshotEvent(snapshot));
+            }
+            #[cfg(feature = "net")]
+            message @ (Message::Inputs { .. } | Message::Checksum { .. }) => {
+                rollback_messages.send(PeerMessageEvent(message));
+            }
+            Message::Bye => {
+                info!("{from} left the LAN game");
+                next_state.set(MatchState::Menu);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Steers the [`RemotePaddle`] the way the other machine's player last steered it.
+fn remote_input_system(
+    rules: Res<Rules>,
+    mut inputs: EventReader<RemoteInputEvent>,
+    mut query: Query<(&mut Velocity, &Paddle), With<RemotePaddle>>,
+) {
+    let Some(input) = inputs.read().last() else {
+        return;
+    };
+
+    for (mut velocity, paddle) in query.iter_mut() {
+        velocity.0 = (paddle.edge.along() * input.direction * rules.paddle_speed).extend(0.);
+    }
+}
+
+/// Applies the host's latest state on the guest.
+#[allow(clippy::too_many_arguments)]
+fn apply_snapshot_system(
+    mut commands: Commands,
+    mut snapshots: EventReader<SnapshotEvent>,
+    rules: Res<Rules>,
+    state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut score: ResMut<Score>,
+    mut games: ResMut<Games>,
+    mut rally: ResMut<Rally>,
+    mut paddle_query: Query<(&mut Transform, &Paddle), Without<Ball>>,
+    mut ball_query: Query<(Entity, &mut Transform, &mut Velocity), With<Ball>>,
+) {
+    let Some(SnapshotEvent(snapshot)) = snapshots.read().last() else {
+        return;
+    };
+
+    let paused = [*state.get(), snapshot.state].contains(&MatchState::Paused);
+    if !paused && *state.get() != snapshot.state {
+        next_state.set(snapshot.state);
+    }
+
+    [score.player_1, score.player_2] = snapshot.score;
+    [games.player_1, games.player_2] = snapshot.games;
+    rally.hits = snapshot.rally_hits;
+    rally.speed = snapshot.rally_speed;
+
+    for (mut transform, paddle) in paddle_query.iter_mut() {
+        if let Some(position) = snapshot.paddles.get(usize::from(paddle.player - 1)) {
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+
+    // Balls come and go on the host with multi-ball pickups and serves.
+    let mut balls = ball_query.iter_mut();
+    for &(position, velocity) in &snapshot.balls {
+        if let Some((_, mut transform, mut ball_velocity)) = balls.next() {
+            transform.translation = position.extend(transform.translation.z);
+            ball_velocity.0 = velocity.extend(0.);
+        } else {
+            commands.spawn((ball_bundle(position.extend(0.), velocity.extend(0.), &rules), LastHitBy(None)));
+        }
+    }
+    for (entity, ..) in balls {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Sends the local paddle input, and the match state from the host.
+#[allow(clippy::too_many_arguments)]
+fn send_system(
+    connection: Res<Connection>,
+    rules: Res<Rules>,
+    state: Res<State<MatchState>>,
+    score: Res<Score>,
+    games: Res<Games>,
+    rally: Res<Rally>,
+    paddle_query: Query<(&Transform, &Velocity, &Paddle), Without<Ball>>,
+    ball_query: Query<(&Transform, &Velocity), With<Ball>>,
+) {
+    let local_player = connection.local_player();
+    if let Some((_, velocity, paddle)) = paddle_query.iter().find(|(_, _, paddle)| paddle.player == local_player) {
+        let direction = velocity.0.truncate().dot(paddle.edge.along()) / rules.paddle_speed;
+        connection.send(&Message::Input { direction });
+    }
+
+    if connection.role != Role::Host {
+        return;
+    }
+
+    let mut paddles = [Vec2::ZERO; 2];
+    for (transform, _, paddle) in paddle_query.iter() {
+        if let Some(position) = paddles.get_mut(usize::from(paddle.player - 1)) {
+            *position = transform.translation.truncate();
+        }
+    }
+    connection.send(&Message::Snapshot(Snapshot {
+        state: *state.get(),
+        score: [score.player_1, score.player_2],
+        games: [games.player_1, games.player_2],
+        rally_hits: rally.hits,
+        rally_speed: rally.speed,
+        paddles,
+        balls: ball_query
+            .iter()
+            .map(|(transform, velocity)| (transform.translation.truncate(), velocity.0.truncate()))
+            .collect(),
+    }));
+}
+
+/// Gives up on the other machine after [`CONNECTION_TIMEOUT`] without a word from it.
+fn timeout_system(
+    time: Res<Time>,
+    mut connection: ResMut<Connection>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    connection.silence += time.delta_secs();
+    if connection.silence > CONNECTION_TIMEOUT {
+        warn!("lost the LAN game: nothing heard for {CONNECTION_TIMEOUT} seconds");
+        next_state.set(MatchState::Menu);
+    }
+}
+
+/// Says goodbye to the other machine, if there is one, and closes the socket.
+fn disconnect(mut commands: Commands, connection: Option<Res<Connection>>) {
+    if let Some(connection) = connection {
+        connection.send(&Message::Bye);
+        commands.remove_resource::<Connection>();
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+
+use crate::ai::AiPaddle;
+use crate::attract::Attract;
+use crate::court::{Edge, Orientation};
+use crate::menu::GameMode;
+use crate::network::RemotePaddle;
+use crate::pointer::PointerPaddle;
+use crate::rules::Rules;
+use crate::score::Lives;
+use crate::{MatchState, Velocity};
+
+pub const PADDLE_1_COLOR: Color = Color::srgb(0.3, 0.7, 0.3);
+pub const PADDLE_2_COLOR: Color = Color::srgb(0.3, 0.3, 0.7);
+pub const PADDLE_3_COLOR: Color = Color::srgb(0.75, 0.5, 0.2);
+pub const PADDLE_4_COLOR: Color = Color::srgb(0.6, 0.3, 0.6);
+
+pub const PADDLE_OFFSET: f32 = 20.;
+
+#[derive(Component)]
+pub struct Paddle {
+    pub player: u8,
+    /// The edge of the court it defends and slides along.
+    pub edge: Edge,
+}
+
+/// A paddle out of the match; its edge acts as a wall.
+#[derive(Component)]
+pub struct Eliminated;
+
+/// Paddles are steered, by the keyboard or the computer, before they move.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaddleSet {
+    Control,
+    Move,
+}
+
+/// The paddles, one per player on the edges the [`GameMode`] and [`Orientation`] give them.
+pub struct PaddlePlugin;
+
+impl Plugin for PaddlePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            FixedUpdate,
+            (PaddleSet::Control, PaddleSet::Move)
+                .chain()
+                .run_if(
+                    in_state(MatchState::Playing).or(in_state(MatchState::Serving)).or(in_state(Attract::Running)),
+                ),
+        )
+        .add_systems(Startup, (spawn_paddles, seat_paddles).chain())
+        .add_systems(FixedUpdate, input_system.in_set(PaddleSet::Control))
+        .add_systems(FixedUpdate, paddle_movement_system.in_set(PaddleSet::Move))
+        .add_systems(Update, resize_paddles.run_if(resource_changed::<Rules>))
+        .add_systems(Update, seat_paddles.run_if(resource_changed::<Orientation>))
+        .add_systems(Update, eliminate_paddles.run_if(resource_changed::<Lives>))
+        .add_systems(OnEnter(MatchState::Menu), seat_paddles)
+        .add_systems(OnExit(MatchState::Menu), seat_paddles);
+
+        // Every new game, whether a rematch or the next of the match.
+        for exited in [MatchState::GameOver, MatchState::BetweenGames] {
+            app.add_systems(OnTransition { exited, entered: MatchState::Serving }, seat_paddles);
+        }
+    }
+}
+
+pub fn paddle_color(player: u8) -> Color {
+    match player {
+        1 => PADDLE_1_COLOR,
+        2 => PADDLE_2_COLOR,
+        3 => PADDLE_3_COLOR,
+        _ => PADDLE_4_COLOR,
+    }
+}
+
+/// The edge `player` defends.
+pub fn player_edge(game_mode: GameMode, orientation: Orientation, player: u8) -> Edge {
+    if game_mode.players() == 4 {
+        Edge::ALL[usize::from(player - 1)]
+    } else {
+        orientation.edge(player)
+    }
+}
+
+fn spawn_paddles(mut commands: Commands, rules: Res<Rules>) {
+    for player in 1..=4 {
+        commands.spawn((
+            Sprite {
+                color: paddle_color(player),
+                custom_size: Some(rules.paddle_size()),
+                ..default()
+            },
+            Transform::default(),
+            Paddle { player, edge: Edge::ALL[usize::from(player - 1)] },
+            Velocity(Vec3::ZERO),
+        ));
+    }
+}
+
+/// Re-centres the players in the match and takes the rest out.
+fn seat_paddles(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    orientation: Res<Orientation>,
+    mut query: Query<(Entity, &mut Transform, &mut Paddle)>,
+) {
+    for (entity, mut transform, mut paddle) in query.iter_mut() {
+        paddle.edge = player_edge(*game_mode, *orientation, paddle.player);
+        let translation = paddle.edge.to_world(Vec2::new(0., paddle.edge.distance() - PADDLE_OFFSET));
+        transform.translation = translation.extend(transform.translation.z);
+        transform.rotation = paddle.edge.rotation();
+
+        if paddle.player <= game_mode.players() {
+            commands.entity(entity).remove::<Eliminated>().insert(Visibility::Inherited);
+        } else {
+            commands.entity(entity).insert((Eliminated, Visibility::Hidden));
+        }
+    }
+}
+
+/// Takes out every quad pong player who has run out of [`Lives`].
+fn eliminate_paddles(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    lives: Res<Lives>,
+    query: Query<(Entity, &Paddle), Without<Eliminated>>,
+) {
+    if game_mode.players() != 4 {
+        return;
+    }
+
+    for (entity, paddle) in query.iter() {
+        if lives.left(paddle.player) == 0 {
+            commands.entity(entity).insert((Eliminated, Visibility::Hidden));
+        }
+    }
+}
+
+/// A paddle's size under the [`Rules`], scaled by its [`Transform`].
+pub fn paddle_size(rules: &Rules, transform: &Transform) -> Vec2 {
+    rules.paddle_size() * transform.scale.truncate()
+}
+
+fn resize_paddles(rules: Res<Rules>, mut query: Query<&mut Sprite, With<Paddle>>) {
+    for mut sprite in query.iter_mut() {
+        sprite.custom_size = Some(rules.paddle_size());
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    rules: Res<Rules>,
+    mut query: Query<(&mut Velocity, &Paddle), (Without<AiPaddle>, Without<PointerPaddle>, Without<RemotePaddle>)>,
+) {
+    for (mut velocity, paddle) in query.iter_mut() {
+        let sideways = matches!(paddle.edge, Edge::Top | Edge::Bottom);
+        let (back, forward) = match (paddle.player, sideways) {
+            (1, true) => (KeyCode::KeyA, KeyCode::KeyD),
+            (1, false) => (KeyCode::KeyS, KeyCode::KeyW),
+            (2, true) => (KeyCode::ArrowLeft, KeyCode::ArrowRight),
+            (2, false) => (KeyCode::ArrowDown, KeyCode::ArrowUp),
+            (3, _) => (KeyCode::KeyG, KeyCode::KeyT),
+            (4, _) => (KeyCode::KeyK, KeyCode::KeyI),
+            _ => continue,
+        };
+
+        let direction = if keys.pressed(back) {
+            -1.
+        } else if keys.pressed(forward) {
+            1.
+        } else {
+            0.
+        };
+        velocity.0 = (paddle.edge.along() * direction * rules.paddle_speed).extend(0.);
+    }
+}
+
+/// Moves the paddles along their edges by their [`Velocity`], stopping them at the corners.
+fn paddle_movement_system(
+    time: Res<Time>,
+    rules: Res<Rules>,
+    mut query: Query<(&mut Transform, &mut Velocity, &Paddle)>,
+) {
+    let dt = time.delta_secs();
+
+    for (mut transform, mut velocity, paddle) in query.iter_mut() {
+        let edge = paddle.edge;
+        let limit = edge.length() / 2. - paddle_size(&rules, &transform).x / 2.;
+        let position = edge.to_local(transform.translation.truncate());
+        let along = position.x + velocity.0.truncate().dot(edge.along()) * dt;
+        let clamped = along.clamp(-limit, limit);
+        let z = transform.translation.z;
+        transform.translation = edge.to_world(Vec2::new(clamped, position.y)).extend(z);
+        // Against a side it isn't moving, so it has no momentum to give the ball.
+        if along != clamped {
+            velocity.0 = Vec3::ZERO;
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::paddle::{Paddle, PaddleSet};
+use crate::rules::Rules;
+use crate::Velocity;
+
+/// Which players steer with the mouse or a touch instead of the keyboard.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PointerPlayers {
+    Neither,
+    PlayerOne,
+    PlayerTwo,
+    Both,
+}
+
+impl PointerPlayers {
+    pub fn name(self) -> &'static str {
+        match self {
+            PointerPlayers::Neither => "Off",
+            PointerPlayers::PlayerOne => "Player 1",
+            PointerPlayers::PlayerTwo => "Player 2",
+            PointerPlayers::Both => "Both",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            PointerPlayers::Neither => PointerPlayers::PlayerOne,
+            PointerPlayers::PlayerOne => PointerPlayers::PlayerTwo,
+            PointerPlayers::PlayerTwo => PointerPlayers::Both,
+            PointerPlayers::Both => PointerPlayers::Neither,
+        }
+    }
+
+    pub fn controls(self, player: u8) -> bool {
+        match self {
+            PointerPlayers::Neither => false,
+            PointerPlayers::PlayerOne => player == 1,
+            PointerPlayers::PlayerTwo => player == 2,
+            PointerPlayers::Both => player <= 2,
+        }
+    }
+}
+
+/// A paddle following the mouse or a touch on its player's half.
+#[derive(Component)]
+pub struct PointerPaddle;
+
+/// Mouse and touch control for the paddles picked on the menu.
+pub struct PointerPlugin;
+
+impl Plugin for PointerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, pointer_paddle_system.in_set(PaddleSet::Control));
+    }
+}
+
+/// Moves each [`PointerPaddle`] towards its pointer at keyboard speed.
+fn pointer_paddle_system(
+    time: Res<Time>,
+    rules: Res<Rules>,
+    touches: Res<Touches>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut paddle_query: Query<(&Transform, &mut Velocity, &Paddle), With<PointerPaddle>>,
+) {
+    let (Ok(window), Ok((camera, camera_transform))) = (window_query.get_single(), camera_query.get_single()) else {
+        return;
+    };
+    let dt = time.delta_secs();
+
+    for (transform, mut velocity, paddle) in paddle_query.iter_mut() {
+        let edge = paddle.edge;
+        // Where on the court a touch or the cursor is, along and out towards the paddle's edge.
+        let on_court = |position: Vec2| {
+            camera.viewport_to_world_2d(camera_transform, position).ok().map(|world| edge.to_local(world))
+        };
+        let on_own_half = |position: &Vec2| position.y > 0.;
+        let pointer = touches
+            .iter()
+            .filter_map(|touch| on_court(touch.position()))
+            .filter(on_own_half)
+            .last()
+            .or_else(|| window.cursor_position().and_then(on_court));
+
+        let x = edge.to_local(transform.translation.truncate()).x;
+        let target_x = pointer.map_or(x, |position| position.x);
+
+        let max_speed = rules.paddle_speed;
+        let speed = ((target_x - x) / dt.max(f32::EPSILON)).clamp(-max_speed, max_speed);
+        velocity.0 = (edge.along() * speed).extend(0.);
+    }
+}
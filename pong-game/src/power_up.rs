@@ -0,0 +1,328 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::ball::{ball_bundle, Ball, LastHitBy, ServeRng, BALL_SIZE};
+use crate::court::{Edge, Orientation};
+use crate::menu::GameMode;
+use crate::paddle::{player_edge, Eliminated, Paddle};
+use crate::rules::Rules;
+use crate::ui::SCORE_FONT_SIZE;
+use crate::{MatchState, PhysicsSet, Velocity};
+
+const PICKUP_INTERVAL: f32 = 8.;
+const MAX_PICKUPS: usize = 2;
+const PICKUP_SIZE: Vec2 = Vec2::new(20., 20.);
+/// How close to the sides of the court pickups can turn up.
+const PICKUP_SIDE_MARGIN: f32 = 60.;
+const PICKUP_TEXT_COLOR: Color = Color::srgb(0.95, 0.95, 0.95);
+
+const SIZE_EFFECT_DURATION: f32 = 10.;
+const GROW_SCALE: f32 = 1.5;
+const SHRINK_SCALE: f32 = 0.6;
+/// How much faster the ball goes after passing through [`PowerUp::Fast`].
+const FAST_SPEED_UP: f32 = 1.3;
+
+const ICON_SIZE: f32 = 32.;
+/// How far each icon sits in from its corner of the window.
+const ICON_INSET: f32 = 12.;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PowerUp {
+    /// Another ball, heading off the other way.
+    MultiBall,
+    /// A wider paddle for the player who got it.
+    Grow,
+    /// A narrower paddle for their opponents.
+    Shrink,
+    /// The ball speeds on towards the opponent.
+    Fast,
+}
+
+impl PowerUp {
+    const ALL: [PowerUp; 4] = [PowerUp::MultiBall, PowerUp::Grow, PowerUp::Shrink, PowerUp::Fast];
+
+    fn letter(self) -> &'static str {
+        match self {
+            PowerUp::MultiBall => "M",
+            PowerUp::Grow => "+",
+            PowerUp::Shrink => "-",
+            PowerUp::Fast => "F",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            PowerUp::MultiBall => Color::srgb(0.8, 0.5, 0.2),
+            PowerUp::Grow => Color::srgb(0.2, 0.6, 0.3),
+            PowerUp::Shrink => Color::srgb(0.6, 0.2, 0.5),
+            PowerUp::Fast => Color::srgb(0.8, 0.2, 0.2),
+        }
+    }
+}
+
+/// A power-up waiting in midfield for a ball to pass through it.
+#[derive(Component)]
+struct Pickup(PowerUp);
+
+/// A ball beyond the one served, gone again at the next serve.
+#[derive(Component)]
+pub struct ExtraBall;
+
+/// A paddle grown or shrunk by `power_up` until its timer runs out.
+#[derive(Component)]
+struct SizeEffect {
+    power_up: PowerUp,
+    timer: Timer,
+}
+
+/// Shows the [`SizeEffect`] on `player`'s paddle, if any, and how long it has left.
+#[derive(Component)]
+struct EffectIcon {
+    player: u8,
+}
+
+#[derive(Resource)]
+struct PickupTimer(Timer);
+
+impl Default for PickupTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(PICKUP_INTERVAL, TimerMode::Repeating))
+    }
+}
+
+/// Pickups that give an effect to whoever last hit the ball through them.
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickupTimer>()
+            .add_systems(Startup, spawn_effect_icons)
+            .add_systems(
+                FixedUpdate,
+                (
+                    spawn_pickup_system.run_if(not(resource_equals(GameMode::Lan))),
+                    pickup_system.in_set(PhysicsSet::Respond),
+                    size_effect_system,
+                )
+                    .run_if(in_state(MatchState::Playing)),
+            )
+            .add_systems(Update, effect_icon_system)
+            .add_systems(
+                Update,
+                place_effect_icons.run_if(resource_changed::<Orientation>.or(resource_changed::<GameMode>)),
+            )
+            .add_systems(OnEnter(MatchState::Serving), clear_court)
+            .add_systems(OnEnter(MatchState::Menu), (clear_court, clear_effects));
+
+        // Every new game, whether a rematch or the next of the match.
+        for exited in [MatchState::GameOver, MatchState::BetweenGames] {
+            app.add_systems(OnTransition { exited, entered: MatchState::Serving }, clear_effects);
+        }
+    }
+}
+
+fn spawn_effect_icons(mut commands: Commands) {
+    for player in 1..=4 {
+        commands
+            .spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(ICON_SIZE * 1.5),
+                    height: Val::Px(ICON_SIZE),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(Color::NONE),
+                Visibility::Hidden,
+                EffectIcon { player },
+            ))
+            .with_child((
+                Text::default(),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 0.75,
+                    ..default()
+                },
+                TextColor(PICKUP_TEXT_COLOR),
+            ));
+    }
+}
+
+/// Puts each player's icon at the corner where their edge starts, going clockwise.
+fn place_effect_icons(
+    game_mode: Res<GameMode>,
+    orientation: Res<Orientation>,
+    mut icon_query: Query<(&EffectIcon, &mut Node)>,
+) {
+    let inset = Val::Px(ICON_INSET);
+    for (icon, mut node) in icon_query.iter_mut() {
+        let (top, bottom, left, right) = match player_edge(*game_mode, *orientation, icon.player) {
+            Edge::Top => (inset, Val::Auto, inset, Val::Auto),
+            Edge::Right => (inset, Val::Auto, Val::Auto, inset),
+            Edge::Bottom => (Val::Auto, inset, Val::Auto, inset),
+            Edge::Left => (Val::Auto, inset, inset, Val::Auto),
+        };
+        node.top = top;
+        node.bottom = bottom;
+        node.left = left;
+        node.right = right;
+    }
+}
+
+fn spawn_pickup_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<PickupTimer>,
+    mut rng: ResMut<ServeRng>,
+    orientation: Res<Orientation>,
+    pickup_query: Query<(), With<Pickup>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() || pickup_query.iter().count() >= MAX_PICKUPS {
+        return;
+    }
+
+    let power_up = PowerUp::ALL[rng.rng.random_range(0..PowerUp::ALL.len())];
+    // Anywhere across the court, but only the middle third of the way from goal to goal.
+    let edge = orientation.edge(1);
+    let area = Vec2::new(edge.length() / 2. - PICKUP_SIDE_MARGIN, edge.distance() / 3.);
+    let position = edge.to_world(Vec2::new(
+        rng.rng.random_range(-area.x..=area.x),
+        rng.rng.random_range(-area.y..=area.y),
+    ));
+
+    commands
+        .spawn((
+            Sprite {
+                color: power_up.color(),
+                custom_size: Some(PICKUP_SIZE),
+                ..default()
+            },
+            Transform::from_translation(position.extend(-0.5)),
+            Pickup(power_up),
+        ))
+        .with_child((
+            Text2d::new(power_up.letter()),
+            TextFont {
+                font_size: PICKUP_SIZE.y * 0.8,
+                ..default()
+            },
+            TextColor(PICKUP_TEXT_COLOR),
+            Transform::from_xyz(0., 0., 0.1),
+        ));
+}
+
+/// Gives a pickup's effect to the last hitter of a ball passing through it.
+#[allow(clippy::type_complexity)]
+fn pickup_system(
+    mut commands: Commands,
+    rules: Res<Rules>,
+    pickup_query: Query<(Entity, &Transform, &Pickup), Without<Paddle>>,
+    mut ball_query: Query<(&Transform, &mut Velocity, &LastHitBy), With<Ball>>,
+    mut paddle_query: Query<(Entity, &Paddle, &mut Transform), (Without<Ball>, Without<Eliminated>)>,
+) {
+    for (pickup, pickup_transform, &Pickup(power_up)) in pickup_query.iter() {
+        let caught = ball_query.iter_mut().find(|(ball_transform, _, last_hit)| {
+            let distance = (ball_transform.translation - pickup_transform.translation).truncate().abs();
+            last_hit.0.is_some() && distance.cmplt((PICKUP_SIZE + BALL_SIZE) / 2.).all()
+        });
+        let Some((ball_transform, mut velocity, &LastHitBy(Some(player)))) = caught else {
+            continue;
+        };
+
+        commands.entity(pickup).despawn_recursive();
+        match power_up {
+            PowerUp::MultiBall => {
+                // Mirrored across the court, heading the same way from the catcher's paddle.
+                let Some((_, paddle, _)) = paddle_query.iter().find(|(_, paddle, _)| paddle.player == player) else {
+                    continue;
+                };
+                let along = paddle.edge.along().extend(0.);
+                let mirrored = velocity.0 - along * 2. * velocity.0.dot(along);
+                commands.spawn((
+                    ball_bundle(ball_transform.translation, mirrored, &rules),
+                    LastHitBy(Some(player)),
+                    ExtraBall,
+                ));
+            }
+            PowerUp::Grow | PowerUp::Shrink => {
+                // Grow is for the catcher, Shrink for everyone else.
+                let (grow, scale) = match power_up {
+                    PowerUp::Grow => (true, GROW_SCALE),
+                    _ => (false, SHRINK_SCALE),
+                };
+                for (entity, paddle, mut transform) in paddle_query.iter_mut() {
+                    if (paddle.player == player) == grow {
+                        transform.scale.x = scale;
+                        commands.entity(entity).insert(SizeEffect {
+                            power_up,
+                            timer: Timer::from_seconds(SIZE_EFFECT_DURATION, TimerMode::Once),
+                        });
+                    }
+                }
+            }
+            PowerUp::Fast => velocity.0 *= FAST_SPEED_UP,
+        }
+    }
+}
+
+fn size_effect_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut paddle_query: Query<(Entity, &mut Transform, &mut SizeEffect)>,
+) {
+    for (entity, mut transform, mut effect) in paddle_query.iter_mut() {
+        if effect.timer.tick(time.delta()).finished() {
+            transform.scale.x = 1.;
+            commands.entity(entity).remove::<SizeEffect>();
+        }
+    }
+}
+
+fn effect_icon_system(
+    paddle_query: Query<(&Paddle, Option<&SizeEffect>)>,
+    mut icon_query: Query<(&EffectIcon, &mut Visibility, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (icon, mut visibility, mut background, children) in icon_query.iter_mut() {
+        let effect = paddle_query
+            .iter()
+            .find(|(paddle, _)| paddle.player == icon.player)
+            .and_then(|(_, effect)| effect);
+
+        let Some(effect) = effect else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        background.0 = effect.power_up.color();
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0 = format!("{} {:.0}", effect.power_up.letter(), effect.timer.remaining_secs().ceil());
+        }
+    }
+}
+
+/// Takes the pickups and extra balls off the court, ready for a serve.
+fn clear_court(
+    mut commands: Commands,
+    pickup_query: Query<Entity, With<Pickup>>,
+    extra_ball_query: Query<Entity, With<ExtraBall>>,
+) {
+    for entity in pickup_query.iter().chain(extra_ball_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Puts every paddle back to its own size for a new match.
+fn clear_effects(
+    mut commands: Commands,
+    mut timer: ResMut<PickupTimer>,
+    mut paddle_query: Query<(Entity, &mut Transform), With<Paddle>>,
+) {
+    timer.0.reset();
+    for (entity, mut transform) in paddle_query.iter_mut() {
+        transform.scale.x = 1.;
+        commands.entity(entity).remove::<SizeEffect>();
+    }
+}
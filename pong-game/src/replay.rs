@@ -0,0 +1,568 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::arena::{obstacle_sprite, Obstacle, OBSTACLE_DEPTH};
+use crate::ball::{Ball, BALL_COLOR, BALL_SIZE};
+use crate::court::{Edge, Orientation};
+use crate::menu::GameMode;
+use crate::paddle::{paddle_color, paddle_size, Eliminated, Paddle};
+use crate::rules::Rules;
+use crate::score::{BestOf, Games, Lives, PlayerNames, Score};
+use crate::ui::{SCORE_COLOR, SCORE_FONT_SIZE};
+use crate::{MatchState, PhysicsSet};
+
+const REPLAY_FOLDER: &str = "replays";
+const REPLAY_EXTENSION: &str = "replay";
+
+/// Seconds an instant replay lasts.
+const INSTANT_REPLAY_LENGTH: f32 = 3.;
+/// Playback speed of the instant replay.
+const SLOW_MOTION: f32 = 0.5;
+
+const SELECTED_REPLAY_COLOR: Color = Color::srgb(0.2, 0.2, 0.6);
+
+#[derive(Serialize, Deserialize, Clone)]
+struct PaddleFrame {
+    player: u8,
+    position: Vec2,
+    edge: Edge,
+    /// Grown or shrunk by any power-up.
+    size: Vec2,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ObstacleFrame {
+    position: Vec2,
+    size: Vec2,
+    bounciness: f32,
+}
+
+/// Everything on the court and the scoreboard for one fixed step.
+#[derive(Serialize, Deserialize, Clone)]
+struct Frame {
+    balls: Vec<Vec2>,
+    /// The paddles still in the match.
+    paddles: Vec<PaddleFrame>,
+    obstacles: Vec<ObstacleFrame>,
+    score: [u32; 2],
+    games: [u32; 2],
+    lives: [u32; 4],
+}
+
+/// A recorded match: one [`Frame`] per fixed step of serving or play.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Replay {
+    game_mode: GameMode,
+    orientation: Orientation,
+    best_of: u32,
+    names: [String; 4],
+    frames: Vec<Frame>,
+}
+
+impl Replay {
+    fn path(name: &str) -> String {
+        format!("{REPLAY_FOLDER}/{name}.{REPLAY_EXTENSION}")
+    }
+
+    fn load(name: &str) -> Option<Self> {
+        let path = Self::path(name);
+        let contents = std::fs::read_to_string(&path)
+            .inspect_err(|error| error!("could not read replay {path}: {error}"))
+            .ok()?;
+        ron::from_str(&contents)
+            .inspect_err(|error| error!("could not parse replay {path}: {error}"))
+            .ok()
+    }
+
+    /// Saves under a name from the time, giving the name, or `None` if it couldn't be saved.
+    fn save(&self) -> Option<String> {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+        let name = format!("match-{seconds}");
+        let path = Self::path(&name);
+
+        let contents = ron::to_string(self)
+            .inspect_err(|error| warn!("could not serialize replay: {error}"))
+            .ok()?;
+        std::fs::create_dir_all(REPLAY_FOLDER)
+            .and_then(|()| std::fs::write(&path, contents))
+            .inspect_err(|error| warn!("could not save replay {path}: {error}"))
+            .ok()?;
+        Some(name)
+    }
+
+    /// The names of the saved replays, newest first.
+    fn saved() -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(REPLAY_FOLDER) else {
+            return Vec::new();
+        };
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == REPLAY_EXTENSION))
+            .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string))
+            .collect();
+        names.sort_by(|a, b| b.cmp(a));
+        names
+    }
+}
+
+/// The match being played, and where in it the point being played started.
+#[derive(Resource, Default)]
+struct Recording {
+    replay: Replay,
+    point_start: usize,
+}
+
+/// The last match played to the end, until it is saved or another is.
+#[derive(Resource, Default)]
+struct LastMatch(Option<Replay>);
+
+/// Frames being shown in place of the match, `speed` frames a fixed step.
+#[derive(Resource)]
+struct Playback {
+    frames: Vec<Frame>,
+    /// The frame showing, with a fraction when slowed down.
+    cursor: f32,
+    speed: f32,
+}
+
+/// The settings a saved match was played by put back as they were after watching it.
+#[derive(Resource)]
+struct SettingsBeforeReplay {
+    orientation: Orientation,
+    best_of: u32,
+}
+
+/// A ball, paddle or obstacle shown by a [`Playback`], standing in for the match's own.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash)]
+enum Ghost {
+    Ball(usize),
+    Paddle(u8),
+    Obstacle(usize),
+}
+
+/// A ball, paddle or obstacle of the match, hidden while a [`Playback`] shows its own.
+#[derive(Component)]
+struct HiddenForReplay;
+
+/// The saved replays on the replays screen, and how saving the last match went.
+#[derive(Resource, Default)]
+struct ReplayList {
+    names: Vec<String>,
+    selected: usize,
+    status: String,
+}
+
+#[derive(Component)]
+struct ReplayListText;
+
+/// Records matches, replays each point in slow motion and lists saved replays.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recording>()
+            .init_resource::<LastMatch>()
+            .add_systems(
+                FixedUpdate,
+                record_system
+                    .after(PhysicsSet::Detect)
+                    .before(PhysicsSet::Respond)
+                    .run_if(in_state(MatchState::Playing).or(in_state(MatchState::Serving))),
+            )
+            .add_systems(OnEnter(MatchState::Serving), start_point)
+            .add_systems(OnEnter(MatchState::GameOver), keep_last_match)
+            .add_systems(OnEnter(MatchState::Menu), clear_recording)
+            .add_systems(OnEnter(MatchState::InstantReplay), start_instant_replay)
+            .add_systems(OnEnter(MatchState::WatchingReplay), start_watching)
+            .add_systems(OnExit(MatchState::InstantReplay), end_playback)
+            .add_systems(OnExit(MatchState::WatchingReplay), (end_playback, restore_settings))
+            .add_systems(
+                FixedUpdate,
+                playback_system
+                    .run_if(in_state(MatchState::InstantReplay).or(in_state(MatchState::WatchingReplay)))
+                    .run_if(resource_exists::<Playback>),
+            )
+            .add_systems(
+                Update,
+                (hide_match_system, skip_replay_system)
+                    .run_if(in_state(MatchState::InstantReplay).or(in_state(MatchState::WatchingReplay))),
+            )
+            .add_systems(OnEnter(MatchState::Replays), spawn_replays_screen)
+            .add_systems(
+                Update,
+                (replays_input_system, replays_text_system).chain().run_if(in_state(MatchState::Replays)),
+            );
+    }
+}
+
+/// Records a frame after the balls move and before a goal resets them.
+#[allow(clippy::too_many_arguments)]
+fn record_system(
+    mut recording: ResMut<Recording>,
+    rules: Res<Rules>,
+    game_mode: Res<GameMode>,
+    orientation: Res<Orientation>,
+    best_of: Res<BestOf>,
+    names: Res<PlayerNames>,
+    scoreboard: (Res<Score>, Res<Games>, Res<Lives>),
+    ball_query: Query<&Transform, With<Ball>>,
+    paddle_query: Query<(&Transform, &Paddle), Without<Eliminated>>,
+    obstacle_query: Query<(&Transform, &Obstacle)>,
+) {
+    let replay = &mut recording.replay;
+    if replay.frames.is_empty() {
+        replay.game_mode = *game_mode;
+        replay.orientation = *orientation;
+        replay.best_of = best_of.0;
+        replay.names = names.0.clone();
+    }
+
+    let (score, games, lives) = scoreboard;
+    replay.frames.push(Frame {
+        balls: ball_query.iter().map(|transform| transform.translation.truncate()).collect(),
+        paddles: paddle_query
+            .iter()
+            .map(|(transform, paddle)| PaddleFrame {
+                player: paddle.player,
+                position: transform.translation.truncate(),
+                edge: paddle.edge,
+                size: paddle_size(&rules, transform),
+            })
+            .collect(),
+        obstacles: obstacle_query
+            .iter()
+            .map(|(transform, obstacle)| ObstacleFrame {
+                position: transform.translation.truncate(),
+                size: obstacle.size,
+                bounciness: obstacle.bounciness,
+            })
+            .collect(),
+        score: [score.player_1, score.player_2],
+        games: [games.player_1, games.player_2],
+        lives: lives.0,
+    });
+}
+
+fn start_point(mut recording: ResMut<Recording>) {
+    recording.point_start = recording.replay.frames.len();
+}
+
+fn keep_last_match(mut recording: ResMut<Recording>, mut last_match: ResMut<LastMatch>) {
+    last_match.0 = Some(std::mem::take(&mut recording.replay));
+    recording.point_start = 0;
+}
+
+/// Forgets a match left unfinished.
+fn clear_recording(mut recording: ResMut<Recording>) {
+    *recording = Recording::default();
+}
+
+/// Plays back the end of the point just scored, slowed down to fill the [`INSTANT_REPLAY_LENGTH`].
+fn start_instant_replay(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    recording: Res<Recording>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    let shown = (INSTANT_REPLAY_LENGTH * SLOW_MOTION / time.timestep().as_secs_f32()) as usize;
+    let frames = &recording.replay.frames;
+    let start = recording.point_start.max(frames.len().saturating_sub(shown)).min(frames.len());
+    if start == frames.len() {
+        next_state.set(MatchState::Serving);
+        return;
+    }
+
+    commands.insert_resource(Playback { frames: frames[start..].to_vec(), cursor: 0., speed: SLOW_MOTION });
+    spawn_replay_banner(&mut commands, "Instant Replay\nPress Enter to skip", MatchState::InstantReplay);
+}
+
+fn start_watching(mut commands: Commands) {
+    spawn_replay_banner(&mut commands, "Replay\nPress Escape to stop", MatchState::WatchingReplay);
+}
+
+fn spawn_replay_banner(commands: &mut Commands, text: &str, state: MatchState) {
+    commands.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: SCORE_FONT_SIZE,
+            ..default()
+        },
+        TextColor(SCORE_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            top: Val::Percent(20.),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        StateScoped(state),
+    ));
+}
+
+/// Shows the current playback frame and advances it.
+#[allow(clippy::too_many_arguments)]
+fn playback_system(
+    mut commands: Commands,
+    mut playback: ResMut<Playback>,
+    state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut score: ResMut<Score>,
+    mut games: ResMut<Games>,
+    mut lives: ResMut<Lives>,
+    mut ghost_query: Query<(Entity, &Ghost, &mut Transform, &mut Sprite)>,
+) {
+    let Some(frame) = playback.frames.get(playback.cursor as usize) else {
+        next_state.set(if *state.get() == MatchState::InstantReplay {
+            MatchState::Serving
+        } else {
+            MatchState::Replays
+        });
+        return;
+    };
+
+    // An instant replay shows the point on the score it was played at, but not a saved match.
+    if *state.get() == MatchState::WatchingReplay {
+        score.set_if_neq(Score { player_1: frame.score[0], player_2: frame.score[1] });
+        games.set_if_neq(Games { player_1: frame.games[0], player_2: frame.games[1] });
+        if lives.0 != frame.lives {
+            lives.0 = frame.lives;
+        }
+    }
+
+    let mut shown: HashMap<Ghost, (Vec3, Quat, Sprite)> = HashMap::new();
+    for (index, &position) in frame.balls.iter().enumerate() {
+        let sprite = Sprite { color: BALL_COLOR, custom_size: Some(BALL_SIZE), ..default() };
+        shown.insert(Ghost::Ball(index), (position.extend(0.), Quat::IDENTITY, sprite));
+    }
+    for paddle in &frame.paddles {
+        let sprite = Sprite { color: paddle_color(paddle.player), custom_size: Some(paddle.size), ..default() };
+        shown.insert(Ghost::Paddle(paddle.player), (paddle.position.extend(0.), paddle.edge.rotation(), sprite));
+    }
+    for (index, obstacle) in frame.obstacles.iter().enumerate() {
+        let sprite = obstacle_sprite(obstacle.size, obstacle.bounciness);
+        shown.insert(Ghost::Obstacle(index), (obstacle.position.extend(OBSTACLE_DEPTH), Quat::IDENTITY, sprite));
+    }
+
+    for (entity, ghost, mut transform, mut sprite) in ghost_query.iter_mut() {
+        match shown.remove(ghost) {
+            Some((translation, rotation, shown_sprite)) => {
+                transform.translation = translation;
+                transform.rotation = rotation;
+                *sprite = shown_sprite;
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+    for (ghost, (translation, rotation, sprite)) in shown {
+        commands.spawn((sprite, Transform::from_translation(translation).with_rotation(rotation), ghost));
+    }
+
+    playback.cursor += playback.speed;
+}
+
+/// Hides the live entities while a [`Playback`] is showing.
+#[allow(clippy::type_complexity)]
+fn hide_match_system(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &Visibility),
+        (Or<(With<Ball>, With<Paddle>, With<Obstacle>)>, Without<HiddenForReplay>),
+    >,
+) {
+    for (entity, visibility) in query.iter() {
+        if *visibility != Visibility::Hidden {
+            commands.entity(entity).insert((Visibility::Hidden, HiddenForReplay));
+        }
+    }
+}
+
+/// Skips an instant replay on Enter, or stops watching a saved match on Escape.
+fn skip_replay_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    match state.get() {
+        MatchState::InstantReplay if keys.just_pressed(KeyCode::Enter) => next_state.set(MatchState::Serving),
+        MatchState::WatchingReplay if keys.just_pressed(KeyCode::Escape) => next_state.set(MatchState::Replays),
+        _ => {}
+    }
+}
+
+/// Takes away the [`Playback`]'s balls, paddles and obstacles and shows the match's own again.
+fn end_playback(
+    mut commands: Commands,
+    ghost_query: Query<Entity, With<Ghost>>,
+    hidden_query: Query<Entity, With<HiddenForReplay>>,
+) {
+    commands.remove_resource::<Playback>();
+    for entity in ghost_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for entity in hidden_query.iter() {
+        commands.entity(entity).remove::<HiddenForReplay>().insert(Visibility::Inherited);
+    }
+}
+
+fn restore_settings(
+    mut commands: Commands,
+    settings: Option<Res<SettingsBeforeReplay>>,
+    mut orientation: ResMut<Orientation>,
+    mut best_of: ResMut<BestOf>,
+) {
+    let Some(settings) = settings else {
+        return;
+    };
+    orientation.set_if_neq(settings.orientation);
+    best_of.0 = settings.best_of;
+    commands.remove_resource::<SettingsBeforeReplay>();
+}
+
+fn spawn_replays_screen(mut commands: Commands) {
+    commands.insert_resource(ReplayList { names: Replay::saved(), ..default() });
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.),
+                ..default()
+            },
+            StateScoped(MatchState::Replays),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Replays"),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 2.,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+            ));
+
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 0.75,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+                ReplayListText,
+            ));
+
+            parent.spawn((
+                Text::new(format!(
+                    "S to save the last match to {REPLAY_FOLDER}/\n\
+                     Up/Down to pick, Enter to watch, Escape for the menu"
+                )),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 0.75,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+}
+
+/// The replays screen: S saves, Up/Down pick, Enter watches, Escape leaves.
+#[allow(clippy::too_many_arguments)]
+fn replays_input_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut list: ResMut<ReplayList>,
+    mut last_match: ResMut<LastMatch>,
+    mut game_mode: ResMut<GameMode>,
+    mut orientation: ResMut<Orientation>,
+    mut best_of: ResMut<BestOf>,
+    mut names: ResMut<PlayerNames>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(MatchState::Menu);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyS) {
+        list.status = match last_match.0.as_ref().map(Replay::save) {
+            None => "No match played to the end yet to save".to_string(),
+            Some(None) => "Couldn't save the last match".to_string(),
+            Some(Some(name)) => {
+                last_match.0 = None;
+                list.names = Replay::saved();
+                list.selected = list.names.iter().position(|saved| *saved == name).unwrap_or(0);
+                format!("Saved the last match as {name}")
+            }
+        };
+    }
+
+    let count = list.names.len();
+    if count == 0 {
+        return;
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        list.selected = (list.selected + 1) % count;
+    } else if keys.just_pressed(KeyCode::ArrowUp) {
+        list.selected = (list.selected + count - 1) % count;
+    }
+
+    if keys.just_pressed(KeyCode::Enter) {
+        let name = &list.names[list.selected];
+        let Some(replay) = Replay::load(name) else {
+            list.status = format!("Couldn't load {name}");
+            return;
+        };
+
+        commands.insert_resource(SettingsBeforeReplay { orientation: *orientation, best_of: best_of.0 });
+        *game_mode = replay.game_mode;
+        *orientation = replay.orientation;
+        best_of.0 = replay.best_of;
+        names.0 = replay.names;
+        commands.insert_resource(Playback { frames: replay.frames, cursor: 0., speed: 1. });
+        next_state.set(MatchState::WatchingReplay);
+    }
+}
+
+/// Lists the saved replays, the picked one marked and in colour, under how saving went.
+fn replays_text_system(
+    list: Res<ReplayList>,
+    mut commands: Commands,
+    text_query: Query<(Entity, Ref<ReplayListText>)>,
+) {
+    let Ok((entity, text)) = text_query.get_single() else {
+        return;
+    };
+    if !text.is_added() && !list.is_changed() {
+        return;
+    }
+
+    commands.entity(entity).despawn_descendants();
+    let mut header = if list.status.is_empty() { String::new() } else { format!("{}\n\n", list.status) };
+    if list.names.is_empty() {
+        header += "No saved replays yet";
+    }
+    commands.entity(entity).insert(Text::new(header)).with_children(|parent| {
+        for (index, name) in list.names.iter().enumerate() {
+            let selected = index == list.selected;
+            parent.spawn((
+                TextSpan::new(format!("{} {name}\n", if selected { ">" } else { " " })),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 0.75,
+                    ..default()
+                },
+                TextColor(if selected { SELECTED_REPLAY_COLOR } else { SCORE_COLOR }),
+            ));
+        }
+    });
+}
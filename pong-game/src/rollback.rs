@@ -0,0 +1,589 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::ball::{
+    curve, edge_hit, paddle_rebound, sped_up, swept_hit, Ball, Collider, CollisionEvent, GoalEvent, Rally, Serve,
+    ServeRng, BALL_SIZE, MAX_SERVE_ANGLE, SERVE_COUNTDOWN,
+};
+use crate::court::{Edge, Orientation};
+use crate::network::{is_connected, Connection, Message};
+use crate::paddle::{Paddle, PaddleSet, PADDLE_OFFSET};
+use crate::rules::Rules;
+use crate::score::{BestOf, Games, Score};
+use crate::{MatchState, Velocity};
+
+/// Frames a local input is delayed, to hide most of the latency.
+const INPUT_DELAY: u32 = 2;
+/// Frames the match may run ahead of the remote input.
+const MAX_PREDICTION: u32 = 8;
+/// Frames between desync checks.
+const CHECKSUM_INTERVAL: u32 = 60;
+const MAX_INPUTS_PER_MESSAGE: usize = 32;
+/// Seconds between games, since neither player presses Enter to continue.
+const BETWEEN_GAMES_DELAY: f32 = 3.;
+const FULL_INPUT: f32 = 127.;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Match settings, identical on both machines.
+struct Setup {
+    rules: Rules,
+    best_of: u32,
+    edges: [Edge; 2],
+    dt: f32,
+}
+
+impl Setup {
+    fn frames(&self, seconds: f32) -> u32 {
+        (seconds / self.dt).round() as u32
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Phase {
+    Serving(u32),
+    Playing,
+    BetweenGames(u32),
+    Over,
+}
+
+/// An event the simulation reports for sounds and effects.
+#[derive(Clone, Copy)]
+enum Happening {
+    Paddle { player: usize, position: Vec2, speed: f32 },
+    Wall { edge: Edge, position: Vec2, speed: f32 },
+    Goal { against: usize },
+}
+
+/// A deterministic two-player match; players are 0 and 1 here, for paddles 1 and 2.
+#[derive(Clone)]
+struct Simulation {
+    phase: Phase,
+    ball: Vec2,
+    velocity: Vec2,
+    spin: f32,
+    paddles: [f32; 2],
+    paddle_speeds: [f32; 2],
+    score: [u32; 2],
+    games: [u32; 2],
+    rally_hits: u32,
+    rally_speed: f32,
+    serve_towards: usize,
+    /// SplitMix64 state, kept in-house so both machines draw the same numbers.
+    rng: u64,
+}
+
+impl Simulation {
+    fn new(setup: &Setup, seed: u64) -> Self {
+        Self {
+            phase: Phase::Serving(setup.frames(SERVE_COUNTDOWN)),
+            ball: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+            spin: 0.,
+            paddles: [0.; 2],
+            paddle_speeds: [0.; 2],
+            score: [0; 2],
+            games: [0; 2],
+            rally_hits: 0,
+            rally_speed: setup.rules.ball_speed,
+            serve_towards: 0,
+            rng: seed,
+        }
+    }
+
+    fn random(&mut self) -> f32 {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn paddle_center(&self, setup: &Setup, player: usize) -> Vec2 {
+        let edge = setup.edges[player];
+        edge.to_world(Vec2::new(self.paddles[player], edge.distance() - PADDLE_OFFSET))
+    }
+
+    fn step(&mut self, inputs: [i8; 2], setup: &Setup) -> Option<Happening> {
+        if matches!(self.phase, Phase::Serving(_) | Phase::Playing) {
+            self.move_paddles(inputs, setup);
+        }
+
+        match self.phase {
+            Phase::Serving(0) => self.serve(setup),
+            Phase::Serving(frames) => self.phase = Phase::Serving(frames - 1),
+            Phase::Playing => return self.move_ball(setup),
+            Phase::BetweenGames(0) => {
+                self.score = [0; 2];
+                self.paddles = [0.; 2];
+                self.phase = Phase::Serving(setup.frames(SERVE_COUNTDOWN));
+            }
+            Phase::BetweenGames(frames) => self.phase = Phase::BetweenGames(frames - 1),
+            Phase::Over => {}
+        }
+        None
+    }
+
+    fn move_paddles(&mut self, inputs: [i8; 2], setup: &Setup) {
+        for (player, input) in inputs.into_iter().enumerate() {
+            let limit = setup.edges[player].length() / 2. - setup.rules.paddle_width / 2.;
+            let speed = f32::from(input) / FULL_INPUT * setup.rules.paddle_speed;
+            let along = self.paddles[player] + speed * setup.dt;
+            self.paddles[player] = along.clamp(-limit, limit);
+            // A paddle stopped against the side gives the ball no momentum.
+            self.paddle_speeds[player] = if along == self.paddles[player] { speed } else { 0. };
+        }
+    }
+
+    fn serve(&mut self, setup: &Setup) {
+        let angle = (self.random() * 2. - 1.) * MAX_SERVE_ANGLE;
+        let edge = setup.edges[self.serve_towards];
+        self.velocity = edge.to_world(Vec2::new(angle.sin(), angle.cos())) * self.rally_speed;
+        self.spin = 0.;
+        self.phase = Phase::Playing;
+    }
+
+    /// Mirrors the ball plugin's sweep and bounce.
+    fn move_ball(&mut self, setup: &Setup) -> Option<Happening> {
+        self.spin = curve(&mut self.velocity, self.spin, setup.dt);
+        let motion = self.velocity * setup.dt;
+        let speed = self.velocity.length();
+
+        let wall_hit = Edge::ALL
+            .into_iter()
+            .filter(|edge| !setup.edges.contains(edge))
+            .filter_map(|edge| edge_hit(edge, self.ball, motion).map(|t| (t, None, Some(edge))));
+        let paddle_hit = (0..2)
+            // Skip paddles the ball is moving away from.
+            .filter(|&player| {
+                let outward = setup.edges[player].outward();
+                (self.paddle_center(setup, player) - self.ball).dot(outward) * motion.dot(outward) > 0.
+            })
+            .filter_map(|player| {
+                let half_size = (setup.edges[player].extent(setup.rules.paddle_size()) + BALL_SIZE) / 2.;
+                swept_hit(self.ball, motion, self.paddle_center(setup, player), half_size)
+                    .map(|t| (t, Some(player), None))
+            });
+        let hit = wall_hit.chain(paddle_hit).min_by(|a, b| a.0.total_cmp(&b.0));
+
+        self.ball += motion * hit.map_or(1., |(t, ..)| t);
+        match hit {
+            Some((_, Some(player), _)) => {
+                let edge = setup.edges[player];
+                let offset = edge.to_local(self.ball - self.paddle_center(setup, player));
+                self.rally_hits += 1;
+                self.rally_speed = sped_up(&setup.rules, self.rally_speed);
+                (self.velocity, self.spin) = paddle_rebound(
+                    edge,
+                    offset,
+                    setup.rules.paddle_width,
+                    self.paddle_speeds[player],
+                    self.rally_speed,
+                );
+                return Some(Happening::Paddle { player, position: self.ball, speed });
+            }
+            Some((_, None, Some(edge))) => {
+                let outward = edge.outward();
+                self.velocity -= outward * 2. * self.velocity.dot(outward);
+                self.spin *= -1.;
+                return Some(Happening::Wall { edge, position: self.ball, speed });
+            }
+            _ => {}
+        }
+
+        let against = (0..2).find(|&player| {
+            let edge = setup.edges[player];
+            self.ball.dot(edge.outward()) > edge.distance() + BALL_SIZE.y / 2.
+        })?;
+        self.score_goal(setup, against);
+        Some(Happening::Goal { against })
+    }
+
+    fn score_goal(&mut self, setup: &Setup, against: usize) {
+        let scorer = 1 - against;
+        self.score[scorer] += 1;
+        self.ball = Vec2::ZERO;
+        self.velocity = Vec2::ZERO;
+        self.spin = 0.;
+        self.rally_hits = 0;
+        self.rally_speed = setup.rules.ball_speed;
+        self.serve_towards = against;
+
+        self.phase = if self.score[scorer] < setup.rules.points_to_win {
+            Phase::Serving(setup.frames(SERVE_COUNTDOWN))
+        } else {
+            self.games[scorer] += 1;
+            if self.games[scorer] > setup.best_of / 2 {
+                Phase::Over
+            } else {
+                Phase::BetweenGames(setup.frames(BETWEEN_GAMES_DELAY))
+            }
+        };
+    }
+
+    /// FNV-1a over the whole match state.
+    fn checksum(&self) -> u64 {
+        let (phase, frames) = match self.phase {
+            Phase::Serving(frames) => (0, frames),
+            Phase::Playing => (1, 0),
+            Phase::BetweenGames(frames) => (2, frames),
+            Phase::Over => (3, 0),
+        };
+        let counts = [phase, frames, self.score[0], self.score[1], self.games[0], self.games[1], self.rally_hits];
+        let floats = [
+            self.ball.x,
+            self.ball.y,
+            self.velocity.x,
+            self.velocity.y,
+            self.spin,
+            self.paddles[0],
+            self.paddles[1],
+            self.paddle_speeds[0],
+            self.paddle_speeds[1],
+            self.rally_speed,
+        ];
+        counts
+            .into_iter()
+            .chain(floats.map(f32::to_bits))
+            .map(u64::from)
+            .chain([self.serve_towards as u64, self.rng])
+            .fold(FNV_OFFSET, |hash, word| (hash ^ word).wrapping_mul(FNV_PRIME))
+    }
+}
+
+#[derive(Event)]
+pub struct PeerMessageEvent(pub Message);
+
+/// A rollback LAN match: plays ahead on guessed remote input and replays frames it guessed wrong.
+#[derive(Resource)]
+struct Session {
+    setup: Setup,
+    local: usize,
+    frame: u32,
+    simulation: Simulation,
+    /// Snapshots of frames that may still be rolled back to.
+    saved: BTreeMap<u32, Simulation>,
+    inputs: [BTreeMap<u32, i8>; 2],
+    /// Remote inputs guessed for frames played before they arrived.
+    guesses: BTreeMap<u32, i8>,
+    mispredicted: Option<u32>,
+    /// First frame without remote input.
+    confirmed: u32,
+    /// First local input the peer hasn't acknowledged.
+    acknowledged: u32,
+    /// Frames already played once, so replays don't repeat their happenings.
+    played: u32,
+    happenings: Vec<Happening>,
+    next_checksum: u32,
+    checksums: BTreeMap<u32, u64>,
+    remote_checksums: BTreeMap<u32, u64>,
+}
+
+impl Session {
+    fn new(setup: Setup, local: usize, seed: u64) -> Self {
+        let delayed: BTreeMap<u32, i8> = (0..INPUT_DELAY).map(|frame| (frame, 0)).collect();
+        Self {
+            simulation: Simulation::new(&setup, seed),
+            setup,
+            local,
+            frame: 0,
+            saved: BTreeMap::new(),
+            inputs: [delayed.clone(), delayed],
+            guesses: BTreeMap::new(),
+            mispredicted: None,
+            confirmed: 0,
+            acknowledged: 0,
+            played: 0,
+            happenings: Vec::new(),
+            next_checksum: 0,
+            checksums: BTreeMap::new(),
+            remote_checksums: BTreeMap::new(),
+        }
+    }
+
+    fn remote(&self) -> usize {
+        1 - self.local
+    }
+
+    /// Stores remote inputs and notes the earliest wrong guess.
+    fn receive_inputs(&mut self, ack: u32, first: u32, inputs: &[i8]) {
+        self.acknowledged = self.acknowledged.max(ack);
+        let remote = self.remote();
+        for (frame, &input) in (first..).zip(inputs) {
+            if frame < self.confirmed || self.inputs[remote].contains_key(&frame) {
+                continue;
+            }
+            self.inputs[remote].insert(frame, input);
+            if self.guesses.remove(&frame).is_some_and(|guess| guess != input) {
+                self.mispredicted = Some(self.mispredicted.map_or(frame, |earliest| earliest.min(frame)));
+            }
+        }
+        while self.inputs[remote].contains_key(&self.confirmed) {
+            self.confirmed += 1;
+        }
+    }
+
+    /// Replays from the earliest wrong guess.
+    fn roll_back(&mut self) {
+        let Some(from) = self.mispredicted.take() else {
+            return;
+        };
+        let Some(saved) = self.saved.get(&from) else {
+            return;
+        };
+
+        let to = self.frame;
+        self.simulation = saved.clone();
+        self.frame = from;
+        while self.frame < to {
+            self.advance();
+        }
+    }
+
+    /// Plays a frame, repeating the last remote input heard if this frame's hasn't arrived.
+    fn advance(&mut self) {
+        let frame = self.frame;
+        let remote = self.remote();
+        let mut inputs = [0; 2];
+        inputs[self.local] = self.inputs[self.local].get(&frame).copied().unwrap_or(0);
+        inputs[remote] = match self.inputs[remote].get(&frame) {
+            Some(&input) => input,
+            None => {
+                let guess = self.inputs[remote].range(..frame).next_back().map_or(0, |(_, &input)| input);
+                self.guesses.insert(frame, guess);
+                guess
+            }
+        };
+
+        self.saved.insert(frame, self.simulation.clone());
+        let happening = self.simulation.step(inputs, &self.setup);
+        if frame >= self.played {
+            self.played = frame + 1;
+            self.happenings.extend(happening);
+        }
+        self.frame += 1;
+    }
+
+    /// Queues the local input and plays a frame unless too far ahead of the remote one.
+    fn play(&mut self, input: i8) {
+        self.inputs[self.local].entry(self.frame + INPUT_DELAY).or_insert(input);
+        if self.frame < self.confirmed + MAX_PREDICTION {
+            self.advance();
+        }
+    }
+
+    /// Checksums of newly confirmed frames, every [`CHECKSUM_INTERVAL`] frames.
+    fn new_checksums(&mut self) -> Vec<(u32, u64)> {
+        let settled = self.confirmed.min(self.frame);
+        let mut checksums = Vec::new();
+        while self.next_checksum <= settled {
+            let frame = self.next_checksum;
+            let simulation = if frame == self.frame { Some(&self.simulation) } else { self.saved.get(&frame) };
+            if let Some(simulation) = simulation {
+                let checksum = simulation.checksum();
+                self.checksums.insert(frame, checksum);
+                checksums.push((frame, checksum));
+            }
+            self.next_checksum += CHECKSUM_INTERVAL;
+        }
+        checksums
+    }
+
+    /// The first frame whose checksum differs from the peer's, if any.
+    fn desynced(&mut self) -> Option<u32> {
+        let newest = self.remote_checksums.keys().next_back().copied()?;
+        for (frame, remote) in std::mem::take(&mut self.remote_checksums) {
+            match self.checksums.remove(&frame) {
+                Some(local) if local != remote => return Some(frame),
+                Some(_) => {}
+                None => {
+                    self.remote_checksums.insert(frame, remote);
+                }
+            }
+        }
+        // Older remote checksums were lost.
+        self.checksums.retain(|&frame, _| frame > newest);
+        None
+    }
+
+    fn inputs_message(&self) -> Message {
+        let inputs = self.inputs[self.local]
+            .range(self.acknowledged..)
+            .take(MAX_INPUTS_PER_MESSAGE)
+            .map(|(_, &input)| input)
+            .collect();
+        Message::Inputs { ack: self.confirmed, frame: self.acknowledged, inputs }
+    }
+
+    fn prune(&mut self) {
+        let settled = self.confirmed.min(self.frame);
+        self.saved = self.saved.split_off(&settled);
+        let local = self.local;
+        self.inputs[local] = self.inputs[local].split_off(&self.acknowledged.min(settled));
+        // Keep the last remote input to guess from.
+        let remote = self.remote();
+        self.inputs[remote] = self.inputs[remote].split_off(&settled.saturating_sub(1));
+    }
+}
+
+/// Rollback netcode for LAN matches.
+pub struct RollbackPlugin;
+
+impl Plugin for RollbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PeerMessageEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    start_session.run_if(not(resource_exists::<Session>)),
+                    (rollback_system, show_match_system).chain().run_if(resource_exists::<Session>),
+                )
+                    .chain()
+                    .after(PaddleSet::Move)
+                    .run_if(is_connected),
+            )
+            .add_systems(OnEnter(MatchState::Menu), end_session);
+    }
+}
+
+fn start_session(
+    mut commands: Commands,
+    connection: Res<Connection>,
+    time: Res<Time<Fixed>>,
+    rules: Res<Rules>,
+    best_of: Res<BestOf>,
+    orientation: Res<Orientation>,
+    serve_rng: Res<ServeRng>,
+) {
+    let setup = Setup {
+        rules: rules.clone(),
+        best_of: best_of.0,
+        edges: [orientation.edge(1), orientation.edge(2)],
+        dt: time.timestep().as_secs_f32(),
+    };
+    let local = usize::from(connection.local_player() - 1);
+    info!("playing by rollback as player {}", local + 1);
+    commands.insert_resource(Session::new(setup, local, serve_rng.seed));
+}
+
+fn rollback_system(
+    mut session: ResMut<Session>,
+    connection: Res<Connection>,
+    mut messages: EventReader<PeerMessageEvent>,
+    paddle_query: Query<(&Velocity, &Paddle)>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    for PeerMessageEvent(message) in messages.read() {
+        match message {
+            Message::Inputs { ack, frame, inputs } => session.receive_inputs(*ack, *frame, inputs),
+            Message::Checksum { frame, checksum } => {
+                session.remote_checksums.insert(*frame, *checksum);
+            }
+            _ => {}
+        }
+    }
+    session.roll_back();
+
+    let local_player = connection.local_player();
+    let paddle_speed = session.setup.rules.paddle_speed;
+    let input = paddle_query.iter().find(|(_, paddle)| paddle.player == local_player).map_or(0, |(velocity, paddle)| {
+        let along = velocity.0.truncate().dot(paddle.edge.along()) / paddle_speed;
+        (along * FULL_INPUT).round().clamp(-FULL_INPUT, FULL_INPUT) as i8
+    });
+    session.play(input);
+
+    for (frame, checksum) in session.new_checksums() {
+        connection.send(&Message::Checksum { frame, checksum });
+    }
+    if let Some(frame) = session.desynced() {
+        error!("the LAN game went out of sync at frame {frame}");
+        next_state.set(MatchState::Menu);
+    }
+    connection.send(&session.inputs_message());
+    session.prune();
+}
+
+/// Copies the simulation onto the entities and resources the rest of the game shows.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn show_match_system(
+    mut session: ResMut<Session>,
+    state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut score: ResMut<Score>,
+    mut games: ResMut<Games>,
+    mut rally: ResMut<Rally>,
+    mut serve: ResMut<Serve>,
+    mut ball_query: Query<(Entity, &mut Transform, &mut Velocity), With<Ball>>,
+    mut paddle_query: Query<(Entity, &mut Transform, &mut Velocity, &Paddle), Without<Ball>>,
+    mut collisions: EventWriter<CollisionEvent>,
+    mut goals: EventWriter<GoalEvent>,
+) {
+    let session = &mut *session;
+    let simulation = &session.simulation;
+    let setup = &session.setup;
+
+    let shown = match simulation.phase {
+        Phase::Serving(_) => MatchState::Serving,
+        Phase::Playing => MatchState::Playing,
+        Phase::BetweenGames(_) => MatchState::BetweenGames,
+        Phase::Over => MatchState::GameOver,
+    };
+    if ![MatchState::Paused, shown].contains(state.get()) {
+        next_state.set(shown);
+    }
+    if let Phase::Serving(frames) = simulation.phase {
+        let left = frames as f32 * setup.dt;
+        serve.countdown.set_elapsed(Duration::from_secs_f32((SERVE_COUNTDOWN - left).max(0.)));
+    }
+
+    score.set_if_neq(Score { player_1: simulation.score[0], player_2: simulation.score[1] });
+    games.set_if_neq(Games { player_1: simulation.games[0], player_2: simulation.games[1] });
+    if rally.hits != simulation.rally_hits {
+        rally.hits = simulation.rally_hits;
+        rally.speed = simulation.rally_speed;
+    }
+
+    let mut paddles = [None; 2];
+    for (entity, mut transform, mut velocity, paddle) in paddle_query.iter_mut() {
+        let player = usize::from(paddle.player - 1);
+        if player >= 2 {
+            continue;
+        }
+        let position = simulation.paddle_center(setup, player);
+        transform.translation = position.extend(transform.translation.z);
+        velocity.0 = (setup.edges[player].along() * simulation.paddle_speeds[player]).extend(0.);
+        paddles[player] = Some(entity);
+    }
+
+    let Some((ball, mut transform, mut velocity)) = ball_query.iter_mut().next() else {
+        return;
+    };
+    transform.translation = simulation.ball.extend(transform.translation.z);
+    velocity.0 = simulation.velocity.extend(0.);
+
+    for happening in session.happenings.drain(..) {
+        match happening {
+            Happening::Paddle { player, position, speed } => {
+                if let Some(paddle) = paddles[player] {
+                    collisions.send(CollisionEvent { ball, collider: Collider::Paddle(paddle), position, speed });
+                }
+            }
+            Happening::Wall { edge, position, speed } => {
+                collisions.send(CollisionEvent { ball, collider: Collider::Wall(edge), position, speed });
+            }
+            Happening::Goal { against } => {
+                let against = against as u8 + 1;
+                goals.send(GoalEvent { against, scorer: Some(3 - against) });
+            }
+        }
+    }
+}
+
+fn end_session(mut commands: Commands) {
+    commands.remove_resource::<Session>();
+}
@@ -0,0 +1,272 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ui::{SCORE_COLOR, SCORE_FONT_SIZE};
+use crate::MatchState;
+
+const RULE_BOOK: &str = "rules.ron";
+
+const SELECTED_RULES_COLOR: Color = Color::srgb(0.2, 0.2, 0.6);
+
+/// How a match plays, picked from the [`RuleBook`].
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Rules {
+    pub name: String,
+    /// How fast a paddle moves on the keyboard or after a pointer, in pixels per second.
+    pub paddle_speed: f32,
+    pub paddle_width: f32,
+    pub paddle_height: f32,
+    /// How fast the ball is served, in pixels per second.
+    pub ball_speed: f32,
+    /// How much faster the ball gets with each paddle hit in a rally.
+    pub speed_up: f32,
+    /// Points a player needs to win a game.
+    pub points_to_win: u32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            name: "Classic".to_string(),
+            paddle_speed: 400.,
+            paddle_width: 100.,
+            paddle_height: 10.,
+            ball_speed: 424.,
+            speed_up: 1.05,
+            points_to_win: 11,
+        }
+    }
+}
+
+impl Rules {
+    /// A paddle's size before any power-up grows or shrinks it.
+    pub fn paddle_size(&self) -> Vec2 {
+        Vec2::new(self.paddle_width, self.paddle_height)
+    }
+
+    /// A line on what sets these rules apart, for the rules screen.
+    fn description(&self) -> String {
+        format!(
+            "{}x{} paddles at {:.0}, ball at {:.0} and {:.2}x a hit, first to {}",
+            self.paddle_width,
+            self.paddle_height,
+            self.paddle_speed,
+            self.ball_speed,
+            self.speed_up,
+            self.points_to_win,
+        )
+    }
+}
+
+/// Every set of [`Rules`] to pick from, read from [`RULE_BOOK`] so new modes need no code.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct RuleBook {
+    pub rule_sets: Vec<Rules>,
+}
+
+#[derive(Default)]
+struct RuleBookLoader;
+
+impl AssetLoader for RuleBookLoader {
+    type Asset = RuleBook;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Resource)]
+struct RuleBookHandle(Handle<RuleBook>);
+
+/// The [`RuleBook`], once it has loaded.
+#[derive(SystemParam)]
+struct LoadedRuleBook<'w> {
+    handle: Res<'w, RuleBookHandle>,
+    books: Res<'w, Assets<RuleBook>>,
+}
+
+impl LoadedRuleBook<'_> {
+    fn get(&self) -> Option<&RuleBook> {
+        self.books.get(&self.handle.0)
+    }
+}
+
+/// Which of the [`RuleBook`]'s rule sets is played, by its place in the book.
+#[derive(Resource, Default)]
+struct SelectedRules(usize);
+
+#[derive(Component)]
+struct RulesText;
+
+/// Loads the [`RuleBook`] and the rules screen.
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<RuleBook>()
+            .init_asset_loader::<RuleBookLoader>()
+            .init_resource::<Rules>()
+            .init_resource::<SelectedRules>()
+            .add_systems(Startup, load_rule_book)
+            .add_systems(Update, apply_rules_system)
+            .add_systems(OnEnter(MatchState::PickingRules), spawn_rules_screen)
+            .add_systems(
+                Update,
+                (pick_rules_system, rules_text_system).chain().run_if(in_state(MatchState::PickingRules)),
+            );
+    }
+}
+
+fn load_rule_book(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(RuleBookHandle(asset_server.load(RULE_BOOK)));
+}
+
+/// Applies the selected rule set, keeping the classic rules until the book loads.
+fn apply_rules_system(
+    mut book_events: EventReader<AssetEvent<RuleBook>>,
+    selected: Res<SelectedRules>,
+    rule_book: LoadedRuleBook,
+    mut rules: ResMut<Rules>,
+) {
+    let book_changed = book_events.read().count() > 0;
+    if !book_changed && !selected.is_changed() {
+        return;
+    }
+
+    let Some(picked) = rule_book.get().and_then(|book| book.rule_sets.get(selected.0).or(book.rule_sets.first()))
+    else {
+        return;
+    };
+    if rules.set_if_neq(picked.clone()) {
+        info!("playing by the {} rules", rules.name);
+    }
+}
+
+fn spawn_rules_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.),
+                ..default()
+            },
+            StateScoped(MatchState::PickingRules),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Rules"),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 2.,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+            ));
+
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 0.75,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+                RulesText,
+            ));
+
+            parent.spawn((
+                Text::new(format!(
+                    "Up/Down to pick, Enter or Escape for the menu\nEdit assets/{RULE_BOOK} to add your own"
+                )),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 0.75,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+}
+
+/// Steps through the rule sets with Up and Down, going back to the menu on Enter or Escape.
+fn pick_rules_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    rule_book: LoadedRuleBook,
+    mut selected: ResMut<SelectedRules>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    if keys.any_just_pressed([KeyCode::Enter, KeyCode::Escape]) {
+        next_state.set(MatchState::Menu);
+        return;
+    }
+
+    let Some(count) = rule_book.get().map(|book| book.rule_sets.len()).filter(|&count| count > 0) else {
+        return;
+    };
+    // The book may have lost rule sets since this one was picked.
+    let current = selected.0.min(count - 1);
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        selected.0 = (current + 1) % count;
+    } else if keys.just_pressed(KeyCode::ArrowUp) {
+        selected.0 = (current + count - 1) % count;
+    }
+}
+
+/// Lists every rule set in the book, the one being played marked and in colour.
+fn rules_text_system(
+    rules: Res<Rules>,
+    rule_book: LoadedRuleBook,
+    mut commands: Commands,
+    text_query: Query<(Entity, Ref<RulesText>)>,
+) {
+    let Ok((entity, text)) = text_query.get_single() else {
+        return;
+    };
+    if !text.is_added() && !rules.is_changed() && !rule_book.books.is_changed() {
+        return;
+    }
+
+    commands.entity(entity).despawn_descendants();
+    let Some(book) = rule_book.get() else {
+        commands.entity(entity).insert(Text::new(format!("Loading {RULE_BOOK}...")));
+        return;
+    };
+
+    commands.entity(entity).insert(Text::default()).with_children(|parent| {
+        for rule_set in &book.rule_sets {
+            let playing = *rule_set == *rules;
+            parent.spawn((
+                TextSpan::new(format!(
+                    "{} {}\n{}\n\n",
+                    if playing { ">" } else { " " },
+                    rule_set.name,
+                    rule_set.description(),
+                )),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 0.75,
+                    ..default()
+                },
+                TextColor(if playing { SELECTED_RULES_COLOR } else { SCORE_COLOR }),
+            ));
+        }
+    });
+}
@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+use crate::ball::GoalEvent;
+use crate::menu::GameMode;
+use crate::rules::Rules;
+use crate::{MatchState, PhysicsSet};
+
+/// Points per player. The ball getting past a paddle's edge of the window scores for the other player.
+#[derive(Resource, Default, PartialEq)]
+pub struct Score {
+    pub player_1: u32,
+    pub player_2: u32,
+}
+
+/// Games in a match, picked on the menu. Whoever wins more than half of them wins the match.
+#[derive(Resource)]
+pub struct BestOf(pub u32);
+
+impl Default for BestOf {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Games won per player so far this match.
+#[derive(Resource, Default, PartialEq)]
+pub struct Games {
+    pub player_1: u32,
+    pub player_2: u32,
+}
+
+/// Lives each player starts a game of quad pong with.
+const QUAD_LIVES: u32 = 5;
+
+/// Lives left per player in quad pong, player 1 first.
+#[derive(Resource)]
+pub struct Lives(pub [u32; 4]);
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self([QUAD_LIVES; 4])
+    }
+}
+
+impl Lives {
+    pub fn left(&self, player: u8) -> u32 {
+        self.0[usize::from(player - 1)]
+    }
+
+    /// The one player with lives left, once everyone else has run out.
+    pub fn last_standing(&self) -> Option<u8> {
+        let mut standing = (1..=4).filter(|&player| self.left(player) > 0);
+        match (standing.next(), standing.next()) {
+            (Some(player), None) => Some(player),
+            _ => None,
+        }
+    }
+}
+
+/// What each paddle's player is called on the score and between games, player 1 first.
+#[derive(Resource)]
+pub struct PlayerNames(pub [String; 4]);
+
+impl Default for PlayerNames {
+    fn default() -> Self {
+        Self(std::array::from_fn(|index| format!("Player {}", index + 1)))
+    }
+}
+
+impl PlayerNames {
+    pub fn name(&self, player: u8) -> &str {
+        &self.0[usize::from(player - 1)]
+    }
+}
+
+/// Points, games and the match, or lives in quad pong.
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .init_resource::<BestOf>()
+            .init_resource::<Games>()
+            .init_resource::<Lives>()
+            .init_resource::<PlayerNames>()
+            .add_systems(
+                FixedUpdate,
+                // The computer's game behind the menu is only for show.
+                (scoring_system, match_point_system)
+                    .chain()
+                    .in_set(PhysicsSet::Respond)
+                    .run_if(in_state(MatchState::Playing)),
+            )
+            .add_systems(OnEnter(MatchState::Menu), (reset_match, reset_score, reset_lives, reset_player_names))
+            .add_systems(
+                OnTransition { exited: MatchState::GameOver, entered: MatchState::Serving },
+                (reset_match, reset_score, reset_lives),
+            )
+            .add_systems(OnTransition { exited: MatchState::BetweenGames, entered: MatchState::Serving }, reset_score);
+    }
+}
+
+/// Scores a goal, or takes a life in quad pong, ignoring further goals the same step.
+fn scoring_system(
+    game_mode: Res<GameMode>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut goals: EventReader<GoalEvent>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    let Some(goal) = goals.read().last() else {
+        return;
+    };
+
+    if *game_mode == GameMode::Quad {
+        let left = &mut lives.0[usize::from(goal.against - 1)];
+        *left = left.saturating_sub(1);
+        if lives.last_standing().is_some() {
+            next_state.set(MatchState::GameOver);
+            return;
+        }
+    } else if goal.scorer == Some(1) {
+        score.player_1 += 1;
+    } else {
+        score.player_2 += 1;
+    }
+    next_state.set(MatchState::InstantReplay);
+}
+
+/// Ends the game at the points to win, and the match past half the [`BestOf`].
+fn match_point_system(
+    score: Res<Score>,
+    rules: Res<Rules>,
+    best_of: Res<BestOf>,
+    mut games: ResMut<Games>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    if !score.is_changed() || score.player_1.max(score.player_2) < rules.points_to_win {
+        return;
+    }
+
+    let won = if score.player_1 > score.player_2 { &mut games.player_1 } else { &mut games.player_2 };
+    *won += 1;
+    if *won > best_of.0 / 2 {
+        next_state.set(MatchState::GameOver);
+    } else {
+        next_state.set(MatchState::BetweenGames);
+    }
+}
+
+/// Starts a match with no games won.
+fn reset_match(mut games: ResMut<Games>) {
+    *games = Games::default();
+}
+
+/// Starts a game with no points.
+fn reset_score(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
+/// Starts a game of quad pong with every player's lives.
+fn reset_lives(mut lives: ResMut<Lives>) {
+    *lives = Lives::default();
+}
+
+fn reset_player_names(mut names: ResMut<PlayerNames>) {
+    *names = PlayerNames::default();
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::attract::Attract;
+use crate::ball::{Collider, CollisionEvent, GoalEvent, Rally};
+
+const PADDLE_SOUND: &str = "audio/paddle.wav";
+const WALL_SOUND: &str = "audio/wall.wav";
+const SCORE_SOUND: &str = "audio/score.wav";
+
+/// How much higher the paddle blip plays with each hit of a rally.
+const PITCH_STEP: f32 = 0.04;
+/// The highest the paddle blip goes, however long the rally.
+const MAX_PITCH: f32 = 2.;
+
+#[derive(Resource)]
+struct Sounds {
+    paddle: Handle<AudioSource>,
+    wall: Handle<AudioSource>,
+    score: Handle<AudioSource>,
+}
+
+/// Blips for bounces and a buzzer for points.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_sounds).add_systems(Update, sound_effect_system);
+    }
+}
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Sounds {
+        paddle: asset_server.load(PADDLE_SOUND),
+        wall: asset_server.load(WALL_SOUND),
+        score: asset_server.load(SCORE_SOUND),
+    });
+}
+
+/// Paddle blips rise in pitch with the rally; the demo plays silently.
+fn sound_effect_system(
+    mut commands: Commands,
+    sounds: Res<Sounds>,
+    rally: Res<Rally>,
+    attract: Res<State<Attract>>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+) {
+    if *attract.get() == Attract::Running {
+        collision_events.clear();
+        goal_events.clear();
+        return;
+    }
+
+    let mut play = |sound: &Handle<AudioSource>, pitch: f32| {
+        commands.spawn((AudioPlayer::new(sound.clone()), PlaybackSettings::DESPAWN.with_speed(pitch)));
+    };
+
+    for event in collision_events.read() {
+        match event.collider {
+            Collider::Paddle(_) => play(&sounds.paddle, (1. + rally.hits as f32 * PITCH_STEP).min(MAX_PITCH)),
+            Collider::Wall(_) | Collider::Obstacle(_) => play(&sounds.wall, 1.),
+        }
+    }
+    for _ in goal_events.read() {
+        play(&sounds.score, 1.);
+    }
+}
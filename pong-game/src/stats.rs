@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use crate::ball::{Collider, CollisionEvent, Rally};
+use crate::menu::GameMode;
+use crate::paddle::Paddle;
+use crate::rules::Rules;
+use crate::score::PlayerNames;
+use crate::ui::{SCORE_COLOR, SCORE_FONT_SIZE};
+use crate::MatchState;
+
+const OVERLAY_COLOR: Color = Color::srgba(0.95, 0.95, 0.95, 0.85);
+
+/// How the match has gone so far, across all its games.
+#[derive(Resource, Default)]
+pub struct MatchStats {
+    longest_rally: u32,
+    /// Paddle hits per player, player 1 first.
+    hits: [u32; 4],
+    /// The ball's speed at every paddle hit relative to the serve, added up, to average.
+    total_speed_up: f32,
+}
+
+impl MatchStats {
+    /// The ball's average speed at the paddles relative to the serve, or 1 before anyone has hit it.
+    fn average_speed_up(&self) -> f32 {
+        let hits: u32 = self.hits.iter().sum();
+        if hits == 0 {
+            return 1.;
+        }
+        self.total_speed_up / hits as f32
+    }
+
+    /// A few lines on the match between `players` players, for the overlay and the winner screen.
+    pub fn summary(&self, names: &PlayerNames, players: u8) -> String {
+        let mut summary = format!("Longest rally: {}\n", self.longest_rally);
+        for player in 1..=players {
+            summary += &format!("{} hits: {}\n", names.name(player), self.hits[usize::from(player - 1)]);
+        }
+        summary + &format!("Average speed: {:.2}x", self.average_speed_up())
+    }
+}
+
+#[derive(Component)]
+struct StatsOverlay;
+
+/// Keeps the [`MatchStats`], and an overlay of them and the current rally toggled with Tab.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchStats>()
+            .add_systems(Startup, spawn_stats_overlay)
+            .add_systems(Update, (record_hits_system, toggle_stats_system, stats_overlay_system).chain())
+            .add_systems(OnEnter(MatchState::Menu), reset_stats)
+            .add_systems(OnTransition { exited: MatchState::GameOver, entered: MatchState::Serving }, reset_stats);
+    }
+}
+
+fn spawn_stats_overlay(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: SCORE_FONT_SIZE * 0.75,
+            ..default()
+        },
+        TextColor(SCORE_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(12.),
+            top: Val::Px(12.),
+            padding: UiRect::all(Val::Px(8.)),
+            ..default()
+        },
+        BackgroundColor(OVERLAY_COLOR),
+        Visibility::Hidden,
+        StatsOverlay,
+    ));
+}
+
+fn record_hits_system(
+    mut stats: ResMut<MatchStats>,
+    rules: Res<Rules>,
+    rally: Res<Rally>,
+    mut collision_events: EventReader<CollisionEvent>,
+    paddle_query: Query<&Paddle>,
+) {
+    for event in collision_events.read() {
+        let Collider::Paddle(entity) = event.collider else {
+            continue;
+        };
+        if let Ok(paddle) = paddle_query.get(entity) {
+            stats.hits[usize::from(paddle.player - 1)] += 1;
+            stats.total_speed_up += event.speed / rules.ball_speed;
+        }
+    }
+
+    if rally.hits > stats.longest_rally {
+        stats.longest_rally = rally.hits;
+    }
+}
+
+fn toggle_stats_system(keys: Res<ButtonInput<KeyCode>>, mut query: Query<&mut Visibility, With<StatsOverlay>>) {
+    if !keys.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    for mut visibility in query.iter_mut() {
+        visibility.toggle_inherited_hidden();
+    }
+}
+
+fn stats_overlay_system(
+    stats: Res<MatchStats>,
+    rally: Res<Rally>,
+    names: Res<PlayerNames>,
+    game_mode: Res<GameMode>,
+    mut query: Query<&mut Text, With<StatsOverlay>>,
+) {
+    if !stats.is_changed() && !rally.is_changed() && !names.is_changed() && !game_mode.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.0 = format!("Rally: {}\n{}", rally.hits, stats.summary(&names, game_mode.players()));
+    }
+}
+
+fn reset_stats(mut stats: ResMut<MatchStats>) {
+    *stats = MatchStats::default();
+}
@@ -0,0 +1,303 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+
+use crate::menu::GameMode;
+use crate::score::{Games, PlayerNames};
+use crate::ui::{SCORE_COLOR, SCORE_FONT_SIZE};
+use crate::MatchState;
+
+const MIN_PLAYERS: usize = 2;
+const MAX_PLAYERS: usize = 4;
+const NAME_MAX_LENGTH: usize = 12;
+
+/// Where a player in a bracket match comes from.
+#[derive(Clone, Copy)]
+enum Seat {
+    /// A player by their seed, 0 being the top seed.
+    Player(usize),
+    /// Whoever wins an earlier match of the bracket.
+    WinnerOf(usize),
+}
+
+struct BracketMatch {
+    /// Player 1's seat first, then player 2's.
+    seats: [Seat; 2],
+    winner: Option<usize>,
+}
+
+impl BracketMatch {
+    fn new(player_1: Seat, player_2: Seat) -> Self {
+        Self { seats: [player_1, player_2], winner: None }
+    }
+}
+
+/// A knockout bracket, seeded in entry order.
+#[derive(Resource)]
+struct Tournament {
+    players: Vec<String>,
+    matches: Vec<BracketMatch>,
+}
+
+impl Tournament {
+    fn new(players: Vec<String>) -> Self {
+        let matches = match players.len() {
+            2 => vec![BracketMatch::new(Seat::Player(0), Seat::Player(1))],
+            // The top seed gets a bye into the final.
+            3 => vec![
+                BracketMatch::new(Seat::Player(1), Seat::Player(2)),
+                BracketMatch::new(Seat::Player(0), Seat::WinnerOf(0)),
+            ],
+            _ => vec![
+                BracketMatch::new(Seat::Player(0), Seat::Player(3)),
+                BracketMatch::new(Seat::Player(1), Seat::Player(2)),
+                BracketMatch::new(Seat::WinnerOf(0), Seat::WinnerOf(1)),
+            ],
+        };
+        Self { players, matches }
+    }
+
+    /// The player in `seat`, once they are known.
+    fn seated(&self, seat: Seat) -> Option<usize> {
+        match seat {
+            Seat::Player(player) => Some(player),
+            Seat::WinnerOf(index) => self.matches[index].winner,
+        }
+    }
+
+    fn seat_name(&self, seat: Seat) -> &str {
+        self.seated(seat).map_or("?", |player| &self.players[player])
+    }
+
+    /// The match being played, or next up, if the tournament isn't over.
+    fn current(&self) -> Option<usize> {
+        self.matches.iter().position(|bracket_match| bracket_match.winner.is_none())
+    }
+
+    fn champion(&self) -> Option<&str> {
+        let winner = self.matches.last()?.winner?;
+        Some(&self.players[winner])
+    }
+
+    fn round_name(&self, index: usize) -> String {
+        match self.matches.len() - 1 {
+            semi_finals if index == semi_finals => "Final".to_string(),
+            1 => "Semi-final".to_string(),
+            _ => format!("Semi-final {}", index + 1),
+        }
+    }
+
+    /// Hands the paddles to the players of the current match, if there is one left to play.
+    fn seat_current(&self, names: &mut PlayerNames) -> bool {
+        let Some(current) = self.current() else {
+            return false;
+        };
+
+        let seats = self.matches[current].seats;
+        for (name, seat) in names.0.iter_mut().zip(seats) {
+            *name = self.seat_name(seat).to_string();
+        }
+        true
+    }
+}
+
+/// The players entered so far on the setup screen, and the name being typed.
+#[derive(Resource, Default)]
+struct TournamentEntry {
+    players: Vec<String>,
+    name: String,
+}
+
+#[derive(Component)]
+struct SetupText;
+
+/// A local knockout tournament of up to [`MAX_PLAYERS`] named players.
+pub struct TournamentPlugin;
+
+impl Plugin for TournamentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MatchState::TournamentSetup), spawn_setup_screen)
+            .add_systems(
+                Update,
+                (name_entry_system, setup_text_system).chain().run_if(in_state(MatchState::TournamentSetup)),
+            )
+            .add_systems(
+                OnEnter(MatchState::GameOver),
+                (record_result, spawn_bracket_screen).chain().run_if(resource_equals(GameMode::Tournament)),
+            )
+            .add_systems(
+                Update,
+                bracket_system.run_if(in_state(MatchState::GameOver)).run_if(resource_equals(GameMode::Tournament)),
+            );
+    }
+}
+
+fn spawn_setup_screen(mut commands: Commands) {
+    commands.insert_resource(TournamentEntry::default());
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            StateScoped(MatchState::TournamentSetup),
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: SCORE_FONT_SIZE,
+                ..default()
+            },
+            TextColor(SCORE_COLOR),
+            TextLayout::new_with_justify(JustifyText::Center),
+            SetupText,
+        ));
+}
+
+/// Adds typed names on Enter; an empty Enter starts, Escape leaves.
+fn name_entry_system(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut entry: ResMut<TournamentEntry>,
+    mut names: ResMut<PlayerNames>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        let name = &mut entry.name;
+        match &event.logical_key {
+            Key::Character(text) => {
+                for character in text.chars().filter(|character| character.is_alphanumeric()) {
+                    if name.chars().count() < NAME_MAX_LENGTH {
+                        name.push(character);
+                    }
+                }
+            }
+            Key::Space if !name.is_empty() && name.chars().count() < NAME_MAX_LENGTH => name.push(' '),
+            Key::Backspace => {
+                name.pop();
+            }
+            Key::Enter if !name.trim().is_empty() => {
+                if entry.players.len() < MAX_PLAYERS {
+                    let name = entry.name.trim().to_string();
+                    entry.players.push(name);
+                }
+                entry.name.clear();
+            }
+            Key::Enter if entry.players.len() >= MIN_PLAYERS => {
+                let tournament = Tournament::new(std::mem::take(&mut entry.players));
+                tournament.seat_current(&mut names);
+                commands.insert_resource(tournament);
+                next_state.set(MatchState::Serving);
+                return;
+            }
+            Key::Escape => {
+                next_state.set(MatchState::Menu);
+                return;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn setup_text_system(entry: Res<TournamentEntry>, mut query: Query<&mut Text, With<SetupText>>) {
+    if !entry.is_changed() {
+        return;
+    }
+
+    let mut lines = vec!["Tournament".to_string(), String::new()];
+    for (seed, player) in entry.players.iter().enumerate() {
+        lines.push(format!("{}. {player}", seed + 1));
+    }
+    if entry.players.len() < MAX_PLAYERS {
+        lines.push(format!("{}. {}_", entry.players.len() + 1, entry.name));
+    }
+    lines.push(String::new());
+    lines.push(format!("Type a name and press Enter to add it, up to {MAX_PLAYERS} players"));
+    if entry.players.len() >= MIN_PLAYERS {
+        lines.push("Press Enter with no name to start".to_string());
+    }
+    lines.push("Press Escape for the menu".to_string());
+
+    for mut text in query.iter_mut() {
+        text.0 = lines.join("\n");
+    }
+}
+
+/// Puts the winner of the match just played through to their next round.
+fn record_result(games: Res<Games>, mut tournament: ResMut<Tournament>) {
+    let Some(current) = tournament.current() else {
+        return;
+    };
+
+    let seat = tournament.matches[current].seats[usize::from(games.player_2 > games.player_1)];
+    tournament.matches[current].winner = tournament.seated(seat);
+}
+
+fn spawn_bracket_screen(mut commands: Commands, tournament: Res<Tournament>) {
+    let mut lines = vec!["Tournament".to_string(), String::new()];
+    for (index, bracket_match) in tournament.matches.iter().enumerate() {
+        let [player_1, player_2] = bracket_match.seats.map(|seat| tournament.seat_name(seat));
+        let mut line = format!("{}: {player_1} vs {player_2}", tournament.round_name(index));
+        if let Some(winner) = bracket_match.winner {
+            line += &format!(", won by {}", tournament.players[winner]);
+        }
+        lines.push(line);
+    }
+    lines.push(String::new());
+
+    if let Some(champion) = tournament.champion() {
+        lines.push(format!("{champion} wins the tournament!"));
+        lines.push("Press Enter for the menu".to_string());
+    } else if let Some(current) = tournament.current() {
+        let [player_1, player_2] = tournament.matches[current].seats.map(|seat| tournament.seat_name(seat));
+        lines.push(format!("Next up: {player_1} vs {player_2}"));
+        lines.push("Press Enter to play it\nor Escape for the menu".to_string());
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            StateScoped(MatchState::GameOver),
+        ))
+        .with_child((
+            Text::new(lines.join("\n")),
+            TextFont {
+                font_size: SCORE_FONT_SIZE * 1.25,
+                ..default()
+            },
+            TextColor(SCORE_COLOR),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+}
+
+/// Starts the next match on Enter, or goes back to the menu.
+fn bracket_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    tournament: Res<Tournament>,
+    mut names: ResMut<PlayerNames>,
+    mut next_state: ResMut<NextState<MatchState>>,
+) {
+    if keys.just_pressed(KeyCode::Enter) {
+        if tournament.seat_current(&mut names) {
+            next_state.set(MatchState::Serving);
+        } else {
+            next_state.set(MatchState::Menu);
+        }
+    } else if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(MatchState::Menu);
+    }
+}
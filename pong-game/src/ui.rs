@@ -0,0 +1,352 @@
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+
+use crate::ball::{Rally, Serve, SERVE_COUNTDOWN};
+use crate::court::Orientation;
+use crate::menu::GameMode;
+use crate::network::runs_match;
+use crate::rules::Rules;
+use crate::score::{BestOf, Games, Lives, PlayerNames, Score};
+use crate::stats::MatchStats;
+use crate::MatchState;
+
+pub const SCORE_FONT_SIZE: f32 = 24.;
+pub const SCORE_COLOR: Color = Color::srgb(0.2, 0.2, 0.2);
+
+const PAUSE_OVERLAY_COLOR: Color = Color::srgba(0., 0., 0., 0.5);
+const PAUSE_TEXT_COLOR: Color = Color::srgb(0.95, 0.95, 0.95);
+
+/// How far the score and rally sit in from the window's edges, clear of paddles on them.
+const HUD_SIDE_PADDLES_INSET: f32 = 48.;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct RallyText;
+
+#[derive(Component)]
+struct CountdownText;
+
+/// The HUD and the between-games, game over and pause screens.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hud)
+            .add_systems(Update, (score_text_system, rally_text_system, pause_system))
+            .add_systems(
+                Update,
+                hud_layout_system.run_if(resource_changed::<Orientation>.or(resource_changed::<GameMode>)),
+            )
+            .add_systems(OnEnter(MatchState::Serving), spawn_countdown)
+            .add_systems(Update, countdown_text_system.run_if(in_state(MatchState::Serving)))
+            .add_systems(OnEnter(MatchState::Paused), spawn_pause_screen)
+            .add_systems(OnEnter(MatchState::BetweenGames), spawn_between_games_screen)
+            // A LAN guest waits for the host to move on, and a rollback match moves on by itself.
+            .add_systems(
+                Update,
+                next_game_system.run_if(in_state(MatchState::BetweenGames)).run_if(runs_match),
+            )
+            .add_systems(
+                OnEnter(MatchState::GameOver),
+                spawn_game_over_screen.run_if(not(resource_equals(GameMode::Tournament))),
+            )
+            .add_systems(
+                Update,
+                rematch_system
+                    .run_if(in_state(MatchState::GameOver))
+                    .run_if(not(resource_equals(GameMode::Tournament)))
+                    .run_if(runs_match),
+            );
+    }
+}
+
+fn spawn_hud(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: SCORE_FONT_SIZE,
+            ..default()
+        },
+        TextColor(SCORE_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.),
+            top: Val::Percent(45.),
+            ..default()
+        },
+        ScoreText,
+    ));
+
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: SCORE_FONT_SIZE,
+            ..default()
+        },
+        TextColor(SCORE_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(12.),
+            top: Val::Percent(45.),
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Right),
+        RallyText,
+    ));
+}
+
+/// Moves the score and rally text out of the paddles' way for the current layout.
+#[allow(clippy::type_complexity)]
+fn hud_layout_system(
+    orientation: Res<Orientation>,
+    game_mode: Res<GameMode>,
+    mut query: Query<(&mut Node, Has<ScoreText>), Or<(With<ScoreText>, With<RallyText>)>>,
+) {
+    let (inset, top) = match *orientation {
+        _ if game_mode.players() == 4 => (Val::Px(HUD_SIDE_PADDLES_INSET), Val::Px(HUD_SIDE_PADDLES_INSET)),
+        Orientation::TopBottom => (Val::Px(12.), Val::Percent(45.)),
+        Orientation::LeftRight => (Val::Px(HUD_SIDE_PADDLES_INSET), Val::Px(12.)),
+    };
+
+    // The score is on the left, the rally on the right.
+    for (mut node, is_score) in query.iter_mut() {
+        node.top = top;
+        if is_score {
+            node.left = inset;
+        } else {
+            node.right = inset;
+        }
+    }
+}
+
+/// The points and games of a two-player match, or every player's [`Lives`] in quad pong.
+#[allow(clippy::too_many_arguments)]
+fn score_text_system(
+    score: Res<Score>,
+    games: Res<Games>,
+    best_of: Res<BestOf>,
+    lives: Res<Lives>,
+    game_mode: Res<GameMode>,
+    names: Res<PlayerNames>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
+    let changed = score.is_changed() || games.is_changed() || best_of.is_changed() || lives.is_changed();
+    if !changed && !game_mode.is_changed() && !names.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        if *game_mode == GameMode::Quad {
+            text.0 = "Lives".to_string();
+            for player in 1..=4 {
+                text.0 += &format!("\n{}: {}", names.name(player), lives.left(player));
+            }
+            continue;
+        }
+
+        text.0 = format!("{}: {}\n{}: {}", names.name(1), score.player_1, names.name(2), score.player_2);
+        if best_of.0 > 1 {
+            text.0 += &format!("\nGames: {}-{}", games.player_1, games.player_2);
+        }
+    }
+}
+
+fn rally_text_system(rally: Res<Rally>, rules: Res<Rules>, mut query: Query<&mut Text, With<RallyText>>) {
+    if !rally.is_changed() && !rules.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.0 = format!("Rally: {}\nSpeed: {:.2}x", rally.hits, rally.speed / rules.ball_speed);
+    }
+}
+
+fn spawn_countdown(mut commands: Commands) {
+    commands.spawn((
+        Text::new(format!("{}", SERVE_COUNTDOWN.ceil())),
+        TextFont {
+            font_size: SCORE_FONT_SIZE * 3.,
+            ..default()
+        },
+        TextColor(SCORE_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            top: Val::Percent(30.),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        CountdownText,
+        StateScoped(MatchState::Serving),
+    ));
+}
+
+/// Counts down 3-2-1 to the serve.
+fn countdown_text_system(serve: Res<Serve>, mut query: Query<&mut Text, With<CountdownText>>) {
+    for mut text in query.iter_mut() {
+        text.0 = format!("{}", serve.countdown.remaining_secs().ceil());
+    }
+}
+
+/// Pauses on Escape or lost focus; Escape resumes and M goes back to the menu.
+fn pause_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut focus_events: EventReader<WindowFocused>,
+    state: Res<State<MatchState>>,
+    mut next_state: ResMut<NextState<MatchState>>,
+    mut paused_from: Local<Option<MatchState>>,
+) {
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+
+    match *state.get() {
+        running @ (MatchState::Playing | MatchState::Serving) if lost_focus || keys.just_pressed(KeyCode::Escape) => {
+            *paused_from = Some(running);
+            next_state.set(MatchState::Paused);
+        }
+        MatchState::Paused if keys.just_pressed(KeyCode::Escape) => {
+            next_state.set(paused_from.take().unwrap_or(MatchState::Serving));
+        }
+        MatchState::Paused if keys.just_pressed(KeyCode::KeyM) => next_state.set(MatchState::Menu),
+        _ => {}
+    }
+}
+
+fn spawn_pause_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(PAUSE_OVERLAY_COLOR),
+            GlobalZIndex(1),
+            StateScoped(MatchState::Paused),
+        ))
+        .with_child((
+            Text::new("Paused\nPress Escape to resume\nPress M for the menu"),
+            TextFont {
+                font_size: SCORE_FONT_SIZE * 1.5,
+                ..default()
+            },
+            TextColor(PAUSE_TEXT_COLOR),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+}
+
+fn spawn_between_games_screen(mut commands: Commands, score: Res<Score>, games: Res<Games>, names: Res<PlayerNames>) {
+    let winner = if score.player_1 > score.player_2 { 1 } else { 2 };
+    let game = games.player_1 + games.player_2;
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            StateScoped(MatchState::BetweenGames),
+        ))
+        .with_child((
+            Text::new(format!(
+                "{} takes game {game}, {}-{}\n\
+                 Games: {} {}, {} {}\n\
+                 Press Enter for the next game",
+                names.name(winner),
+                score.player_1.max(score.player_2),
+                score.player_1.min(score.player_2),
+                names.name(1),
+                games.player_1,
+                names.name(2),
+                games.player_2,
+            )),
+            TextFont {
+                font_size: SCORE_FONT_SIZE * 1.5,
+                ..default()
+            },
+            TextColor(SCORE_COLOR),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+}
+
+/// Starts the next game of the match on Enter, or abandons the match for the menu on Escape.
+fn next_game_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<MatchState>>) {
+    if keys.just_pressed(KeyCode::Enter) {
+        next_state.set(MatchState::Serving);
+    } else if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(MatchState::Menu);
+    }
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    games: Res<Games>,
+    lives: Res<Lives>,
+    game_mode: Res<GameMode>,
+    names: Res<PlayerNames>,
+    stats: Res<MatchStats>,
+) {
+    let result = if let Some(winner) = lives.last_standing().filter(|_| *game_mode == GameMode::Quad) {
+        format!("{} is the last one standing!", names.name(winner))
+    } else {
+        let winner = if games.player_1 > games.player_2 { 1 } else { 2 };
+        let won = if games.player_1 + games.player_2 > 1 {
+            format!("the match {}-{}", games.player_1.max(games.player_2), games.player_1.min(games.player_2))
+        } else {
+            "the game".to_string()
+        };
+        format!("{} wins {won}!", names.name(winner))
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.),
+                ..default()
+            },
+            StateScoped(MatchState::GameOver),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("{result}\nPress Enter for a rematch\nor Escape for the menu")),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE * 2.,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+
+            parent.spawn((
+                Text::new(stats.summary(&names, game_mode.players())),
+                TextFont {
+                    font_size: SCORE_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(SCORE_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+}
+
+/// Starts the next match on Enter, or goes back to the menu to pick another mode on Escape.
+fn rematch_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<MatchState>>) {
+    if keys.just_pressed(KeyCode::Enter) {
+        next_state.set(MatchState::Serving);
+    } else if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(MatchState::Menu);
+    }
+}
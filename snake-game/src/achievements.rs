@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::food::EatEvent;
+use crate::round::Winner;
+use crate::snake::{AiControlled, DirectionQueue, Player, Snake};
+use crate::ui::{HUD_COLOR, HUD_FONT_SIZE};
+use crate::{storage, GameState, StepSet};
+
+const STATS_KEY: &str = "snake-game.stats";
+
+const TOAST_WIDTH: f32 = 300.;
+const TOAST_SLIDE_DURATION: f32 = 0.3;
+const TOAST_DURATION: f32 = 3.;
+const TOAST_SPACING: f32 = 60.;
+const TOAST_COLOR: Color = Color::srgba(0., 0., 0., 0.8);
+const TOAST_TEXT_COLOR: Color = Color::srgb(0.95, 0.95, 0.95);
+const LOCKED_COLOR: Color = Color::srgb(0.55, 0.55, 0.55);
+
+const LONG_SNAKE_LENGTH: usize = 50;
+const GLUTTON_FOOD: u64 = 1000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+enum Achievement {
+    LongSnake,
+    NoLeftTurns,
+    Glutton,
+}
+
+impl Achievement {
+    const ALL: [Achievement; 3] = [Achievement::LongSnake, Achievement::NoLeftTurns, Achievement::Glutton];
+
+    fn title(self) -> &'static str {
+        match self {
+            Achievement::LongSnake => "Long Story",
+            Achievement::NoLeftTurns => "Right Minded",
+            Achievement::Glutton => "Glutton",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Achievement::LongSnake => "Grow to a length of 50 to unlock rainbow snakes",
+            Achievement::NoLeftTurns => "Win a two-player round without turning left",
+            Achievement::Glutton => "Eat 1000 food",
+        }
+    }
+}
+
+/// Lifetime statistics of the human players, kept in [`STATS_KEY`].
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct LifetimeStats {
+    food_eaten: u64,
+    longest_snake: usize,
+    deaths: u32,
+    unlocked: Vec<Achievement>,
+}
+
+impl LifetimeStats {
+    /// Whether [`SnakeColoring::Rainbow`](crate::snake::SnakeColoring::Rainbow) can be picked.
+    pub fn rainbow_unlocked(&self) -> bool {
+        self.unlocked.contains(&Achievement::LongSnake)
+    }
+
+    fn unlock(&mut self, achievement: Achievement) -> bool {
+        if self.unlocked.contains(&achievement) {
+            return false;
+        }
+
+        self.unlocked.push(achievement);
+        true
+    }
+}
+
+/// Marks a snake that has turned left this round.
+#[derive(Component)]
+struct TurnedLeft;
+
+#[derive(Event)]
+struct Unlocked(Achievement);
+
+#[derive(Component)]
+struct Toast {
+    timer: Timer,
+}
+
+/// Lifetime statistics, the achievements they unlock, the toasts announcing them and the stats
+/// screen listing both.
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<LifetimeStats>(STATS_KEY).unwrap_or_default())
+            .add_event::<Unlocked>()
+            .add_systems(FixedUpdate, (food_stats_system, length_stats_system).in_set(StepSet::Grow))
+            .add_systems(FixedUpdate, left_turn_system.in_set(StepSet::Collide))
+            .add_systems(Update, (unlock_toast_system, toast_system))
+            .add_systems(OnEnter(GameState::GameOver), record_round)
+            .add_systems(OnEnter(GameState::Stats), spawn_stats_screen);
+    }
+}
+
+fn food_stats_system(
+    mut eat_events: EventReader<EatEvent>,
+    human_query: Query<(), (With<Snake>, Without<AiControlled>)>,
+    mut stats: ResMut<LifetimeStats>,
+    mut unlocked: EventWriter<Unlocked>,
+) {
+    let eaten = eat_events.read().filter(|event| human_query.contains(event.snake)).count() as u64;
+    if eaten == 0 {
+        return;
+    }
+
+    stats.food_eaten += eaten;
+    if stats.food_eaten >= GLUTTON_FOOD && stats.unlock(Achievement::Glutton) {
+        unlocked.send(Unlocked(Achievement::Glutton));
+    }
+}
+
+fn length_stats_system(
+    snake_query: Query<&Snake, (Changed<Snake>, Without<AiControlled>)>,
+    mut stats: ResMut<LifetimeStats>,
+    mut unlocked: EventWriter<Unlocked>,
+) {
+    let Some(longest) = snake_query.iter().map(|snake| snake.segments.len()).max() else {
+        return;
+    };
+
+    if longest > stats.longest_snake {
+        stats.longest_snake = longest;
+    }
+    if longest >= LONG_SNAKE_LENGTH && stats.unlock(Achievement::LongSnake) {
+        unlocked.send(Unlocked(Achievement::LongSnake));
+    }
+}
+
+/// Watches each step's heading against the one before it; turning left is turning to its
+/// perpendicular.
+#[allow(clippy::type_complexity)]
+fn left_turn_system(
+    mut commands: Commands,
+    snake_query: Query<(Entity, &DirectionQueue), (Without<AiControlled>, Without<TurnedLeft>)>,
+    mut headings: Local<HashMap<Entity, IVec2>>,
+) {
+    headings.retain(|&entity, _| snake_query.contains(entity));
+
+    for (entity, directions) in snake_query.iter() {
+        let previous = headings.insert(entity, directions.heading);
+        if previous.is_some_and(|previous| directions.heading == previous.perp()) {
+            commands.entity(entity).insert(TurnedLeft);
+        }
+    }
+}
+
+fn record_round(
+    winner: Res<Winner>,
+    snake_query: Query<(&Snake, &Player, Has<TurnedLeft>), Without<AiControlled>>,
+    mut stats: ResMut<LifetimeStats>,
+    mut unlocked: EventWriter<Unlocked>,
+) {
+    stats.deaths += snake_query.iter().filter(|(snake, ..)| !snake.alive).count() as u32;
+
+    let won_right_minded = snake_query
+        .iter()
+        .any(|(_, &player, turned_left)| winner.0 == Some(player) && !turned_left);
+    if won_right_minded && stats.unlock(Achievement::NoLeftTurns) {
+        unlocked.send(Unlocked(Achievement::NoLeftTurns));
+    }
+
+    storage::save(STATS_KEY, &*stats);
+}
+
+fn unlock_toast_system(
+    mut commands: Commands,
+    mut unlocked: EventReader<Unlocked>,
+    toast_query: Query<(), With<Toast>>,
+    stats: Res<LifetimeStats>,
+) {
+    let first_slot = toast_query.iter().count();
+
+    for (slot, Unlocked(achievement)) in (first_slot..).zip(unlocked.read()) {
+        storage::save(STATS_KEY, &*stats);
+
+        commands
+            .spawn((
+                Node {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12. + slot as f32 * TOAST_SPACING),
+                    right: Val::Px(-TOAST_WIDTH),
+                    width: Val::Px(TOAST_WIDTH),
+                    padding: UiRect::all(Val::Px(8.)),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                BackgroundColor(TOAST_COLOR),
+                Toast { timer: Timer::from_seconds(TOAST_DURATION, TimerMode::Once) },
+            ))
+            .with_children(|toast| {
+                toast.spawn((
+                    Text::new(format!("Achievement: {}", achievement.title())),
+                    TextFont {
+                        font_size: HUD_FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(TOAST_TEXT_COLOR),
+                ));
+                toast.spawn((
+                    Text::new(achievement.description()),
+                    TextFont {
+                        font_size: HUD_FONT_SIZE * 0.7,
+                        ..default()
+                    },
+                    TextColor(TOAST_TEXT_COLOR),
+                ));
+            });
+    }
+}
+
+fn toast_system(mut commands: Commands, time: Res<Time>, mut toast_query: Query<(Entity, &mut Toast, &mut Node)>) {
+    for (entity, mut toast, mut node) in toast_query.iter_mut() {
+        if toast.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let elapsed = toast.timer.elapsed_secs();
+        let remaining = toast.timer.remaining_secs();
+        let shown = (elapsed.min(remaining) / TOAST_SLIDE_DURATION).min(1.);
+        node.right = Val::Px(-TOAST_WIDTH + (TOAST_WIDTH + 12.) * shown);
+    }
+}
+
+fn spawn_stats_screen(mut commands: Commands, stats: Res<LifetimeStats>) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.),
+                ..default()
+            },
+            StateScoped(GameState::Stats),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Stats"),
+                TextFont {
+                    font_size: HUD_FONT_SIZE * 3.,
+                    ..default()
+                },
+                TextColor(HUD_COLOR),
+            ));
+
+            parent.spawn((
+                Text::new(format!(
+                    "Food eaten: {}\nLongest snake: {}\nDeaths: {}",
+                    stats.food_eaten, stats.longest_snake, stats.deaths
+                )),
+                TextFont {
+                    font_size: HUD_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(HUD_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+
+            for achievement in Achievement::ALL {
+                let unlocked = stats.unlocked.contains(&achievement);
+                parent.spawn((
+                    Text::new(format!(
+                        "[{}] {}\n{}",
+                        if unlocked { "x" } else { " " },
+                        achievement.title(),
+                        achievement.description()
+                    )),
+                    TextFont {
+                        font_size: HUD_FONT_SIZE * 0.8,
+                        ..default()
+                    },
+                    TextColor(if unlocked { HUD_COLOR } else { LOCKED_COLOR }),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                ));
+            }
+
+            parent.spawn((
+                Text::new("Press Escape to go back"),
+                TextFont {
+                    font_size: HUD_FONT_SIZE * 0.8,
+                    ..default()
+                },
+                TextColor(HUD_COLOR),
+            ));
+        });
+}
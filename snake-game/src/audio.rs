@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::food::{BonusEatenEvent, EatEvent, PowerUpEvent};
+use crate::high_scores::NameEntry;
+use crate::round::GameOverEvent;
+
+const EAT_SOUND: &str = "audio/eat.wav";
+const BONUS_SOUND: &str = "audio/bonus.wav";
+const GAME_OVER_SOUND: &str = "audio/game-over.wav";
+const MUSIC: &str = "audio/music.wav";
+const MUSIC_VOLUME: f32 = 0.4;
+/// Toggles all sound; M already leads back to the menu.
+const MUTE_KEY: KeyCode = KeyCode::KeyV;
+
+#[derive(Resource)]
+struct GameSounds {
+    eat: Handle<AudioSource>,
+    bonus: Handle<AudioSource>,
+    game_over: Handle<AudioSource>,
+}
+
+#[derive(Component)]
+struct Music;
+
+#[derive(Resource, Default)]
+struct Muted(bool);
+
+/// The looping music and the sound effects of eating and dying, all silenced by [`MUTE_KEY`].
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Muted>().add_systems(Startup, load_sounds).add_systems(
+            Update,
+            (mute_system.run_if(not(resource_exists::<NameEntry>)), music_volume_system, sound_effect_system).chain(),
+        );
+    }
+}
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameSounds {
+        eat: asset_server.load(EAT_SOUND),
+        bonus: asset_server.load(BONUS_SOUND),
+        game_over: asset_server.load(GAME_OVER_SOUND),
+    });
+    commands.spawn((AudioPlayer::new(asset_server.load(MUSIC)), PlaybackSettings::LOOP, Music));
+}
+
+fn mute_system(keys: Res<ButtonInput<KeyCode>>, mut muted: ResMut<Muted>) {
+    if keys.just_pressed(MUTE_KEY) {
+        muted.0 = !muted.0;
+    }
+}
+
+/// Keeps the looping music in line with [`Muted`], including once its sink first shows up.
+fn music_volume_system(muted: Res<Muted>, music_query: Query<&AudioSink, With<Music>>) {
+    let volume = if muted.0 { 0. } else { MUSIC_VOLUME };
+    for sink in music_query.iter() {
+        sink.set_volume(volume);
+    }
+}
+
+fn sound_effect_system(
+    mut commands: Commands,
+    sounds: Res<GameSounds>,
+    muted: Res<Muted>,
+    mut eat_events: EventReader<EatEvent>,
+    mut bonus_events: EventReader<BonusEatenEvent>,
+    mut power_up_events: EventReader<PowerUpEvent>,
+    mut game_over_events: EventReader<GameOverEvent>,
+) {
+    let mut play = |sound: &Handle<AudioSource>| {
+        if !muted.0 {
+            commands.spawn((AudioPlayer::new(sound.clone()), PlaybackSettings::DESPAWN));
+        }
+    };
+
+    if eat_events.read().count() > 0 {
+        play(&sounds.eat);
+    }
+    if bonus_events.read().count() + power_up_events.read().count() > 0 {
+        play(&sounds.bonus);
+    }
+    if game_over_events.read().count() > 0 {
+        play(&sounds.game_over);
+    }
+}
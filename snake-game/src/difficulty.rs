@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use crate::grid::BorderMode;
+use crate::snake::GameSpeed;
+
+/// Rings of wall around the board on [`Difficulty::Hard`].
+const HARD_ARENA_INSET: i32 = 6;
+
+/// How hard a game is, picked on the main menu before it starts.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+}
+
+/// What the picked [`Difficulty`] sets up. The speeds and border go to [`GameSpeed`] and
+/// [`BorderMode`] as soon as it is picked, so the settings screen can still fine-tune them.
+#[derive(Resource)]
+pub struct DifficultySettings {
+    pub difficulty: Difficulty,
+    /// Seconds per step before any food is eaten.
+    move_interval: f32,
+    /// Fraction of the interval shaved off per food eaten.
+    speed_step: f32,
+    /// Rings of wall closing the board in from its edge.
+    pub arena_inset: i32,
+    border_mode: BorderMode,
+}
+
+impl DifficultySettings {
+    pub fn new(difficulty: Difficulty) -> Self {
+        let normal = GameSpeed::default();
+        match difficulty {
+            Difficulty::Easy => Self {
+                difficulty,
+                move_interval: normal.base_interval * 1.6,
+                speed_step: normal.step / 2.,
+                arena_inset: 0,
+                border_mode: BorderMode::Wrap,
+            },
+            Difficulty::Normal => Self {
+                difficulty,
+                move_interval: normal.base_interval,
+                speed_step: normal.step,
+                arena_inset: 0,
+                border_mode: BorderMode::Solid,
+            },
+            Difficulty::Hard => Self {
+                difficulty,
+                move_interval: normal.base_interval * 0.8,
+                speed_step: normal.step * 2.,
+                arena_inset: HARD_ARENA_INSET,
+                border_mode: BorderMode::Solid,
+            },
+        }
+    }
+}
+
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        Self::new(Difficulty::default())
+    }
+}
+
+/// The difficulty picked on the main menu, passed on to the game's speed and border.
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DifficultySettings>()
+            .add_systems(Update, apply_difficulty_system.run_if(resource_changed::<DifficultySettings>));
+    }
+}
+
+fn apply_difficulty_system(
+    difficulty: Res<DifficultySettings>,
+    mut speed: ResMut<GameSpeed>,
+    mut border_mode: ResMut<BorderMode>,
+) {
+    speed.base_interval = difficulty.move_interval;
+    speed.step = difficulty.speed_step;
+    *border_mode = difficulty.border_mode;
+}
@@ -0,0 +1,602 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{random_free_cell, GridPosition, CELL_SIZE, DIRECTIONS, GRID_HEIGHT, GRID_WIDTH};
+use crate::rng::GameRng;
+use crate::score::{Score, ScoreEvent};
+use crate::snake::{border_system, step_due, CollisionEvent, Player, Snake, SnakeSegment};
+use crate::theme::{Palette, Theme};
+use crate::{GameState, StepSet};
+
+const FOOD_TABLE: &str = "food_table.ron";
+const FOOD_SIZE: Vec2 = Vec2::new(10., 10.);
+pub const FOOD_START_POSITION: IVec2 = IVec2::new(45, 35);
+const FOOD_TIMER_BAR_SIZE: Vec2 = Vec2::new(20., 3.);
+
+const MOUSE_CHANCE: f64 = 0.2;
+const MOUSE_POINTS: u32 = 3;
+/// Snake steps between each step a mouse takes.
+const MOUSE_STEP_INTERVAL: u32 = 3;
+const MOUSE_COLOR: Color = Color::srgb(0.55, 0.45, 0.4);
+
+const POWER_UP_CHANCE: f64 = 0.15;
+const GHOST_DURATION: f32 = 5.;
+const SLOW_DURATION: f32 = 4.;
+/// How much longer each step takes while slow time is active.
+const SLOW_INTERVAL_SCALE: f32 = 2.;
+const SHRINK_SEGMENTS: usize = 2;
+const GHOST_COLOR: Color = Color::srgb(0.85, 0.85, 0.95);
+const SHRINK_COLOR: Color = Color::srgb(0.6, 0.3, 0.7);
+const SLOW_COLOR: Color = Color::srgb(0.3, 0.7, 0.8);
+
+/// Choices the settings screen cycles through for [`FoodCount`].
+pub const FOOD_COUNT_OPTIONS: [usize; 4] = [1, 2, 3, 5];
+
+/// A kind of food the spawner can roll, as laid out in [`FOOD_TABLE`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct FoodKind {
+    pub name: String,
+    /// How likely it is to be rolled, against the other kinds' weights.
+    pub weight: u32,
+    /// Points for eating it, multiplied by the combo for food that grows the snake. Negative
+    /// points are taken off the score.
+    pub points: i32,
+    pub color: FoodColor,
+    /// Seconds it lies on the board before it goes off. Lasting food keeps the board stocked, while
+    /// timed food only turns up as an extra after a meal.
+    #[serde(default)]
+    pub lifetime: Option<f32>,
+    pub effect: FoodEffect,
+}
+
+impl FoodKind {
+    /// Whether a snake is better off steering around it.
+    pub fn is_harmful(&self) -> bool {
+        self.points < 0 || matches!(self.effect, FoodEffect::Shrink(_))
+    }
+}
+
+/// The theme color a [`FoodKind`] is drawn in, or a fixed one.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum FoodColor {
+    Food,
+    Bonus,
+    Poison,
+    Srgb(f32, f32, f32),
+}
+
+impl FoodColor {
+    fn color(self, palette: &Palette) -> Color {
+        match self {
+            FoodColor::Food => palette.food,
+            FoodColor::Bonus => palette.bonus_food,
+            FoodColor::Poison => palette.poison_food,
+            FoodColor::Srgb(red, green, blue) => Color::srgb(red, green, blue),
+        }
+    }
+}
+
+/// What eating a [`FoodKind`] does besides scoring its points.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum FoodEffect {
+    /// Grows the snake a segment and counts as a meal: it builds the combo, speeds the game up and
+    /// rolls for extra food.
+    Grow,
+    /// Just the points, with the fanfare of a treat.
+    Bonus,
+    /// Drops this many segments off the tail; a snake too short to lose them dies instead.
+    Shrink(usize),
+}
+
+/// Every kind of food there is, read from [`FOOD_TABLE`] so new kinds need no code.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct FoodTable {
+    pub foods: Vec<FoodKind>,
+}
+
+impl FoodTable {
+    /// Rolls one of the kinds `filter` lets through, by weight; `None` when none of them can come up.
+    fn roll(&self, rng: &mut impl Rng, filter: impl Fn(&FoodKind) -> bool) -> Option<&FoodKind> {
+        let kinds: Vec<&FoodKind> = self.foods.iter().filter(|kind| filter(kind)).collect();
+        kinds.choose_weighted(rng, |kind| kind.weight).ok().copied()
+    }
+
+    /// Rolls a kind of lasting food, to stock the board with.
+    pub fn roll_lasting(&self, rng: &mut impl Rng) -> Option<&FoodKind> {
+        self.roll(rng, |kind| kind.lifetime.is_none())
+    }
+}
+
+#[derive(Default)]
+struct FoodTableLoader;
+
+impl AssetLoader for FoodTableLoader {
+    type Asset = FoodTable;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Resource)]
+struct FoodTableHandle(Handle<FoodTable>);
+
+/// The [`FoodTable`], once it has loaded.
+#[derive(SystemParam)]
+pub struct LoadedFoodTable<'w> {
+    handle: Res<'w, FoodTableHandle>,
+    tables: Res<'w, Assets<FoodTable>>,
+}
+
+impl LoadedFoodTable<'_> {
+    pub fn get(&self) -> Option<&FoodTable> {
+        self.tables.get(&self.handle.0)
+    }
+}
+
+/// A piece of food of some kind from the [`FoodTable`].
+#[derive(Component)]
+pub struct Food(pub FoodKind);
+
+/// Timed food, which goes off when its timer runs out.
+#[derive(Component)]
+pub struct FoodLifetime {
+    pub timer: Timer,
+}
+
+/// Child of timed food that shrinks as its time runs out.
+#[derive(Component)]
+struct FoodTimerBar;
+
+/// Food worth [`MOUSE_POINTS`] that runs from the nearest snake head, a cell every
+/// [`MOUSE_STEP_INTERVAL`] steps.
+#[derive(Component, Default)]
+pub struct Mouse {
+    steps: u32,
+}
+
+/// Whether mice turn up on the board, picked in the settings.
+#[derive(Resource, Default)]
+pub struct Mice(pub bool);
+
+/// A pickup that gives the snake eating it a temporary effect, tracked in [`ActiveEffects`].
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PowerUp {
+    /// The snake can pass through its own body for [`GHOST_DURATION`] seconds.
+    Ghost,
+    /// Drops [`SHRINK_SEGMENTS`] segments off the tail.
+    Shrink,
+    /// Halves the tick rate for [`SLOW_DURATION`] seconds.
+    Slow,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 3] = [PowerUp::Ghost, PowerUp::Shrink, PowerUp::Slow];
+
+    pub fn color(self) -> Color {
+        match self {
+            PowerUp::Ghost => GHOST_COLOR,
+            PowerUp::Shrink => SHRINK_COLOR,
+            PowerUp::Slow => SLOW_COLOR,
+        }
+    }
+}
+
+/// Power-up effects that are still running, each with the time it has left.
+#[derive(Resource, Default)]
+pub struct ActiveEffects {
+    /// Snakes that can currently pass through themselves.
+    pub ghost: HashMap<Player, Timer>,
+    pub slow: Option<Timer>,
+}
+
+impl ActiveEffects {
+    pub fn is_ghost(&self, player: Player) -> bool {
+        self.ghost.contains_key(&player)
+    }
+
+    pub fn interval_scale(&self) -> f32 {
+        if self.slow.is_some() { SLOW_INTERVAL_SCALE } else { 1. }
+    }
+}
+
+/// A snake's head reached food that grows it.
+#[derive(Event)]
+pub struct EatEvent {
+    pub snake: Entity,
+    pub position: IVec2,
+    /// What the food is worth before the combo.
+    pub points: i32,
+}
+
+/// A snake ate a treat: bonus food or a mouse.
+#[derive(Event)]
+pub struct BonusEatenEvent {
+    pub position: IVec2,
+}
+
+/// A snake picked up a power-up.
+#[derive(Event)]
+pub struct PowerUpEvent;
+
+/// How many pieces of regular food are kept on the board at once.
+#[derive(Resource)]
+pub struct FoodCount(pub usize);
+
+impl Default for FoodCount {
+    fn default() -> Self {
+        Self(FOOD_COUNT_OPTIONS[0])
+    }
+}
+
+/// Food from the [`FoodTable`], mice and power-ups: eating them, replacing them and timing them out.
+pub struct FoodPlugin;
+
+impl Plugin for FoodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<FoodTable>()
+            .init_asset_loader::<FoodTableLoader>()
+            .add_systems(Startup, load_food_table)
+            .init_resource::<FoodCount>()
+            .init_resource::<Mice>()
+            .init_resource::<ActiveEffects>()
+            .add_event::<EatEvent>()
+            .add_event::<BonusEatenEvent>()
+            .add_event::<PowerUpEvent>()
+            .add_systems(
+                FixedUpdate,
+                (food_collision_system, power_up_collision_system, mouse_collision_system).in_set(StepSet::Collide),
+            )
+            // Steps with the snakes, once they have moved.
+            .add_systems(
+                FixedUpdate,
+                mouse_movement_system.run_if(step_due).after(border_system).in_set(StepSet::Move),
+            )
+            .add_systems(FixedUpdate, (food_spawn_system, food_top_up_system).chain().in_set(StepSet::Grow))
+            .add_systems(
+                Update,
+                (food_lifetime_system, active_effects_system).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn load_food_table(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(FoodTableHandle(asset_server.load(FOOD_TABLE)));
+}
+
+/// Scores and applies whatever food a head reached. Food that grows the snake is scored with the
+/// combo by whoever listens for the [`EatEvent`].
+#[allow(clippy::too_many_arguments)]
+fn food_collision_system(
+    mut commands: Commands,
+    mut snake_query: Query<(Entity, &mut Snake, &mut Score)>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+    food_query: Query<(Entity, &GridPosition, &Food)>,
+    mut eat_events: EventWriter<EatEvent>,
+    mut bonus_events: EventWriter<BonusEatenEvent>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    for (food_entity, food_position, Food(kind)) in food_query.iter() {
+        let eater = snake_query
+            .iter_mut()
+            .find(|(_, snake, _)| segment_query.get(snake.head()).is_ok_and(|head| head == food_position));
+        let Some((snake_entity, mut snake, mut score)) = eater else {
+            continue;
+        };
+
+        commands.entity(food_entity).despawn_recursive();
+        let position = food_position.0;
+
+        match kind.effect {
+            FoodEffect::Grow => {
+                eat_events.send(EatEvent { snake: snake_entity, position, points: kind.points });
+            }
+            FoodEffect::Bonus => {
+                score.0 = score.0.saturating_add_signed(kind.points);
+                score_events.send(ScoreEvent { snake: snake_entity, position, points: kind.points, combo: 1 });
+                bonus_events.send(BonusEatenEvent { position });
+            }
+            FoodEffect::Shrink(segments) => {
+                score.0 = score.0.saturating_add_signed(kind.points);
+                score_events.send(ScoreEvent { snake: snake_entity, position, points: kind.points, combo: 1 });
+                if snake.segments.len() <= segments {
+                    collision_events.send(CollisionEvent { snake: snake_entity });
+                } else {
+                    snake.drop_tail(&mut commands, segments);
+                }
+            }
+        }
+    }
+}
+
+/// Sometimes follows a meal up with timed food, a mouse or a power-up. The timed food is rolled
+/// against the whole [`FoodTable`], so rolling lasting food adds nothing extra, and there is only
+/// ever one piece of each timed kind at a time.
+#[allow(clippy::type_complexity)]
+fn food_spawn_system(
+    mut commands: Commands,
+    mut eat_events: EventReader<EatEvent>,
+    theme: Res<Theme>,
+    mice: Res<Mice>,
+    food_table: LoadedFoodTable,
+    mut game_rng: ResMut<GameRng>,
+    // Everything on the board, flagging the kinds there is only ever one of.
+    blocked_query: Query<(&GridPosition, Option<&Food>, Has<PowerUp>, Has<Mouse>)>,
+) {
+    let rng = &mut game_rng.rng;
+    for _ in eat_events.read() {
+        let mut occupied: HashSet<IVec2> = blocked_query.iter().map(|(position, ..)| position.0).collect();
+
+        let extra = food_table
+            .get()
+            .and_then(|table| table.roll(rng, |_| true))
+            .filter(|kind| kind.lifetime.is_some())
+            .filter(|kind| !blocked_query.iter().any(|(_, food, ..)| food.is_some_and(|Food(other)| other == *kind)));
+        if let Some(kind) = extra {
+            occupied.extend(spawn_food(&mut commands, theme.palette(), kind, &occupied, rng));
+        }
+
+        let has_mouse = blocked_query.iter().any(|(.., is_mouse)| is_mouse);
+        if mice.0 && !has_mouse && rng.random_bool(MOUSE_CHANCE) {
+            occupied.extend(spawn_mouse(&mut commands, &occupied, rng));
+        }
+
+        let has_power_up = blocked_query.iter().any(|(.., is_power_up, _)| is_power_up);
+        if !has_power_up && rng.random_bool(POWER_UP_CHANCE) {
+            spawn_power_up(&mut commands, &occupied, rng);
+        }
+    }
+}
+
+/// Keeps [`FoodCount`] pieces of lasting food on the board, replacing any that were eaten or lost.
+fn food_top_up_system(
+    mut commands: Commands,
+    food_count: Res<FoodCount>,
+    theme: Res<Theme>,
+    food_table: LoadedFoodTable,
+    mut game_rng: ResMut<GameRng>,
+    board_query: Query<(&GridPosition, Has<Food>, Has<FoodLifetime>)>,
+) {
+    let Some(table) = food_table.get() else {
+        return;
+    };
+
+    let present = board_query.iter().filter(|&(_, is_food, timed)| is_food && !timed).count();
+    let mut occupied: HashSet<IVec2> = board_query.iter().map(|(position, ..)| position.0).collect();
+
+    for _ in present..food_count.0 {
+        let Some(kind) = table.roll_lasting(&mut game_rng.rng) else {
+            break;
+        };
+        let Some(position) = spawn_food(&mut commands, theme.palette(), kind, &occupied, &mut game_rng.rng) else {
+            break;
+        };
+        occupied.insert(position);
+    }
+}
+
+fn power_up_collision_system(
+    mut commands: Commands,
+    mut effects: ResMut<ActiveEffects>,
+    mut snake_query: Query<(&Player, &mut Snake)>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+    power_up_query: Query<(Entity, &GridPosition, &PowerUp)>,
+    mut power_up_events: EventWriter<PowerUpEvent>,
+) {
+    for (power_up_entity, power_up_position, &power_up) in power_up_query.iter() {
+        let eater = snake_query
+            .iter_mut()
+            .find(|(_, snake)| segment_query.get(snake.head()).is_ok_and(|head| head == power_up_position));
+        let Some((&player, mut snake)) = eater else {
+            continue;
+        };
+
+        commands.entity(power_up_entity).despawn();
+        power_up_events.send(PowerUpEvent);
+
+        match power_up {
+            PowerUp::Ghost => {
+                effects.ghost.insert(player, Timer::from_seconds(GHOST_DURATION, TimerMode::Once));
+            }
+            PowerUp::Shrink => snake.drop_tail(&mut commands, SHRINK_SEGMENTS),
+            PowerUp::Slow => effects.slow = Some(Timer::from_seconds(SLOW_DURATION, TimerMode::Once)),
+        }
+    }
+}
+
+fn mouse_collision_system(
+    mut commands: Commands,
+    mut snake_query: Query<(Entity, &Snake, &mut Score)>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+    mouse_query: Query<(Entity, &GridPosition), With<Mouse>>,
+    mut bonus_events: EventWriter<BonusEatenEvent>,
+    mut score_events: EventWriter<ScoreEvent>,
+) {
+    for (mouse_entity, mouse_position) in mouse_query.iter() {
+        let eater = snake_query
+            .iter_mut()
+            .find(|(_, snake, _)| segment_query.get(snake.head()).is_ok_and(|head| head == mouse_position));
+        if let Some((snake_entity, _, mut score)) = eater {
+            commands.entity(mouse_entity).despawn();
+            score.0 += MOUSE_POINTS;
+            let position = mouse_position.0;
+            score_events.send(ScoreEvent { snake: snake_entity, position, points: MOUSE_POINTS as i32, combo: 1 });
+            bonus_events.send(BonusEatenEvent { position });
+        }
+    }
+}
+
+/// Moves each mouse whose turn it is to the free neighboring cell furthest from the nearest snake
+/// head, staying put when none of them gets it further away.
+fn mouse_movement_system(
+    snake_query: Query<&Snake>,
+    mut mouse_query: Query<(&mut Mouse, &mut GridPosition)>,
+    // Everything else on the board.
+    blocked_query: Query<&GridPosition, Without<Mouse>>,
+) {
+    let grid_size = IVec2::new(GRID_WIDTH, GRID_HEIGHT);
+    let blocked: HashSet<IVec2> = blocked_query.iter().map(|position| position.0).collect();
+    let heads: Vec<IVec2> = blocked_query.iter_many(snake_query.iter().map(Snake::head)).map(|head| head.0).collect();
+    let distance = |cell: IVec2| heads.iter().map(|&head| (cell - head).abs().element_sum()).min().unwrap_or(0);
+
+    for (mut mouse, mut position) in mouse_query.iter_mut() {
+        // Caught; it is eaten in the collision phase.
+        if heads.contains(&position.0) {
+            continue;
+        }
+
+        mouse.steps += 1;
+        if mouse.steps < MOUSE_STEP_INTERVAL {
+            continue;
+        }
+        mouse.steps = 0;
+
+        let escape = DIRECTIONS
+            .into_iter()
+            .map(|direction| position.0 + direction)
+            .filter(|cell| cell.cmpge(IVec2::ZERO).all() && cell.cmplt(grid_size).all() && !blocked.contains(cell))
+            .max_by_key(|&cell| distance(cell))
+            .filter(|&cell| distance(cell) > distance(position.0));
+        if let Some(cell) = escape {
+            position.0 = cell;
+        }
+    }
+}
+
+/// Runs down the power-up timers and drops each effect once its time is up.
+fn active_effects_system(time: Res<Time>, mut effects: ResMut<ActiveEffects>) {
+    effects.ghost.retain(|_, timer| !timer.tick(time.delta()).finished());
+    if effects.slow.as_mut().is_some_and(|timer| timer.tick(time.delta()).finished()) {
+        effects.slow = None;
+    }
+}
+
+fn food_lifetime_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut food_query: Query<(Entity, &mut FoodLifetime, &Children)>,
+    mut bar_query: Query<&mut Sprite, With<FoodTimerBar>>,
+) {
+    for (entity, mut lifetime, children) in food_query.iter_mut() {
+        if lifetime.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let mut bars = bar_query.iter_many_mut(children);
+        while let Some(mut bar) = bars.fetch_next() {
+            bar.custom_size = Some(FOOD_TIMER_BAR_SIZE * Vec2::new(lifetime.timer.fraction_remaining(), 1.));
+        }
+    }
+}
+
+/// Places food on a random cell that nothing else occupies and returns it; a full board gets no food.
+fn spawn_food(
+    commands: &mut Commands,
+    palette: &Palette,
+    kind: &FoodKind,
+    occupied: &HashSet<IVec2>,
+    rng: &mut impl Rng,
+) -> Option<IVec2> {
+    let random_pos = random_free_cell(occupied, rng)?;
+    place_food(commands, palette, kind, random_pos);
+    Some(random_pos)
+}
+
+fn spawn_mouse(commands: &mut Commands, occupied: &HashSet<IVec2>, rng: &mut impl Rng) -> Option<IVec2> {
+    let random_pos = random_free_cell(occupied, rng)?;
+    place_mouse(commands, random_pos);
+    Some(random_pos)
+}
+
+/// Places a random power-up on a free cell.
+fn spawn_power_up(commands: &mut Commands, occupied: &HashSet<IVec2>, rng: &mut impl Rng) {
+    let Some(random_pos) = random_free_cell(occupied, rng) else {
+        return;
+    };
+    let Some(&power_up) = PowerUp::ALL.choose(rng) else {
+        return;
+    };
+
+    place_power_up(commands, random_pos, power_up);
+}
+
+/// Puts a piece of `kind` on the board; timed kinds come with a bar showing the time they have left.
+pub fn place_food(commands: &mut Commands, palette: &Palette, kind: &FoodKind, position: IVec2) -> Entity {
+    let color = kind.color.color(palette);
+    let mut food = commands.spawn((
+        Sprite {
+            color,
+            custom_size: Some(FOOD_SIZE),
+            ..default()
+        },
+        Transform::default(),
+        GridPosition(position),
+        Food(kind.clone()),
+    ));
+
+    if let Some(lifetime) = kind.lifetime {
+        food.insert(FoodLifetime { timer: Timer::from_seconds(lifetime, TimerMode::Once) })
+            .with_child((
+                Sprite {
+                    color,
+                    custom_size: Some(FOOD_TIMER_BAR_SIZE),
+                    ..default()
+                },
+                Transform::from_xyz(0., CELL_SIZE, 0.),
+                FoodTimerBar,
+            ));
+    }
+
+    food.id()
+}
+
+pub fn place_power_up(commands: &mut Commands, position: IVec2, power_up: PowerUp) -> Entity {
+    commands
+        .spawn((
+            Sprite {
+                color: power_up.color(),
+                custom_size: Some(FOOD_SIZE),
+                ..default()
+            },
+            Transform::default(),
+            GridPosition(position),
+            power_up,
+        ))
+        .id()
+}
+
+pub fn place_mouse(commands: &mut Commands, position: IVec2) -> Entity {
+    commands
+        .spawn((
+            Sprite {
+                color: MOUSE_COLOR,
+                custom_size: Some(FOOD_SIZE),
+                ..default()
+            },
+            Transform::default(),
+            GridPosition(position),
+            Mouse::default(),
+        ))
+        .id()
+}
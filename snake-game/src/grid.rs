@@ -0,0 +1,262 @@
+use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::utils::HashSet;
+use bevy::window::PrimaryWindow;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::high_scores::NameEntry;
+use crate::snake::{segment_interpolation_system, Snake, SnakeSegment};
+use crate::theme::Theme;
+
+pub const CELL_SIZE: f32 = 10.;
+pub const GRID_WIDTH: i32 = 80;
+pub const GRID_HEIGHT: i32 = 60;
+const BOARD_SIZE: Vec2 = Vec2::new(GRID_WIDTH as f32 * CELL_SIZE, GRID_HEIGHT as f32 * CELL_SIZE);
+/// The frame drawn around the board, outside the playable cells.
+const BORDER_THICKNESS: f32 = CELL_SIZE;
+/// The board with its frame, in world units.
+const ARENA_WIDTH: f32 = BOARD_SIZE.x + 2. * BORDER_THICKNESS;
+const ARENA_HEIGHT: f32 = BOARD_SIZE.y + 2. * BORDER_THICKNESS;
+/// Boards bigger than this are scrolled through by the camera instead of growing the window.
+const MAX_WINDOW_WIDTH: f32 = 1200.;
+const MAX_WINDOW_HEIGHT: f32 = 800.;
+pub const WINDOW_WIDTH: f32 = if ARENA_WIDTH < MAX_WINDOW_WIDTH { ARENA_WIDTH } else { MAX_WINDOW_WIDTH };
+pub const WINDOW_HEIGHT: f32 = if ARENA_HEIGHT < MAX_WINDOW_HEIGHT { ARENA_HEIGHT } else { MAX_WINDOW_HEIGHT };
+/// How quickly the camera catches up with the snakes; higher is snappier.
+const CAMERA_FOLLOW_RATE: f32 = 5.;
+/// Shows the whole board at once while the camera is following.
+const ZOOM_KEY: KeyCode = KeyCode::KeyZ;
+/// The minimap in the bottom right corner, shown only when the board doesn't fit the window.
+const MINIMAP_WIDTH: f32 = 160.;
+const MINIMAP_HEIGHT: f32 = MINIMAP_WIDTH * ARENA_HEIGHT / ARENA_WIDTH;
+const MINIMAP_MARGIN: f32 = 12.;
+const SOLID_BORDER_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const WRAP_BORDER_COLOR: Color = Color::srgb(0.6, 0.75, 0.85);
+
+pub const DIRECTIONS: [IVec2; 4] = [IVec2::Y, IVec2::NEG_Y, IVec2::NEG_X, IVec2::X];
+
+/// One of the darker squares of the board's checkerboard.
+#[derive(Component)]
+pub struct BoardCell;
+
+/// One side of the frame around the board, colored by the [`BorderMode`].
+#[derive(Component)]
+struct BoardBorder;
+
+/// Cell on the board, counted from the bottom-left corner.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub struct GridPosition(pub IVec2);
+
+/// The cell a segment stepped from; its sprite slides from there to [`GridPosition`] over the step.
+#[derive(Component)]
+pub struct PreviousGridPosition(pub IVec2);
+
+/// The second camera drawing the whole board into the [`MINIMAP_WIDTH`] corner of the window.
+#[derive(Component)]
+struct MinimapCamera;
+
+/// Whether the camera is pulled back to fit the whole board rather than following the snakes.
+#[derive(Resource, Default)]
+struct ZoomedOut(bool);
+
+/// What happens when the head leaves the board.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum BorderMode {
+    /// Hitting the edge ends the game.
+    #[default]
+    Solid,
+    /// The snake reappears at the opposite edge.
+    Wrap,
+}
+
+/// The board itself: grid coordinates, the checkerboard and its frame, and the cameras looking at it.
+pub struct GridPlugin;
+
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ZoomedOut>()
+            .add_systems(Startup, (spawn_cameras, spawn_board))
+            .add_systems(
+                Update,
+                (
+                    grid_transform_system,
+                    border_color_system.run_if(resource_changed::<BorderMode>),
+                    zoom_system.run_if(not(resource_exists::<NameEntry>)),
+                    camera_follow_system.after(segment_interpolation_system),
+                    minimap_viewport_system,
+                ),
+            );
+    }
+}
+
+fn spawn_cameras(mut commands: Commands) {
+    // The minimap camera is drawn last, so the UI has to be pinned to this one.
+    commands.spawn((
+        Camera2d,
+        // Shows as much of the board as the window was made for, however big the browser makes the canvas.
+        OrthographicProjection {
+            scaling_mode: ScalingMode::AutoMin { min_width: WINDOW_WIDTH, min_height: WINDOW_HEIGHT },
+            ..OrthographicProjection::default_2d()
+        },
+        IsDefaultUiCamera,
+    ));
+    if ARENA_WIDTH > WINDOW_WIDTH || ARENA_HEIGHT > WINDOW_HEIGHT {
+        commands.spawn((
+            Camera2d,
+            Camera { order: 1, ..default() },
+            OrthographicProjection { scale: ARENA_WIDTH / MINIMAP_WIDTH, ..OrthographicProjection::default_2d() },
+            MinimapCamera,
+        ));
+    }
+}
+
+/// Draws the checkerboard of cells, over the light clear color, and the frame around it.
+fn spawn_board(mut commands: Commands, theme: Res<Theme>) {
+    for x in 0..GRID_WIDTH {
+        for y in (0..GRID_HEIGHT).filter(|y| (x + y) % 2 == 1) {
+            commands.spawn((
+                Sprite {
+                    color: theme.palette().board_dark,
+                    custom_size: Some(Vec2::splat(CELL_SIZE)),
+                    ..default()
+                },
+                Transform::from_translation(grid_to_world(IVec2::new(x, y)).extend(-1.)),
+                BoardCell,
+            ));
+        }
+    }
+
+    let horizontal = Vec2::new(ARENA_WIDTH, BORDER_THICKNESS);
+    let vertical = Vec2::new(BORDER_THICKNESS, ARENA_HEIGHT);
+    let offset = (BOARD_SIZE + BORDER_THICKNESS) / 2.;
+    let sides = [
+        (Vec2::new(0., offset.y), horizontal),
+        (Vec2::new(0., -offset.y), horizontal),
+        (Vec2::new(-offset.x, 0.), vertical),
+        (Vec2::new(offset.x, 0.), vertical),
+    ];
+    for (position, size) in sides {
+        commands.spawn((
+            Sprite {
+                color: SOLID_BORDER_COLOR,
+                custom_size: Some(size),
+                ..default()
+            },
+            Transform::from_translation(position.extend(-1.)),
+            BoardBorder,
+        ));
+    }
+}
+
+/// Shows whether the edge of the board kills or wraps around.
+fn border_color_system(border_mode: Res<BorderMode>, mut query: Query<&mut Sprite, With<BoardBorder>>) {
+    let color = match *border_mode {
+        BorderMode::Solid => SOLID_BORDER_COLOR,
+        BorderMode::Wrap => WRAP_BORDER_COLOR,
+    };
+    for mut sprite in query.iter_mut() {
+        sprite.color = color;
+    }
+}
+
+/// Direction from a cell to a neighboring one, seeing through the wrap-around at the board's edges.
+/// `None` when the cells aren't neighbors, as across a portal.
+pub fn step_between(from: IVec2, to: IVec2) -> Option<IVec2> {
+    let grid_size = IVec2::new(GRID_WIDTH, GRID_HEIGHT);
+    DIRECTIONS
+        .into_iter()
+        .find(|&direction| (from + direction).rem_euclid(grid_size) == to)
+}
+
+pub fn random_free_cell(occupied: &HashSet<IVec2>, rng: &mut impl Rng) -> Option<IVec2> {
+    let free_cells: Vec<IVec2> = (0..GRID_WIDTH)
+        .flat_map(|x| (0..GRID_HEIGHT).map(move |y| IVec2::new(x, y)))
+        .filter(|cell| !occupied.contains(cell))
+        .collect();
+
+    free_cells.choose(rng).copied()
+}
+
+fn zoom_system(keys: Res<ButtonInput<KeyCode>>, mut zoomed_out: ResMut<ZoomedOut>) {
+    if keys.just_pressed(ZOOM_KEY) {
+        zoomed_out.0 = !zoomed_out.0;
+    }
+}
+
+/// Eases the camera towards the middle of the snakes' heads, or to the whole board when
+/// [`ZoomedOut`], never showing past the frame. On a board that fits the window it stays centered.
+#[allow(clippy::type_complexity)]
+fn camera_follow_system(
+    time: Res<Time>,
+    zoomed_out: Res<ZoomedOut>,
+    snake_query: Query<&Snake>,
+    segment_query: Query<&Transform, (With<SnakeSegment>, Without<Camera2d>)>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), (With<Camera2d>, Without<MinimapCamera>)>,
+) {
+    let Ok((mut transform, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    let window_size = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+    let arena_size = Vec2::new(ARENA_WIDTH, ARENA_HEIGHT);
+    let target_scale = if zoomed_out.0 { (arena_size / window_size).max_element().max(1.) } else { 1. };
+
+    let heads: Vec<Vec2> = snake_query
+        .iter()
+        .filter_map(|snake| segment_query.get(snake.head()).ok())
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    let target = if heads.is_empty() {
+        Vec2::ZERO
+    } else {
+        heads.iter().sum::<Vec2>() / heads.len() as f32
+    };
+
+    let smoothing = 1. - (-CAMERA_FOLLOW_RATE * time.delta_secs()).exp();
+    projection.scale += (target_scale - projection.scale) * smoothing;
+
+    let slack = ((arena_size - window_size * projection.scale) / 2.).max(Vec2::ZERO);
+    let position = transform.translation.truncate().lerp(target, smoothing).clamp(-slack, slack);
+    transform.translation = position.extend(transform.translation.z);
+}
+
+/// Keeps the minimap's viewport in the corner in physical pixels, whatever the window's scale factor.
+fn minimap_viewport_system(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<&mut Camera, With<MinimapCamera>>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+
+    let scale_factor = window.scale_factor();
+    let size = (Vec2::new(MINIMAP_WIDTH, MINIMAP_HEIGHT) * scale_factor).as_uvec2();
+    let margin = (Vec2::splat(MINIMAP_MARGIN) * scale_factor).as_uvec2();
+    let position = window.physical_size().saturating_sub(size + margin);
+
+    for mut camera in camera_query.iter_mut() {
+        let in_place = camera
+            .viewport
+            .as_ref()
+            .is_some_and(|viewport| viewport.physical_position == position && viewport.physical_size == size);
+        if !in_place {
+            camera.viewport = Some(Viewport {
+                physical_position: position,
+                physical_size: size,
+                ..default()
+            });
+        }
+    }
+}
+
+pub fn grid_to_world(position: IVec2) -> Vec2 {
+    (position.as_vec2() + 0.5) * CELL_SIZE - BOARD_SIZE / 2.
+}
+
+pub fn grid_transform_system(mut query: Query<(&GridPosition, &mut Transform), Changed<GridPosition>>) {
+    for (position, mut transform) in query.iter_mut() {
+        transform.translation = grid_to_world(position.0).extend(0.);
+    }
+}
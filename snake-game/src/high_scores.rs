@@ -0,0 +1,137 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::score::Score;
+use crate::snake::Snake;
+use crate::timed::MatchTimer;
+use crate::{storage, GameMode, GameState};
+
+const HIGH_SCORE_KEY: &str = "snake-game.scores";
+const HIGH_SCORE_COUNT: usize = 10;
+const NAME_MAX_LENGTH: usize = 12;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub length: usize,
+}
+
+/// The best single-player runs, highest first, kept in [`HIGH_SCORE_KEY`].
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct HighScores(pub Vec<HighScoreEntry>);
+
+impl HighScores {
+    fn load() -> Self {
+        storage::load(HIGH_SCORE_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save(HIGH_SCORE_KEY, self);
+    }
+
+    fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.0.len() < HIGH_SCORE_COUNT || self.0.iter().any(|entry| score > entry.score))
+    }
+
+    /// Adds the entry below any earlier ones with the same score, dropping whatever falls off the table.
+    fn insert(&mut self, entry: HighScoreEntry) {
+        let index = self.0.partition_point(|existing| existing.score >= entry.score);
+        self.0.insert(index, entry);
+        self.0.truncate(HIGH_SCORE_COUNT);
+    }
+}
+
+/// A run that made the high score table, waiting for the player to type a name.
+#[derive(Resource)]
+pub struct NameEntry(pub HighScoreEntry);
+
+/// The table of best single-player runs, and the name typed in for each new one on the game over
+/// screen. Timed runs are ranked on their own by [`TimedPlugin`](crate::timed::TimedPlugin).
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .add_systems(OnEnter(GameState::GameOver), start_name_entry.run_if(not(resource_exists::<MatchTimer>)))
+            .add_systems(
+                Update,
+                name_entry_system.run_if(resource_exists::<NameEntry>).run_if(in_state(GameState::GameOver)),
+            );
+    }
+}
+
+/// Asks for a name when a single-player run made the high score table.
+fn start_name_entry(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    high_scores: Res<HighScores>,
+    snake_query: Query<(&Snake, &Score)>,
+) {
+    if *game_mode != GameMode::Single {
+        return;
+    }
+
+    let Ok((snake, score)) = snake_query.get_single() else {
+        return;
+    };
+
+    if high_scores.qualifies(score.0) {
+        commands.insert_resource(NameEntry(HighScoreEntry {
+            name: String::new(),
+            score: score.0,
+            length: snake.segments.len(),
+        }));
+    }
+}
+
+fn name_entry_system(
+    mut commands: Commands,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    touches: Res<Touches>,
+    mut name_entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    // Without a keyboard, a tap saves the run under the default name.
+    let mut finished = touches.any_just_pressed();
+
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        let name = &mut name_entry.0.name;
+        match &event.logical_key {
+            Key::Character(text) => {
+                for character in text.chars().filter(|character| character.is_alphanumeric()) {
+                    if name.chars().count() < NAME_MAX_LENGTH {
+                        name.push(character);
+                    }
+                }
+            }
+            Key::Space if !name.is_empty() && name.chars().count() < NAME_MAX_LENGTH => name.push(' '),
+            Key::Backspace => {
+                name.pop();
+            }
+            Key::Enter => {
+                finished = true;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if finished {
+        let mut entry = name_entry.0.clone();
+        entry.name = entry.name.trim().to_string();
+        if entry.name.is_empty() {
+            entry.name = "Player".to_string();
+        }
+
+        high_scores.insert(entry);
+        high_scores.save();
+        commands.remove_resource::<NameEntry>();
+    }
+}
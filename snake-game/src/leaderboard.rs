@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, IoTaskPool, Task};
+use serde::{Deserialize, Serialize};
+
+use crate::score::Score;
+use crate::snake::Snake;
+use crate::ui::{HUD_COLOR, HUD_FONT_SIZE};
+use crate::{arg_value, GameMode, GameState, Ruleset};
+
+const LEADERBOARD_SIZE: usize = 10;
+
+/// Submitted as when no `--name` is given.
+const DEFAULT_PLAYER_NAME: &str = "Player";
+
+/// Where finished runs go, from `--leaderboard <url>` and `--name <name>` on the command line.
+#[derive(Resource)]
+struct LeaderboardConfig {
+    url: String,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LeaderboardEntry {
+    name: String,
+    score: u32,
+    length: usize,
+    /// The [`Ruleset`] the run was played under; each is ranked on its own.
+    mode: String,
+}
+
+type LeaderboardResult = Result<Vec<LeaderboardEntry>, String>;
+
+#[derive(Resource)]
+struct LeaderboardTask(Task<LeaderboardResult>);
+
+#[derive(Component)]
+struct LeaderboardText;
+
+/// Submits finished single-player runs to the leaderboard server and lists the global top scores
+/// for the same rules on the game over screen.
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(url) = arg_value("--leaderboard") {
+            let name = arg_value("--name").unwrap_or_else(|| DEFAULT_PLAYER_NAME.to_string());
+            app.insert_resource(LeaderboardConfig { url, name });
+        }
+
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            submit_score
+                .run_if(resource_exists::<LeaderboardConfig>)
+                .run_if(resource_equals(GameMode::Single)),
+        )
+        .add_systems(Update, poll_leaderboard_system.run_if(resource_exists::<LeaderboardTask>))
+        .add_systems(OnExit(GameState::GameOver), |mut commands: Commands| {
+            commands.remove_resource::<LeaderboardTask>();
+        });
+    }
+}
+
+fn submit_score(
+    mut commands: Commands,
+    config: Res<LeaderboardConfig>,
+    ruleset: Res<Ruleset>,
+    snake_query: Query<(&Snake, &Score)>,
+) {
+    let Ok((snake, score)) = snake_query.get_single() else {
+        return;
+    };
+
+    let url = config.url.clone();
+    let entry = LeaderboardEntry {
+        name: config.name.clone(),
+        score: score.0,
+        length: snake.segments.len(),
+        mode: ruleset.name().to_string(),
+    };
+    let task = IoTaskPool::get().spawn(async move { submit_and_fetch(&url, &entry) });
+    commands.insert_resource(LeaderboardTask(task));
+
+    commands.spawn((
+        Text::new("Loading leaderboard..."),
+        TextFont {
+            font_size: HUD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(HUD_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.),
+            bottom: Val::Px(12.),
+            ..default()
+        },
+        LeaderboardText,
+        StateScoped(GameState::GameOver),
+    ));
+}
+
+fn submit_and_fetch(url: &str, entry: &LeaderboardEntry) -> LeaderboardResult {
+    let scores_url = format!("{}/scores", url.trim_end_matches('/'));
+
+    ureq::post(&scores_url)
+        .send_json(entry)
+        .map_err(|error| error.to_string())?;
+
+    ureq::get(&scores_url)
+        .query("limit", &LEADERBOARD_SIZE.to_string())
+        .query("mode", &entry.mode)
+        .call()
+        .map_err(|error| error.to_string())?
+        .into_json()
+        .map_err(|error| error.to_string())
+}
+
+fn poll_leaderboard_system(
+    mut commands: Commands,
+    mut task: ResMut<LeaderboardTask>,
+    mut text_query: Query<&mut Text, With<LeaderboardText>>,
+) {
+    let Some(result) = block_on(future::poll_once(&mut task.0)) else {
+        return;
+    };
+
+    commands.remove_resource::<LeaderboardTask>();
+
+    let contents = match result {
+        Ok(entries) => std::iter::once("Top scores".to_string())
+            .chain(entries.iter().take(LEADERBOARD_SIZE).enumerate().map(|(rank, entry)| {
+                format!("{:>2}. {:<12} {:>4}  length {}", rank + 1, entry.name, entry.score, entry.length)
+            }))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(error) => {
+            warn!("leaderboard request failed: {error}");
+            "Leaderboard unavailable".to_string()
+        }
+    };
+
+    for mut text in text_query.iter_mut() {
+        text.0 = contents.clone();
+    }
+}
@@ -0,0 +1,627 @@
+use std::ops::RangeInclusive;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use rand::Rng;
+
+use crate::food::{Food, Mouse, PowerUp, FOOD_START_POSITION};
+use crate::grid::{random_free_cell, GridPosition, PreviousGridPosition, DIRECTIONS, GRID_HEIGHT, GRID_WIDTH};
+use crate::rng::GameRng;
+use crate::snake::{border_system, DirectionQueue, Snake, SnakeSegment, SNAKE_START_POSITION, VERSUS_START_POSITIONS};
+use crate::theme::{Palette, Theme};
+use crate::ui::{HUD_COLOR, HUD_FONT_SIZE};
+use crate::{GameState, StepSet};
+
+const OBSTACLE_SIZE: Vec2 = Vec2::new(10., 10.);
+const LEVEL_FILES: [&str; 5] = [
+    "levels/open.level",
+    "levels/box.level",
+    "levels/pillars.level",
+    "levels/corridors.level",
+    "levels/portals.level",
+];
+
+const ARENA_SHRINK_INTERVAL: f32 = 15.;
+/// How long the next ring to close flashes before it turns to wall.
+const ARENA_WARNING_TIME: f32 = 3.;
+/// The shrinking arena stops closing in before it gets smaller than this.
+const ARENA_MIN_SIZE: IVec2 = IVec2::new(20, 14);
+const ARENA_WALL_COLOR: Color = Color::srgb(0.45, 0.2, 0.2);
+const ARENA_WARNING_COLOR: Color = Color::srgb(0.9, 0.4, 0.4);
+
+/// How often a new obstacle drops onto the board mid-game.
+const OBSTACLE_DROP_INTERVAL: f32 = 30.;
+/// How long the cell a new obstacle drops onto blinks first.
+const OBSTACLE_WARNING_TIME: f32 = 2.;
+/// Times per second the warning outline blinks on.
+const OBSTACLE_WARNING_BLINK_RATE: f32 = 4.;
+const OBSTACLE_WARNING_THICKNESS: f32 = 2.;
+/// New obstacles never drop within this many steps of a head.
+const OBSTACLE_HEAD_CLEARANCE: i32 = 5;
+
+/// Straight wall pieces the maze generator tries to place.
+const MAZE_WALL_COUNT: usize = 70;
+const MAZE_WALL_LENGTH: RangeInclusive<i32> = 3..=12;
+/// How far around each start position and the first food the maze leaves open.
+const MAZE_CLEARANCE: i32 = 5;
+
+const PORTAL_SIZE: Vec2 = Vec2::new(10., 10.);
+/// One color per portal pair, reused if a level has more pairs than colors.
+const PORTAL_COLORS: [Color; 3] = [
+    Color::srgb(0.2, 0.6, 0.9),
+    Color::srgb(0.9, 0.5, 0.1),
+    Color::srgb(0.8, 0.2, 0.6),
+];
+
+/// A wall tile from the current level; running into one ends the game.
+#[derive(Component)]
+pub struct Obstacle;
+
+/// An [`Obstacle`] closing off a ring of the shrinking arena, rather than one from the level.
+#[derive(Component)]
+pub struct ArenaWall;
+
+/// A cell of the shrinking arena's next ring, flashing until the ring closes.
+#[derive(Component)]
+pub struct ArenaWarning;
+
+/// An outline blinking around the cell the next dropped [`Obstacle`] lands on. It holds the cell
+/// like anything else on the board, so nothing spawns there in the meantime.
+#[derive(Component)]
+pub struct ObstacleWarning {
+    timer: Timer,
+}
+
+/// Counts down to the next [`Obstacle`] dropped onto the board, so long runs get steadily tighter.
+#[derive(Resource)]
+struct ObstacleDrop(Timer);
+
+impl Default for ObstacleDrop {
+    fn default() -> Self {
+        Self(Timer::from_seconds(OBSTACLE_DROP_INTERVAL, TimerMode::Repeating))
+    }
+}
+
+/// How far the shrinking arena has closed in. Closed rings are walled off with [`Obstacle`]s, so
+/// everything that avoids obstacles avoids them too.
+#[derive(Resource)]
+pub struct Arena {
+    /// Rings closed so far, which is also how far in from the edge the next one lies.
+    pub rings: i32,
+    pub timer: Timer,
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Self {
+            rings: 0,
+            timer: Timer::from_seconds(ARENA_SHRINK_INTERVAL, TimerMode::Repeating),
+        }
+    }
+
+    pub fn can_shrink(&self) -> bool {
+        let size_after = IVec2::new(GRID_WIDTH, GRID_HEIGHT) - 2 * (self.rings + 1);
+        size_after.cmpge(ARENA_MIN_SIZE).all()
+    }
+
+    pub fn warning(&self) -> bool {
+        self.can_shrink() && self.timer.remaining_secs() <= ARENA_WARNING_TIME
+    }
+}
+
+/// One end of a portal pair; a head that steps onto it comes out at `exit`, still heading the
+/// same way.
+#[derive(Component)]
+pub struct Portal {
+    pub exit: IVec2,
+}
+
+#[derive(Component)]
+struct LevelSelectText;
+
+/// A board layout read from `assets/levels/`. The first line names it and the rest draws it one
+/// character per cell from the top row down, with `#` for an obstacle, `.` for open floor and a
+/// lowercase letter for a portal. Each letter has to appear exactly twice, once for either end.
+#[derive(Asset, TypePath)]
+pub struct Level {
+    pub name: String,
+    pub obstacles: Vec<IVec2>,
+    pub portals: Vec<(IVec2, IVec2)>,
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(parse_level(&String::from_utf8(bytes)?)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level"]
+    }
+}
+
+/// Every bundled level, in the order the level select lists them.
+#[derive(Resource)]
+pub struct Levels(pub Vec<Handle<Level>>);
+
+/// The generated level at the end of [`Levels`], rebuilt whenever a new seed is rolled.
+#[derive(Resource)]
+struct RandomLevel(Handle<Level>);
+
+/// Index into [`Levels`] of the level to play.
+#[derive(Resource, Default)]
+pub struct SelectedLevel(pub usize);
+
+/// Level files and the generated maze, picking one, and what it puts on the board: walls, portals
+/// and the shrinking arena.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_resource::<SelectedLevel>()
+            .init_resource::<ObstacleDrop>()
+            .add_systems(Startup, load_levels)
+            .add_systems(OnEnter(GameState::LevelSelect), spawn_level_select_screen)
+            .add_systems(
+                Update,
+                (level_select_input_system, level_select_text_system).run_if(in_state(GameState::LevelSelect)),
+            )
+            // Teleports before anything checks the head's cell, so only the exit counts.
+            .add_systems(FixedUpdate, portal_system.after(border_system).in_set(StepSet::Move))
+            .add_systems(
+                Update,
+                (arena_shrink_system, arena_warning_blink_system)
+                    .run_if(in_state(GameState::Playing).and(resource_exists::<Arena>)),
+            )
+            .add_systems(
+                Update,
+                (obstacle_drop_system, obstacle_warning_system).run_if(in_state(GameState::Playing)),
+            );
+
+        // Every way into a new or continued game.
+        for exited in [GameState::LevelSelect, GameState::GameOver, GameState::Menu] {
+            app.add_systems(OnTransition { exited, entered: GameState::Playing }, |mut drop: ResMut<ObstacleDrop>| {
+                drop.0.reset();
+            });
+        }
+    }
+}
+
+fn load_levels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut level_assets: ResMut<Assets<Level>>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let random_level = level_assets.add(generate_maze(&mut game_rng));
+    let mut levels: Vec<Handle<Level>> = LEVEL_FILES.iter().map(|&path| asset_server.load(path)).collect();
+    levels.push(random_level.clone());
+    commands.insert_resource(Levels(levels));
+    commands.insert_resource(RandomLevel(random_level));
+}
+
+/// Puts a level's walls and portals on the board.
+pub fn spawn_level(commands: &mut Commands, level: &Level, palette: &Palette) {
+    for &position in &level.obstacles {
+        place_obstacle(commands, palette, position);
+    }
+
+    for (&(a, b), color) in level.portals.iter().zip(PORTAL_COLORS.iter().cycle()) {
+        for (position, exit) in [(a, b), (b, a)] {
+            commands.spawn((
+                Sprite {
+                    color: *color,
+                    custom_size: Some(PORTAL_SIZE),
+                    ..default()
+                },
+                Transform::default(),
+                GridPosition(position),
+                Portal { exit },
+            ));
+        }
+    }
+}
+
+fn place_obstacle(commands: &mut Commands, palette: &Palette, position: IVec2) {
+    commands.spawn((
+        Sprite {
+            color: palette.obstacle,
+            custom_size: Some(OBSTACLE_SIZE),
+            ..default()
+        },
+        Transform::default(),
+        GridPosition(position),
+        Obstacle,
+    ));
+}
+
+/// Every [`OBSTACLE_DROP_INTERVAL`] seconds, flags a free cell well away from the heads for an
+/// obstacle to drop onto.
+fn obstacle_drop_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut drop: ResMut<ObstacleDrop>,
+    mut game_rng: ResMut<GameRng>,
+    snake_query: Query<&Snake>,
+    board_query: Query<&GridPosition>,
+) {
+    if !drop.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let heads: Vec<IVec2> = board_query.iter_many(snake_query.iter().map(Snake::head)).map(|head| head.0).collect();
+    let near_head =
+        |cell: IVec2| heads.iter().any(|&head| (cell - head).abs().element_sum() <= OBSTACLE_HEAD_CLEARANCE);
+    let mut occupied: HashSet<IVec2> = board_query.iter().map(|position| position.0).collect();
+    occupied.extend(
+        (0..GRID_WIDTH)
+            .flat_map(|x| (0..GRID_HEIGHT).map(move |y| IVec2::new(x, y)))
+            .filter(|&cell| near_head(cell)),
+    );
+
+    let Some(cell) = random_free_cell(&occupied, &mut game_rng.rng) else {
+        return;
+    };
+
+    let edge = OBSTACLE_SIZE.x;
+    let thickness = OBSTACLE_WARNING_THICKNESS;
+    let edges = [
+        (Vec2::new(edge, thickness), Vec2::new(0., (edge - thickness) / 2.)),
+        (Vec2::new(edge, thickness), Vec2::new(0., (thickness - edge) / 2.)),
+        (Vec2::new(thickness, edge), Vec2::new((edge - thickness) / 2., 0.)),
+        (Vec2::new(thickness, edge), Vec2::new((thickness - edge) / 2., 0.)),
+    ];
+
+    commands
+        .spawn((
+            Transform::default(),
+            Visibility::default(),
+            GridPosition(cell),
+            ObstacleWarning { timer: Timer::from_seconds(OBSTACLE_WARNING_TIME, TimerMode::Once) },
+        ))
+        .with_children(|warning| {
+            for (size, offset) in edges {
+                warning.spawn((
+                    Sprite {
+                        color: ARENA_WARNING_COLOR,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    Transform::from_translation(offset.extend(0.)),
+                ));
+            }
+        });
+}
+
+/// Blinks each warning outline, then turns it into an obstacle once its time is up. A snake
+/// passing over the cell holds the obstacle off until it has gone.
+fn obstacle_warning_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    theme: Res<Theme>,
+    mut warning_query: Query<(Entity, &GridPosition, &mut ObstacleWarning, &mut Visibility)>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+) {
+    for (entity, position, mut warning, mut visibility) in warning_query.iter_mut() {
+        if !warning.timer.tick(time.delta()).finished() {
+            let lit = (warning.timer.elapsed_secs() * OBSTACLE_WARNING_BLINK_RATE).fract() < 0.5;
+            *visibility = if lit { Visibility::Inherited } else { Visibility::Hidden };
+            continue;
+        }
+
+        if segment_query.iter().any(|segment| segment == position) {
+            *visibility = Visibility::Inherited;
+            continue;
+        }
+
+        commands.entity(entity).despawn_recursive();
+        place_obstacle(&mut commands, theme.palette(), position.0);
+    }
+}
+
+/// Moves a head that stepped onto a portal to the other end of the pair. The body follows through
+/// on the next steps, since each segment takes the cell the one ahead of it left.
+fn portal_system(
+    snake_query: Query<(&Snake, &DirectionQueue)>,
+    mut segment_query: Query<(&mut GridPosition, &mut PreviousGridPosition), With<SnakeSegment>>,
+    portal_query: Query<(&GridPosition, &Portal), Without<SnakeSegment>>,
+) {
+    let grid_size = IVec2::new(GRID_WIDTH, GRID_HEIGHT);
+
+    for (snake, directions) in snake_query.iter() {
+        let Ok((mut head_position, mut previous)) = segment_query.get_mut(snake.head()) else {
+            continue;
+        };
+
+        if let Some((_, portal)) = portal_query.iter().find(|(position, _)| **position == *head_position) {
+            head_position.0 = portal.exit;
+            // Slide out of the exit rather than across the board.
+            previous.0 = (portal.exit - directions.heading).rem_euclid(grid_size);
+        }
+    }
+}
+
+/// Walls off the arena's outer ring every [`ARENA_SHRINK_INTERVAL`] seconds, after flagging it for
+/// the last [`ARENA_WARNING_TIME`] of them. Anything lying on the ring is lost.
+#[allow(clippy::type_complexity)]
+fn arena_shrink_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut arena: ResMut<Arena>,
+    warning_query: Query<Entity, With<ArenaWarning>>,
+    item_query: Query<(Entity, &GridPosition), Or<(With<Food>, With<PowerUp>, With<Mouse>)>>,
+) {
+    if !arena.can_shrink() {
+        return;
+    }
+
+    arena.timer.tick(time.delta());
+
+    if arena.warning() && warning_query.is_empty() {
+        for cell in ring_cells(arena.rings) {
+            commands.spawn((
+                Sprite {
+                    color: ARENA_WARNING_COLOR,
+                    custom_size: Some(OBSTACLE_SIZE),
+                    ..default()
+                },
+                Transform::default(),
+                GridPosition(cell),
+                ArenaWarning,
+            ));
+        }
+    }
+
+    if !arena.timer.just_finished() {
+        return;
+    }
+
+    for entity in warning_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let ring: HashSet<IVec2> = ring_cells(arena.rings).collect();
+    spawn_arena_ring(&mut commands, arena.rings);
+    arena.rings += 1;
+
+    for (entity, position) in item_query.iter() {
+        if ring.contains(&position.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// How many steps in from the edge of the board `cell` lies.
+pub fn ring_of(cell: IVec2) -> i32 {
+    let far_edge = IVec2::new(GRID_WIDTH, GRID_HEIGHT) - 1 - cell;
+    cell.min(far_edge).min_element()
+}
+
+/// Walls off `ring` steps in from the edge of the board.
+pub fn spawn_arena_ring(commands: &mut Commands, ring: i32) {
+    for cell in ring_cells(ring) {
+        commands.spawn((
+            Sprite {
+                color: ARENA_WALL_COLOR,
+                custom_size: Some(OBSTACLE_SIZE),
+                ..default()
+            },
+            Transform::default(),
+            GridPosition(cell),
+            Obstacle,
+            ArenaWall,
+        ));
+    }
+}
+
+fn arena_warning_blink_system(time: Res<Time>, mut query: Query<&mut Sprite, With<ArenaWarning>>) {
+    let alpha = 0.55 + 0.35 * (time.elapsed_secs() * 10.).sin();
+    for mut sprite in query.iter_mut() {
+        sprite.color = ARENA_WARNING_COLOR.with_alpha(alpha);
+    }
+}
+
+/// The cells `ring` steps in from the edge of the board.
+fn ring_cells(ring: i32) -> impl Iterator<Item = IVec2> {
+    let min = IVec2::splat(ring);
+    let max = IVec2::new(GRID_WIDTH, GRID_HEIGHT) - 1 - ring;
+    (min.x..=max.x)
+        .flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+        .filter(move |cell| cell.x == min.x || cell.x == max.x || cell.y == min.y || cell.y == max.y)
+}
+
+fn spawn_level_select_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            StateScoped(GameState::LevelSelect),
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: HUD_FONT_SIZE * 1.5,
+                ..default()
+            },
+            TextColor(HUD_COLOR),
+            TextLayout::new_with_justify(JustifyText::Center),
+            LevelSelectText,
+        ));
+}
+
+fn level_select_input_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    levels: Res<Levels>,
+    random_level: Res<RandomLevel>,
+    mut level_assets: ResMut<Assets<Level>>,
+    mut game_rng: ResMut<GameRng>,
+    mut selected_level: ResMut<SelectedLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let count = levels.0.len();
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        selected_level.0 = (selected_level.0 + count - 1) % count;
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        selected_level.0 = (selected_level.0 + 1) % count;
+    }
+    if keys.just_pressed(KeyCode::KeyN) {
+        *game_rng = GameRng::from_seed(rand::random());
+        level_assets.insert(&random_level.0, generate_maze(&mut game_rng));
+    }
+
+    // A level that is still loading, or failed to, can't be played yet.
+    if keys.just_pressed(KeyCode::Enter) && level_assets.contains(&levels.0[selected_level.0]) {
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn level_select_text_system(
+    levels: Res<Levels>,
+    level_assets: Res<Assets<Level>>,
+    selected_level: Res<SelectedLevel>,
+    mut query: Query<&mut Text, With<LevelSelectText>>,
+) {
+    let mut content = String::from("Select a level\n\n");
+    for (index, handle) in levels.0.iter().enumerate() {
+        let name = level_assets.get(handle).map_or("Loading...", |level| level.name.as_str());
+        let marker = if index == selected_level.0 { ">" } else { " " };
+        content += &format!("{marker} {name}\n");
+    }
+    content += "\nUp/Down to choose, Enter to play, N for a new random level, Escape to go back";
+
+    // Levels finish loading at any time, so rebuild every frame but only touch the text on a change.
+    for mut text in query.iter_mut() {
+        if text.0 != content {
+            text.0.clone_from(&content);
+        }
+    }
+}
+
+fn parse_level(text: &str) -> Result<Level, String> {
+    let mut lines = text.lines();
+    let name = lines
+        .next()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| "level has no name".to_string())?
+        .to_string();
+
+    let mut obstacles = Vec::new();
+    let mut portal_ends: HashMap<char, Vec<IVec2>> = HashMap::new();
+    for (row, line) in lines.enumerate() {
+        let line = line.trim_end();
+        if row >= GRID_HEIGHT as usize {
+            return Err(format!("level has more than {GRID_HEIGHT} rows"));
+        }
+        if line.chars().count() > GRID_WIDTH as usize {
+            return Err(format!("row {row} is wider than {GRID_WIDTH} cells"));
+        }
+
+        let y = GRID_HEIGHT - 1 - row as i32;
+        for (x, cell) in line.chars().enumerate() {
+            match cell {
+                '#' => obstacles.push(IVec2::new(x as i32, y)),
+                '.' => {}
+                'a'..='z' => portal_ends.entry(cell).or_default().push(IVec2::new(x as i32, y)),
+                other => return Err(format!("unknown cell {other:?} in row {row}")),
+            }
+        }
+    }
+
+    let mut letters: Vec<char> = portal_ends.keys().copied().collect();
+    letters.sort();
+    let mut portals = Vec::new();
+    for letter in letters {
+        match portal_ends[&letter][..] {
+            [a, b] => portals.push((a, b)),
+            _ => {
+                let count = portal_ends[&letter].len();
+                return Err(format!("portal {letter:?} has {count} ends instead of 2"));
+            }
+        }
+    }
+
+    Ok(Level { name, obstacles, portals })
+}
+
+/// Scatters straight wall pieces over the board, dropping any piece that would cut part of the
+/// open floor off from the rest. The start positions and the first food are kept clear.
+fn generate_maze(game_rng: &mut GameRng) -> Level {
+    let grid_size = IVec2::new(GRID_WIDTH, GRID_HEIGHT);
+    let keep_clear = [
+        SNAKE_START_POSITION,
+        VERSUS_START_POSITIONS[0].0,
+        VERSUS_START_POSITIONS[1].0,
+        FOOD_START_POSITION,
+    ];
+    let rng = &mut game_rng.rng;
+
+    let mut walls: HashSet<IVec2> = HashSet::new();
+    for _ in 0..MAZE_WALL_COUNT {
+        let start = IVec2::new(rng.random_range(0..GRID_WIDTH), rng.random_range(0..GRID_HEIGHT));
+        let direction = if rng.random_bool(0.5) { IVec2::X } else { IVec2::Y };
+        let length = rng.random_range(MAZE_WALL_LENGTH);
+
+        let piece: Vec<IVec2> = (0..length)
+            .map(|i| start + direction * i)
+            .filter(|cell| cell.cmplt(grid_size).all() && !walls.contains(cell))
+            .filter(|&cell| keep_clear.iter().all(|&clear| (cell - clear).abs().max_element() > MAZE_CLEARANCE))
+            .collect();
+        walls.extend(&piece);
+
+        if !floor_connected(&walls) {
+            for cell in &piece {
+                walls.remove(cell);
+            }
+        }
+    }
+
+    Level {
+        name: format!("Random (seed {})", game_rng.seed),
+        obstacles: walls.into_iter().collect(),
+        portals: Vec::new(),
+    }
+}
+
+/// Whether every cell that isn't a wall can be reached from every other one.
+fn floor_connected(walls: &HashSet<IVec2>) -> bool {
+    let grid_size = IVec2::new(GRID_WIDTH, GRID_HEIGHT);
+    let floor_size = (GRID_WIDTH * GRID_HEIGHT) as usize - walls.len();
+
+    let mut reached: HashSet<IVec2> = HashSet::from_iter([SNAKE_START_POSITION]);
+    let mut frontier = vec![SNAKE_START_POSITION];
+    while let Some(cell) = frontier.pop() {
+        for direction in DIRECTIONS {
+            let next = cell + direction;
+            let on_board = next.cmpge(IVec2::ZERO).all() && next.cmplt(grid_size).all();
+            if on_board && !walls.contains(&next) && reached.insert(next) {
+                frontier.push(next);
+            }
+        }
+    }
+
+    reached.len() == floor_size
+}
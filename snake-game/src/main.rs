@@ -1,221 +1,249 @@
+mod achievements;
+mod audio;
+mod difficulty;
+mod food;
+mod grid;
+mod high_scores;
+#[cfg(feature = "leaderboard")]
+mod leaderboard;
+mod level;
+mod particles;
+mod rng;
+mod round;
+mod save;
+mod score;
+mod screen_effects;
+mod snake;
+mod storage;
+#[cfg(test)]
+mod tests;
+mod theme;
+mod timed;
+mod trail;
+mod ui;
+
+use achievements::AchievementsPlugin;
+use audio::SoundPlugin;
 use bevy::prelude::*;
-use bevy::ecs::system::ParamSet;
-use rand::Rng;
-
-const WINDOW_WIDTH: f32 = 800.;
-const WINDOW_HEIGHT: f32 = 600.;
+use difficulty::DifficultyPlugin;
+use food::FoodPlugin;
+use grid::{BorderMode, GridPlugin, WINDOW_HEIGHT, WINDOW_WIDTH};
+use high_scores::{HighScoresPlugin, NameEntry};
+use level::LevelPlugin;
+use particles::ParticlesPlugin;
+use rng::GameRng;
+use round::RoundPlugin;
+use save::SavePlugin;
+use score::ScorePlugin;
+use screen_effects::ScreenEffectsPlugin;
+use serde::{Deserialize, Serialize};
+use snake::{load_snake_sprites, Player, SnakePlugin};
+use theme::ThemePlugin;
+use timed::TimedPlugin;
+use trail::TrailPlugin;
+use ui::UiPlugin;
+
+/// Fixed ticks per second the steps are timed on; well above the fastest step rate, so each step
+/// lands close to when it was due.
+const FIXED_TICK_RATE: f64 = 200.;
+
+/// Whether one snake plays alone or two share the arena.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum GameMode {
+    #[default]
+    Single,
+    Versus,
+    /// Player two is steered by the computer.
+    VersusAi,
+    /// Both players steer the one snake: player one turns it left and right, player two up and down.
+    Coop,
+}
 
-const FOOD_SIZE: Vec2 = Vec2::new(10., 10.);
-const FOOD_START_POSITION: Vec2 = Vec2::new(50., 50.);
-const FOOD_COLOR: Color = Color::srgb(0.7, 0.3, 0.3);
+impl GameMode {
+    fn player_name(self, player: Player) -> &'static str {
+        match (self, player) {
+            (GameMode::VersusAi, Player::Two) => "Computer",
+            _ => player.name(),
+        }
+    }
+}
 
-const SNAKE_SIZE: Vec2 = Vec2::new(10., 10.);
-const SNAKE_COLOR: Color = Color::srgb(0.3, 0.3, 0.7);
-const SNAKE_SPEED: f32 = 200.;
+/// Extra rules played on top of any [`GameMode`].
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum Ruleset {
+    #[default]
+    Classic,
+    /// The board closes in by a ring of wall every [`ARENA_SHRINK_INTERVAL`](level::ARENA_SHRINK_INTERVAL) seconds.
+    ShrinkingArena,
+    /// Score as much as possible before the [`MatchTimer`](timed::MatchTimer) runs out.
+    Timed,
+}
 
-#[derive(Component)]
-struct Food;
+impl Ruleset {
+    fn name(self) -> &'static str {
+        match self {
+            Ruleset::Classic => "Classic",
+            Ruleset::ShrinkingArena => "Shrinking arena",
+            Ruleset::Timed => "Timed challenge",
+        }
+    }
 
-#[derive(Component)]
-struct SnakeSegment;
+    fn next(self) -> Self {
+        match self {
+            Ruleset::Classic => Ruleset::ShrinkingArena,
+            Ruleset::ShrinkingArena => Ruleset::Timed,
+            Ruleset::Timed => Ruleset::Classic,
+        }
+    }
+}
 
-#[derive(Resource)]
-struct Direction(Vec2);
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+enum GameState {
+    #[default]
+    Menu,
+    Settings,
+    /// Lifetime statistics and achievements.
+    Stats,
+    LevelSelect,
+    Playing,
+    Paused,
+    /// The round is over and the [`DeathAnimation`](snake::DeathAnimation) is playing.
+    Dying,
+    GameOver,
+}
 
-#[derive(Resource)]
-struct Snake(Vec<Entity>);
+/// The stages of a step of play, run in this order on every fixed tick while [`GameState::Playing`].
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum StepSet {
+    /// Turns from every input source, and the step timer.
+    Input,
+    /// Snakes step forward and come out of the edges and portals.
+    Move,
+    /// Heads are checked against everything else on the board.
+    Collide,
+    /// Eaten food is scored, grown from and replaced.
+    Grow,
+    /// Deaths and the match clock decide whether the round is over.
+    Resolve,
+}
 
 fn main() {
-    App::new()
+    let seed = arg_value("--seed").and_then(|seed| seed.parse().ok()).unwrap_or_else(rand::random);
+
+    let mut app = App::new();
+
+    app
         .add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
-                        title: "Snake Game".into(), 
+                        title: "Snake Game".into(),
                         resolution: Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT).into(),
                         resizable: false,
+                        // In the browser the canvas fills the page instead.
+                        canvas: Some("#bevy".into()),
+                        fit_canvas_to_parent: true,
                         ..default()
                     }),
                     ..default()
                 })
         )
-        .insert_resource(ClearColor(Color::srgb(0.9, 0.9, 0.9)))
-        .insert_resource(Direction(Vec2::X))
-        .add_systems(Startup, setup)
-        .add_systems(Update, (snake_input_system, snake_movement_system, food_collision_system, self_collision_system))
-        .run();
+        .add_plugins((
+            GameplayPlugin,
+            GridPlugin,
+            UiPlugin,
+            SavePlugin,
+            AchievementsPlugin,
+            ScreenEffectsPlugin,
+            TrailPlugin,
+            ThemePlugin,
+            ParticlesPlugin,
+            SoundPlugin,
+            HighScoresPlugin,
+        ))
+        .insert_resource(GameRng::from_seed(seed))
+        .add_systems(Startup, load_snake_sprites)
+        .add_systems(Update, pause_system.run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))))
+        .add_systems(
+            Update,
+            // Typing a name must not trigger the letter shortcuts.
+            (border_mode_input_system, restart_system)
+                .run_if(not(resource_exists::<NameEntry>))
+                .run_if(in_state(GameState::GameOver)),
+        );
+
+    #[cfg(feature = "leaderboard")]
+    app.add_plugins(leaderboard::LeaderboardPlugin);
+
+    app.run();
 }
 
-fn setup(mut commands: Commands) {
-    commands.spawn(Camera2d);
-    
-    let center = Vec2::ZERO;
-
-    commands.spawn((
-        Sprite {
-            color: FOOD_COLOR,
-            custom_size: Some(FOOD_SIZE),
-            ..default()
-        },
-        Transform::from_translation(FOOD_START_POSITION.extend(0.)),
-        Food,
-    ));
-
-    let mut snake = Vec::new();
-    for i in 0..3 {
-        let pos = center + Vec2::new(-i as f32 * SNAKE_SIZE.x, 0.);
-        let entity = commands
-            .spawn((
-                Sprite {
-                    color: SNAKE_COLOR,
-                    custom_size: Some(SNAKE_SIZE),
-                    ..default()
-                },
-                Transform::from_translation(pos.extend(0.)),
-                SnakeSegment,
+/// The rules of the game, kept free of the window, the UI, sound and sprite loading so a game
+/// can also be played out headless.
+struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .enable_state_scoped_entities::<GameState>()
+            .insert_resource(Time::<Fixed>::from_hz(FIXED_TICK_RATE))
+            .configure_sets(
+                FixedUpdate,
+                (StepSet::Input, StepSet::Move, StepSet::Collide, StepSet::Grow, StepSet::Resolve)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_plugins((
+                SnakePlugin,
+                FoodPlugin,
+                LevelPlugin,
+                ScorePlugin,
+                DifficultyPlugin,
+                TimedPlugin,
+                RoundPlugin,
             ))
-            .id();
-        snake.push(entity);
+            .init_resource::<BorderMode>()
+            .init_resource::<GameMode>()
+            .init_resource::<Ruleset>();
     }
-
-    commands.insert_resource(Snake(snake));
 }
 
-fn snake_input_system(keys: Res<ButtonInput<KeyCode>>, mut dir: ResMut<Direction>) {
-    if keys.pressed(KeyCode::ArrowUp) && dir.0 != -Vec2::Y {
-        dir.0 = Vec2::Y;
-    } else if keys.pressed(KeyCode::ArrowDown) && dir.0 != Vec2::Y {
-        dir.0 = -Vec2::Y;
-    } else if keys.pressed(KeyCode::ArrowLeft) && dir.0 != Vec2::X {
-        dir.0 = -Vec2::X;
-    } else if keys.pressed(KeyCode::ArrowRight) && dir.0 != -Vec2::X {
-        dir.0 = Vec2::X;
-    }
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
 }
 
-fn snake_movement_system(
-    time: Res<Time>,
-    dir: Res<Direction>,
-    snake: Res<Snake>,
-    mut query: Query<&mut Transform, With<SnakeSegment>>,
+fn pause_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    let dt = time.delta_secs();
-
-    let mut previous_positions: Vec<Vec3> = Vec::new();
-
-    for &entity in snake.0.iter() {
-        if let Ok(transform) = query.get(entity) {
-            previous_positions.push(transform.translation);
-        }
-    }
-
-    if let Ok(mut head_transform) = query.get_mut(snake.0[0]) {
-        head_transform.translation += (dir.0 * SNAKE_SPEED * dt).extend(0.0);
-    }
-
-    for (i, &entity) in snake.0.iter().enumerate().skip(1) {
-        if let Ok(mut transform) = query.get_mut(entity) {
-            transform.translation = previous_positions[i - 1];
-        }
+    match state.get() {
+        GameState::Playing if keys.just_pressed(KeyCode::Escape) => next_state.set(GameState::Paused),
+        GameState::Paused if keys.just_pressed(KeyCode::Escape) => next_state.set(GameState::Playing),
+        GameState::Paused if keys.just_pressed(KeyCode::KeyM) => next_state.set(GameState::Menu),
+        _ => {}
     }
 }
 
-fn food_collision_system(
-    mut commands: Commands,
-    mut snake: ResMut<Snake>,
-    mut param_set: ParamSet<(
-        Query<&mut Transform, With<SnakeSegment>>,
-        Query<(Entity, &Transform), With<Food>>,
-    )>,
-) {
-    let head_position = {
-        let mut segment_query = param_set.p0();
-        let Ok(head_transform) = segment_query.get_mut(snake.0[0]) else {
-            return;
+fn border_mode_input_system(keys: Res<ButtonInput<KeyCode>>, mut border_mode: ResMut<BorderMode>) {
+    if keys.just_pressed(KeyCode::KeyB) {
+        *border_mode = match *border_mode {
+            BorderMode::Solid => BorderMode::Wrap,
+            BorderMode::Wrap => BorderMode::Solid,
         };
-        head_transform.translation.truncate()
-    };
-
-    let food_data: Vec<(Entity, Vec2)> = param_set
-        .p1()
-        .iter()
-        .map(|(e, transform)| (e, transform.translation.truncate()))
-        .collect();
-
-    for (food_entity, food_pos) in food_data {
-        let distance = head_position.distance(food_pos);
-
-        if distance < (SNAKE_SIZE.x / 2.0 + FOOD_SIZE.x / 2.0) {
-            commands.entity(food_entity).despawn();
-
-            if let Some(&last_segment) = snake.0.last() {
-                let segment_query = param_set.p0();
-                if let Ok(last_transform) = segment_query.get(last_segment) {
-                    let new_segment = commands
-                        .spawn((
-                            Sprite {
-                                color: SNAKE_COLOR,
-                                custom_size: Some(SNAKE_SIZE),
-                                ..default()
-                            },
-                            Transform::from_translation(last_transform.translation),
-                            SnakeSegment,
-                        ))
-                        .id();
-                    snake.0.push(new_segment);
-                }
-            }
-            
-            spawn_food(&mut commands);
-        }
     }
 }
 
-fn spawn_food(commands: &mut Commands) {
-    let x_range = -WINDOW_WIDTH / 2. .. WINDOW_WIDTH / 2.;
-    let y_range = -WINDOW_HEIGHT / 2. .. WINDOW_HEIGHT / 2.;
-
-    let mut rng = rand::rng();
-    let random_x = rng.random_range(x_range.clone());
-    let random_y = rng.random_range(y_range.clone());
-
-    let random_pos = Vec3::new(random_x, random_y, 0.0);
-
-    commands.spawn((
-        Sprite {
-            color: FOOD_COLOR,
-            custom_size: Some(FOOD_SIZE),
-            ..default()
-        },
-        Transform::from_translation(random_pos),
-        Food,
-    ));
-}
-
-fn self_collision_system(
-    snake: Res<Snake>,
-    query: Query<&Transform, With<SnakeSegment>>,
-    mut exit: EventWriter<AppExit>,
+fn restart_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    touches: Res<Touches>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if snake.0.len() < 4 {
-        return;
-    }
-
-    let head_pos = {
-        let Ok(head_transform) = query.get(snake.0[0]) else { 
-            return; 
-        };
-        head_transform.translation.truncate()
-    };
-
-    for &segment in &snake.0[1..] {
-        if let Ok(segment_transform) = query.get(segment) {
-            let segment_pos = segment_transform.translation.truncate();
-            let distance = head_pos.distance(segment_pos);
-
-            if distance < SNAKE_SIZE.x / 2.0 {
-                exit.send(AppExit::Success);
-            }
-        }
+    if keys.just_pressed(KeyCode::KeyR) || touches.any_just_pressed() {
+        next_state.set(GameState::Playing);
+    } else if keys.just_pressed(KeyCode::KeyL) {
+        next_state.set(GameState::LevelSelect);
+    } else if keys.just_pressed(KeyCode::KeyM) {
+        next_state.set(GameState::Menu);
     }
 }
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::food::{BonusEatenEvent, EatEvent};
+use crate::grid::{grid_to_world, GridPosition};
+use crate::snake::{CollisionEvent, Player, Snake, SnakeSegment};
+use crate::theme::Theme;
+
+const PARTICLE_SIZE: Vec2 = Vec2::splat(3.);
+const PARTICLE_GRAVITY: f32 = -300.;
+
+/// Sends out a one-off burst of [`Particle`]s from where it is spawned, then despawns itself. The
+/// presets are white until given a color from the [`Theme`].
+#[derive(Component, Clone, Copy)]
+struct ParticleEmitter {
+    count: usize,
+    color: Color,
+    /// Fastest launch speed; each particle gets between half of it and all of it.
+    speed: f32,
+    lifetime: f32,
+}
+
+impl ParticleEmitter {
+    const FOOD: Self = Self { count: 12, color: Color::WHITE, speed: 120., lifetime: 0.5 };
+    const BONUS: Self = Self { count: 24, color: Color::WHITE, speed: 160., lifetime: 0.7 };
+    const DEATH: Self = Self { count: 40, color: Color::WHITE, speed: 200., lifetime: 0.9 };
+
+    fn with_color(self, color: Color) -> Self {
+        Self { color, ..self }
+    }
+}
+
+/// A speck thrown out by a [`ParticleEmitter`] that falls and fades until its time is up.
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    timer: Timer,
+    color: Color,
+}
+
+/// Bursts of particles where food is eaten and where snakes die.
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (effect_particles_system, particle_emitter_system, particle_system));
+    }
+}
+
+/// Bursts particles where food is eaten and where snakes die.
+fn effect_particles_system(
+    mut commands: Commands,
+    mut eat_events: EventReader<EatEvent>,
+    mut bonus_events: EventReader<BonusEatenEvent>,
+    mut collision_events: EventReader<CollisionEvent>,
+    theme: Res<Theme>,
+    snake_query: Query<(&Player, &Snake)>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+) {
+    let palette = theme.palette();
+    for event in eat_events.read() {
+        emit_particles(&mut commands, ParticleEmitter::FOOD.with_color(palette.food), event.position);
+    }
+    for event in bonus_events.read() {
+        emit_particles(&mut commands, ParticleEmitter::BONUS.with_color(palette.bonus_food), event.position);
+    }
+    for event in collision_events.read() {
+        let Ok((player, snake)) = snake_query.get(event.snake) else {
+            continue;
+        };
+        if let Ok(head_position) = segment_query.get(snake.head()) {
+            emit_particles(&mut commands, ParticleEmitter::DEATH.with_color(player.color(palette)), head_position.0);
+        }
+    }
+}
+
+fn emit_particles(commands: &mut Commands, emitter: ParticleEmitter, position: IVec2) {
+    commands.spawn((emitter, Transform::from_translation(grid_to_world(position).extend(2.))));
+}
+
+fn particle_emitter_system(mut commands: Commands, query: Query<(Entity, &ParticleEmitter, &Transform)>) {
+    // Particles are only for show; drawing them from the GameRng would make replays depend on them.
+    let mut rng = rand::rng();
+
+    for (entity, emitter, transform) in query.iter() {
+        for _ in 0..emitter.count {
+            let angle = rng.random_range(0. ..std::f32::consts::TAU);
+            let speed = rng.random_range(emitter.speed / 2. ..=emitter.speed);
+            commands.spawn((
+                Sprite {
+                    color: emitter.color,
+                    custom_size: Some(PARTICLE_SIZE),
+                    ..default()
+                },
+                *transform,
+                Particle {
+                    velocity: Vec2::from_angle(angle) * speed,
+                    timer: Timer::from_seconds(emitter.lifetime, TimerMode::Once),
+                    color: emitter.color,
+                },
+            ));
+        }
+
+        commands.entity(entity).despawn();
+    }
+}
+
+fn particle_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        if particle.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        particle.velocity.y += PARTICLE_GRAVITY * time.delta_secs();
+        transform.translation += (particle.velocity * time.delta_secs()).extend(0.);
+        sprite.color = particle.color.with_alpha(particle.timer.fraction_remaining());
+    }
+}
@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+/// The game's random number generator, kept with the seed it started from so whatever it
+/// generates can be reproduced. Every game replays the stream from the seed, so the same seed
+/// and the same inputs lay out the same food, and each game's seed follows from the last so a
+/// whole session started from `--seed` repeats too.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    /// The same generator as [`StdRng`](rand::rngs::StdRng), named so how far it has got can be
+    /// saved and restored.
+    pub rng: ChaCha12Rng,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self { seed, rng: ChaCha12Rng::seed_from_u64(seed) }
+    }
+
+    /// Starts the stream over from the seed.
+    pub fn rewind(&mut self) {
+        self.rng = ChaCha12Rng::seed_from_u64(self.seed);
+    }
+
+    /// Moves on to the next game's seed, drawn from this one's rather than from how far its
+    /// stream has got, so it doesn't depend on how the game went.
+    pub fn advance(&mut self) {
+        *self = Self::from_seed(ChaCha12Rng::seed_from_u64(self.seed).random());
+    }
+}
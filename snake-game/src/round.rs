@@ -0,0 +1,206 @@
+use bevy::prelude::*;
+
+use crate::difficulty::DifficultySettings;
+use crate::food::{place_food, ActiveEffects, Food, LoadedFoodTable, Mouse, PowerUp, FOOD_START_POSITION};
+use crate::level::{
+    spawn_arena_ring, spawn_level, Arena, ArenaWarning, Level, Levels, Obstacle, ObstacleWarning, Portal, SelectedLevel,
+};
+use crate::rng::GameRng;
+use crate::score::Score;
+use crate::snake::{
+    death_system, spawn_snake, AiControlled, GameSpeed, MoveTimer, Player, SharedControl, Snake, SnakeSegment,
+    SnakeSprites, SNAKE_START_POSITION, VERSUS_START_POSITIONS,
+};
+use crate::theme::Theme;
+use crate::timed::{MatchTimer, TimedResult};
+use crate::trail::TrailCell;
+use crate::ui::FloatingText;
+use crate::{GameMode, GameState, Ruleset, StepSet};
+
+/// The round is over and the game over screen is about to show.
+#[derive(Event)]
+pub struct GameOverEvent;
+
+/// The player who took the last two-player round, or `None` for a draw.
+#[derive(Resource, Default)]
+pub struct Winner(pub Option<Player>);
+
+/// Everything on the board that a game leaves behind.
+type OnBoard = Or<(
+    With<Snake>,
+    With<SnakeSegment>,
+    With<Food>,
+    With<PowerUp>,
+    With<Mouse>,
+    With<Obstacle>,
+    With<Portal>,
+    With<ArenaWarning>,
+    With<ObstacleWarning>,
+    With<FloatingText>,
+    With<TrailCell>,
+)>;
+
+/// Lays out each new round, decides when and how it ends, and clears the board after it.
+pub struct RoundPlugin;
+
+impl Plugin for RoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Winner>()
+            .add_event::<GameOverEvent>()
+            // Resuming from pause re-enters Playing too, so only these transitions start a new game.
+            .add_systems(OnTransition { exited: GameState::LevelSelect, entered: GameState::Playing }, start_game)
+            .add_systems(OnTransition { exited: GameState::GameOver, entered: GameState::Playing }, start_game)
+            .add_systems(OnTransition { exited: GameState::Paused, entered: GameState::Menu }, clear_board)
+            .add_systems(
+                FixedUpdate,
+                (round_end_system, game_over_state_system).chain().after(death_system).in_set(StepSet::Resolve),
+            )
+            .add_systems(OnExit(GameState::GameOver), clear_board);
+    }
+}
+
+/// Lays out a fresh board on the selected level; runs whenever a game starts, restarts included.
+#[allow(clippy::too_many_arguments)]
+fn start_game(
+    mut commands: Commands,
+    mut speed: ResMut<GameSpeed>,
+    mut effects: ResMut<ActiveEffects>,
+    game_mode: Res<GameMode>,
+    ruleset: Res<Ruleset>,
+    difficulty: Res<DifficultySettings>,
+    levels: Res<Levels>,
+    selected_level: Res<SelectedLevel>,
+    level_assets: Res<Assets<Level>>,
+    sprites: Res<SnakeSprites>,
+    theme: Res<Theme>,
+    food_table: LoadedFoodTable,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let palette = theme.palette();
+    info!("game seed: {}", game_rng.seed);
+    game_rng.rewind();
+    speed.level = 0;
+    *effects = ActiveEffects::default();
+    commands.insert_resource(MoveTimer(Timer::from_seconds(speed.interval(), TimerMode::Repeating)));
+    commands.remove_resource::<TimedResult>();
+
+    match *ruleset {
+        Ruleset::Classic => {
+            commands.remove_resource::<Arena>();
+            commands.remove_resource::<MatchTimer>();
+        }
+        Ruleset::ShrinkingArena => {
+            commands.insert_resource(Arena { rings: difficulty.arena_inset, ..Arena::new() });
+            commands.remove_resource::<MatchTimer>();
+        }
+        Ruleset::Timed => {
+            commands.remove_resource::<Arena>();
+            commands.insert_resource(MatchTimer::default());
+        }
+    }
+
+    for ring in 0..difficulty.arena_inset {
+        spawn_arena_ring(&mut commands, ring);
+    }
+
+    if let Some(kind) = food_table.get().and_then(|table| table.roll_lasting(&mut game_rng.rng)) {
+        place_food(&mut commands, palette, kind, FOOD_START_POSITION);
+    }
+
+    match *game_mode {
+        GameMode::Single => {
+            spawn_snake(&mut commands, &sprites, palette, Player::One, SNAKE_START_POSITION, IVec2::X);
+        }
+        GameMode::Coop => {
+            let snake = spawn_snake(&mut commands, &sprites, palette, Player::One, SNAKE_START_POSITION, IVec2::X);
+            commands.entity(snake).insert(SharedControl);
+        }
+        GameMode::Versus | GameMode::VersusAi => {
+            for (player, (position, heading)) in [Player::One, Player::Two].into_iter().zip(VERSUS_START_POSITIONS) {
+                let snake = spawn_snake(&mut commands, &sprites, palette, player, position, heading);
+                if *game_mode == GameMode::VersusAi && player == Player::Two {
+                    commands.entity(snake).insert(AiControlled);
+                }
+            }
+        }
+    }
+
+    if let Some(level) = level_assets.get(&levels.0[selected_level.0]) {
+        spawn_level(&mut commands, level, palette);
+    }
+}
+
+/// Ends the round once a snake has died or the match clock has run out. In a two-player game the
+/// survivor wins; if both died on the same step, the longer snake does, and if time ran out on
+/// both, the higher score does.
+pub fn round_end_system(
+    mut winner: ResMut<Winner>,
+    game_mode: Res<GameMode>,
+    match_timer: Option<Res<MatchTimer>>,
+    snake_query: Query<(&Player, &Snake, &Score)>,
+    mut game_over_events: EventWriter<GameOverEvent>,
+) {
+    let time_up = match_timer.is_some_and(|match_timer| match_timer.0.finished());
+    if !time_up && snake_query.iter().all(|(_, snake, _)| snake.alive) {
+        return;
+    }
+
+    winner.0 = match *game_mode {
+        GameMode::Single | GameMode::Coop => None,
+        GameMode::Versus | GameMode::VersusAi => {
+            let survivors: Vec<Player> = snake_query
+                .iter()
+                .filter(|(_, snake, _)| snake.alive)
+                .map(|(&player, ..)| player)
+                .collect();
+            match survivors[..] {
+                [survivor] => Some(survivor),
+                _ if time_up => {
+                    unique_best(snake_query.iter().map(|(&player, _, score)| (player, score.0 as usize)))
+                }
+                _ => unique_best(snake_query.iter().map(|(&player, snake, _)| (player, snake.segments.len()))),
+            }
+        }
+    };
+
+    game_over_events.send(GameOverEvent);
+}
+
+fn game_over_state_system(
+    mut game_over_events: EventReader<GameOverEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if game_over_events.read().count() > 0 {
+        next_state.set(GameState::Dying);
+    }
+}
+
+/// The player with the strictly highest value, or `None` when the highest are tied.
+fn unique_best(candidates: impl Iterator<Item = (Player, usize)>) -> Option<Player> {
+    let mut best: Option<(Player, usize)> = None;
+    let mut tied = false;
+
+    for (player, value) in candidates {
+        match best {
+            Some((_, best_value)) if value < best_value => {}
+            Some((_, best_value)) if value == best_value => tied = true,
+            _ => {
+                best = Some((player, value));
+                tied = false;
+            }
+        }
+    }
+
+    best.filter(|_| !tied).map(|(player, _)| player)
+}
+
+fn clear_board(
+    mut commands: Commands,
+    query: Query<Entity, OnBoard>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    game_rng.advance();
+}
@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::food::{place_food, place_mouse, place_power_up, ActiveEffects, Food, FoodKind, FoodLifetime, Mouse, PowerUp};
+use crate::grid::{BorderMode, GridPosition};
+use crate::level::{ring_of, spawn_arena_ring, spawn_level, Arena, ArenaWall, Level, Obstacle, Portal};
+use crate::rng::GameRng;
+use crate::score::{Combo, Score};
+use crate::snake::{
+    spawn_snake_along, AiControlled, DirectionQueue, GameSpeed, MoveTimer, Player, SharedControl, Snake, SnakeSprites,
+};
+use crate::theme::Theme;
+use crate::timed::{MatchTimer, TimedResult};
+use crate::{storage, GameMode, GameState, Ruleset};
+
+const SAVE_KEY: &str = "snake-game.save";
+
+/// A game in progress, written to [`SAVE_KEY`] on Ctrl+S or when the window closes mid-game and
+/// offered on the main menu until it is continued.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct SavedGame {
+    game_mode: GameMode,
+    ruleset: Ruleset,
+    border_mode: BorderMode,
+    speed: GameSpeed,
+    /// The [`GameRng`]'s seed and how many words of its stream had been drawn.
+    seed: u64,
+    rng_position: u64,
+    snakes: Vec<SavedSnake>,
+    /// Each piece of food whole, so it comes back as it was even if the food table has changed since.
+    food: Vec<(FoodKind, IVec2, Option<SavedTimer>)>,
+    power_ups: Vec<(IVec2, PowerUp)>,
+    mice: Vec<IVec2>,
+    /// Walls from the level; the arena's rings are rebuilt from `arena`.
+    obstacles: Vec<IVec2>,
+    portals: Vec<(IVec2, IVec2)>,
+    /// Rings walled off around the board, whether by the difficulty or the shrinking arena.
+    walled_rings: i32,
+    /// Rings closed so far and the timer to the next one.
+    arena: Option<(i32, SavedTimer)>,
+    match_timer: Option<SavedTimer>,
+    ghosts: Vec<(Player, SavedTimer)>,
+    slow: Option<SavedTimer>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SavedSnake {
+    player: Player,
+    /// Head first.
+    segments: Vec<IVec2>,
+    heading: IVec2,
+    queued: Vec<IVec2>,
+    score: u32,
+    combo: u32,
+    combo_window: Option<SavedTimer>,
+    computer: bool,
+}
+
+/// How far a [`Timer`] has run, in seconds.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct SavedTimer {
+    elapsed: f32,
+    duration: f32,
+}
+
+impl SavedTimer {
+    fn new(timer: &Timer) -> Self {
+        Self { elapsed: timer.elapsed_secs(), duration: timer.duration().as_secs_f32() }
+    }
+
+    fn restore(self, mode: TimerMode) -> Timer {
+        let mut timer = Timer::from_seconds(self.duration, mode);
+        timer.set_elapsed(Duration::from_secs_f32(self.elapsed));
+        timer
+    }
+}
+
+impl SavedGame {
+    fn load() -> Option<Self> {
+        storage::load(SAVE_KEY)
+    }
+
+    fn save(&self) {
+        storage::save(SAVE_KEY, self);
+    }
+
+    fn delete() {
+        storage::remove(SAVE_KEY);
+    }
+}
+
+/// Everything on the board and in the rules that a [`SavedGame`] is taken from.
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+struct GameSnapshot<'w, 's> {
+    game_mode: Res<'w, GameMode>,
+    ruleset: Res<'w, Ruleset>,
+    border_mode: Res<'w, BorderMode>,
+    speed: Res<'w, GameSpeed>,
+    effects: Res<'w, ActiveEffects>,
+    arena: Option<Res<'w, Arena>>,
+    match_timer: Option<Res<'w, MatchTimer>>,
+    game_rng: Res<'w, GameRng>,
+    snake_query: Query<
+        'w,
+        's,
+        (&'static Player, &'static Snake, &'static DirectionQueue, &'static Score, &'static Combo, Has<AiControlled>),
+    >,
+    position_query: Query<'w, 's, &'static GridPosition>,
+    food_query: Query<'w, 's, (&'static GridPosition, &'static Food, Option<&'static FoodLifetime>)>,
+    power_up_query: Query<'w, 's, (&'static GridPosition, &'static PowerUp)>,
+    mouse_query: Query<'w, 's, &'static GridPosition, With<Mouse>>,
+    obstacle_query: Query<'w, 's, &'static GridPosition, (With<Obstacle>, Without<ArenaWall>)>,
+    wall_query: Query<'w, 's, &'static GridPosition, With<ArenaWall>>,
+    portal_query: Query<'w, 's, (&'static GridPosition, &'static Portal)>,
+}
+
+impl GameSnapshot<'_, '_> {
+    fn capture(&self) -> SavedGame {
+        let snakes = self
+            .snake_query
+            .iter()
+            .map(|(&player, snake, directions, score, combo, computer)| SavedSnake {
+                player,
+                segments: self.position_query.iter_many(&snake.segments).map(|position| position.0).collect(),
+                heading: directions.heading,
+                queued: directions.queued.iter().copied().collect(),
+                score: score.0,
+                combo: combo.multiplier,
+                combo_window: combo.window.as_ref().map(SavedTimer::new),
+                computer,
+            })
+            .collect();
+
+        SavedGame {
+            game_mode: *self.game_mode,
+            ruleset: *self.ruleset,
+            border_mode: *self.border_mode,
+            speed: self.speed.clone(),
+            seed: self.game_rng.seed,
+            rng_position: self.game_rng.rng.get_word_pos() as u64,
+            snakes,
+            food: self
+                .food_query
+                .iter()
+                .map(|(position, Food(kind), lifetime)| {
+                    (kind.clone(), position.0, lifetime.map(|lifetime| SavedTimer::new(&lifetime.timer)))
+                })
+                .collect(),
+            power_ups: self.power_up_query.iter().map(|(position, &power_up)| (position.0, power_up)).collect(),
+            mice: self.mouse_query.iter().map(|position| position.0).collect(),
+            obstacles: self.obstacle_query.iter().map(|position| position.0).collect(),
+            // Each pair once, from the end that sorts first.
+            portals: self
+                .portal_query
+                .iter()
+                .filter(|(position, portal)| position.0.to_array() < portal.exit.to_array())
+                .map(|(position, portal)| (position.0, portal.exit))
+                .collect(),
+            walled_rings: self.wall_query.iter().map(|position| ring_of(position.0) + 1).max().unwrap_or(0),
+            arena: self.arena.as_ref().map(|arena| (arena.rings, SavedTimer::new(&arena.timer))),
+            match_timer: self.match_timer.as_ref().map(|match_timer| SavedTimer::new(&match_timer.0)),
+            ghosts: self.effects.ghost.iter().map(|(&player, timer)| (player, SavedTimer::new(timer))).collect(),
+            slow: self.effects.slow.as_ref().map(SavedTimer::new),
+        }
+    }
+}
+
+/// Saving the game in progress and picking it back up from the main menu.
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_saved_game)
+            .add_systems(
+                Update,
+                save_key_system.run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))),
+            )
+            // The exit is only sent late in the frame that closes the window.
+            .add_systems(
+                Last,
+                save_on_exit_system.run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))),
+            )
+            .add_systems(
+                OnTransition { exited: GameState::Menu, entered: GameState::Playing },
+                continue_game.run_if(resource_exists::<SavedGame>),
+            );
+    }
+}
+
+fn load_saved_game(mut commands: Commands) {
+    if let Some(saved) = SavedGame::load() {
+        commands.insert_resource(saved);
+    }
+}
+
+fn save_key_system(mut commands: Commands, keys: Res<ButtonInput<KeyCode>>, snapshot: GameSnapshot) {
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if ctrl && keys.just_pressed(KeyCode::KeyS) {
+        let saved = snapshot.capture();
+        saved.save();
+        info!("saved the game to {SAVE_KEY}");
+        commands.insert_resource(saved);
+    }
+}
+
+fn save_on_exit_system(mut exit_events: EventReader<AppExit>, snapshot: GameSnapshot) {
+    if exit_events.read().count() > 0 {
+        snapshot.capture().save();
+    }
+}
+
+/// Lays the saved game back out on the board. It can be continued once; saving again writes a new one.
+#[allow(clippy::too_many_arguments)]
+fn continue_game(
+    mut commands: Commands,
+    saved: Res<SavedGame>,
+    sprites: Res<SnakeSprites>,
+    theme: Res<Theme>,
+    mut game_mode: ResMut<GameMode>,
+    mut ruleset: ResMut<Ruleset>,
+    mut border_mode: ResMut<BorderMode>,
+    mut speed: ResMut<GameSpeed>,
+    mut effects: ResMut<ActiveEffects>,
+) {
+    let palette = theme.palette();
+    *game_mode = saved.game_mode;
+    *ruleset = saved.ruleset;
+    *border_mode = saved.border_mode;
+    *speed = saved.speed.clone();
+    effects.ghost = saved.ghosts.iter().map(|&(player, timer)| (player, timer.restore(TimerMode::Once))).collect();
+    effects.slow = saved.slow.map(|timer| timer.restore(TimerMode::Once));
+
+    let mut game_rng = GameRng::from_seed(saved.seed);
+    game_rng.rng.set_word_pos(u128::from(saved.rng_position));
+    commands.insert_resource(game_rng);
+    commands.insert_resource(MoveTimer(Timer::from_seconds(
+        speed.interval() * effects.interval_scale(),
+        TimerMode::Repeating,
+    )));
+    commands.remove_resource::<TimedResult>();
+
+    for ring in 0..saved.walled_rings {
+        spawn_arena_ring(&mut commands, ring);
+    }
+    match saved.arena {
+        Some((rings, timer)) => commands.insert_resource(Arena { rings, timer: timer.restore(TimerMode::Repeating) }),
+        None => commands.remove_resource::<Arena>(),
+    }
+    match saved.match_timer {
+        Some(timer) => commands.insert_resource(MatchTimer(timer.restore(TimerMode::Once))),
+        None => commands.remove_resource::<MatchTimer>(),
+    }
+
+    let level = Level { name: String::new(), obstacles: saved.obstacles.clone(), portals: saved.portals.clone() };
+    spawn_level(&mut commands, &level, palette);
+
+    for snake in &saved.snakes {
+        let entity = spawn_snake_along(&mut commands, &sprites, palette, snake.player, &snake.segments, snake.heading);
+        commands.entity(entity).insert((
+            DirectionQueue { heading: snake.heading, queued: snake.queued.iter().copied().collect() },
+            Score(snake.score),
+            Combo {
+                multiplier: snake.combo,
+                window: snake.combo_window.map(|timer| timer.restore(TimerMode::Once)),
+            },
+        ));
+        if snake.computer {
+            commands.entity(entity).insert(AiControlled);
+        }
+        if saved.game_mode == GameMode::Coop {
+            commands.entity(entity).insert(SharedControl);
+        }
+    }
+
+    for (kind, position, timer) in &saved.food {
+        let food = place_food(&mut commands, palette, kind, *position);
+        if let Some(timer) = timer {
+            commands.entity(food).insert(FoodLifetime { timer: timer.restore(TimerMode::Once) });
+        }
+    }
+    for &(position, power_up) in &saved.power_ups {
+        place_power_up(&mut commands, position, power_up);
+    }
+    for &position in &saved.mice {
+        place_mouse(&mut commands, position);
+    }
+
+    commands.remove_resource::<SavedGame>();
+    SavedGame::delete();
+}
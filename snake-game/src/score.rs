@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+
+use crate::food::EatEvent;
+use crate::{GameState, StepSet};
+
+/// How long after eating the next food still builds the combo.
+const COMBO_WINDOW: f32 = 3.;
+const COMBO_MAX_MULTIPLIER: u32 = 5;
+
+#[derive(Component, Default)]
+pub struct Score(pub u32);
+
+/// Food eaten in quick succession multiplies its points. The window reopens with every food and
+/// the multiplier drops back to one once it closes.
+#[derive(Component)]
+pub struct Combo {
+    pub multiplier: u32,
+    pub window: Option<Timer>,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self { multiplier: 1, window: None }
+    }
+}
+
+impl Combo {
+    /// Counts a food eaten and returns the multiplier it scores with.
+    fn eat(&mut self) -> u32 {
+        self.multiplier = match self.window {
+            Some(_) => (self.multiplier + 1).min(COMBO_MAX_MULTIPLIER),
+            None => 1,
+        };
+        self.window = Some(Timer::from_seconds(COMBO_WINDOW, TimerMode::Once));
+        self.multiplier
+    }
+}
+
+/// A snake scored points, or lost them, at a cell of the board.
+#[derive(Event)]
+pub struct ScoreEvent {
+    pub snake: Entity,
+    pub position: IVec2,
+    pub points: i32,
+    /// The combo multiplier the points were scored at, already counted in them.
+    pub combo: u32,
+}
+
+/// Every snake's [`Score`] and the [`Combo`] that multiplies what it eats.
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ScoreEvent>()
+            .add_systems(FixedUpdate, eat_score_system.in_set(StepSet::Grow))
+            .add_systems(Update, combo_timer_system.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Scores every food eaten at the eater's combo multiplier.
+fn eat_score_system(
+    mut eat_events: EventReader<EatEvent>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut score_query: Query<(&mut Score, &mut Combo)>,
+) {
+    for event in eat_events.read() {
+        let Ok((mut score, mut combo)) = score_query.get_mut(event.snake) else {
+            continue;
+        };
+
+        let multiplier = combo.eat();
+        let points = event.points * multiplier as i32;
+        score.0 = score.0.saturating_add_signed(points);
+        score_events.send(ScoreEvent { snake: event.snake, position: event.position, points, combo: multiplier });
+    }
+}
+
+fn combo_timer_system(time: Res<Time>, mut combo_query: Query<&mut Combo>) {
+    for mut combo in combo_query.iter_mut() {
+        if combo.window.as_mut().is_some_and(|window| window.tick(time.delta()).finished()) {
+            *combo = Combo::default();
+        }
+    }
+}
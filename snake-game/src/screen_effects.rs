@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use rand::Rng;
+
+use crate::food::BonusEatenEvent;
+use crate::theme::Theme;
+use crate::GameState;
+
+const DEATH_SHAKE_STRENGTH: f32 = 8.;
+const DEATH_SHAKE_DURATION: f32 = 0.4;
+const DEATH_FLASH_COLOR: Color = Color::srgba(0.8, 0.05, 0.05, 0.7);
+const DEATH_FLASH_DURATION: f32 = 0.5;
+
+const BONUS_SHAKE_STRENGTH: f32 = 2.;
+const BONUS_SHAKE_DURATION: f32 = 0.15;
+const BONUS_FLASH_ALPHA: f32 = 0.3;
+const BONUS_FLASH_DURATION: f32 = 0.25;
+
+/// How far in from the window's edges a [`Flash`] reaches at full strength.
+const FLASH_EDGE_WIDTH: f32 = 60.;
+/// How strongly a [`Flash`] tints the middle of the screen, relative to its edges.
+const FLASH_FILL: f32 = 0.2;
+
+/// Jolts the camera it is put on by up to `strength` pixels in a random direction every frame,
+/// easing off until its timer runs out.
+#[derive(Component)]
+pub struct Shake {
+    strength: f32,
+    timer: Timer,
+    /// Taken back off at the start of the next frame, so the camera follows as if unshaken.
+    offset: Vec2,
+}
+
+impl Shake {
+    pub fn new(strength: f32, duration: f32) -> Self {
+        Self { strength, timer: Timer::from_seconds(duration, TimerMode::Once), offset: Vec2::ZERO }
+    }
+}
+
+/// Tints the screen in `color`, strongest along the edges like a vignette, fading out until its
+/// timer runs out. Spawned with [`spawn_flash`].
+#[derive(Component)]
+pub struct Flash {
+    color: Color,
+    timer: Timer,
+}
+
+impl Flash {
+    pub fn new(color: Color, duration: f32) -> Self {
+        Self { color, timer: Timer::from_seconds(duration, TimerMode::Once) }
+    }
+}
+
+/// Camera shakes and screen flashes, strong when a snake dies and gentle for bonus food.
+pub struct ScreenEffectsPlugin;
+
+impl Plugin for ScreenEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Dying), death_effects)
+            .add_systems(Update, (bonus_effects_system, flash_system))
+            .add_systems(PreUpdate, unshake_system)
+            .add_systems(PostUpdate, shake_system.before(TransformSystem::TransformPropagate));
+    }
+}
+
+/// Covers the window with `flash`, above the rest of the UI.
+pub fn spawn_flash(commands: &mut Commands, flash: Flash) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            border: UiRect::all(Val::Px(FLASH_EDGE_WIDTH)),
+            ..default()
+        },
+        BorderColor(flash.color),
+        BackgroundColor(flash.color.with_alpha(flash.color.alpha() * FLASH_FILL)),
+        GlobalZIndex(1),
+        flash,
+    ));
+}
+
+fn shake_main_camera(commands: &mut Commands, camera_query: &Query<Entity, With<IsDefaultUiCamera>>, shake: Shake) {
+    if let Ok(camera) = camera_query.get_single() {
+        commands.entity(camera).insert(shake);
+    }
+}
+
+fn death_effects(mut commands: Commands, camera_query: Query<Entity, With<IsDefaultUiCamera>>) {
+    shake_main_camera(&mut commands, &camera_query, Shake::new(DEATH_SHAKE_STRENGTH, DEATH_SHAKE_DURATION));
+    spawn_flash(&mut commands, Flash::new(DEATH_FLASH_COLOR, DEATH_FLASH_DURATION));
+}
+
+fn bonus_effects_system(
+    mut commands: Commands,
+    mut bonus_events: EventReader<BonusEatenEvent>,
+    theme: Res<Theme>,
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+) {
+    if bonus_events.read().count() == 0 {
+        return;
+    }
+
+    shake_main_camera(&mut commands, &camera_query, Shake::new(BONUS_SHAKE_STRENGTH, BONUS_SHAKE_DURATION));
+    let color = theme.palette().bonus_food.with_alpha(BONUS_FLASH_ALPHA);
+    spawn_flash(&mut commands, Flash::new(color, BONUS_FLASH_DURATION));
+}
+
+fn unshake_system(mut camera_query: Query<(&mut Transform, &mut Shake)>) {
+    for (mut transform, mut shake) in camera_query.iter_mut() {
+        transform.translation -= shake.offset.extend(0.);
+        shake.offset = Vec2::ZERO;
+    }
+}
+
+fn shake_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut camera_query: Query<(Entity, &mut Transform, &mut Shake)>,
+) {
+    // Shaking is only for show; drawing it from the GameRng would make replays depend on it.
+    let mut rng = rand::rng();
+
+    for (entity, mut transform, mut shake) in camera_query.iter_mut() {
+        if shake.timer.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Shake>();
+            continue;
+        }
+
+        let angle = rng.random_range(0.0..std::f32::consts::TAU);
+        shake.offset = Vec2::from_angle(angle) * shake.strength * shake.timer.fraction_remaining();
+        transform.translation += shake.offset.extend(0.);
+    }
+}
+
+fn flash_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut Flash, &mut BorderColor, &mut BackgroundColor)>,
+) {
+    for (entity, mut flash, mut border, mut background) in flash_query.iter_mut() {
+        if flash.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let alpha = flash.color.alpha() * flash.timer.fraction_remaining();
+        border.0 = flash.color.with_alpha(alpha);
+        background.0 = flash.color.with_alpha(alpha * FLASH_FILL);
+    }
+}
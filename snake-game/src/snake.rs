@@ -0,0 +1,1017 @@
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::food::{ActiveEffects, EatEvent, Food, Mouse, PowerUp};
+use crate::grid::{
+    grid_to_world, grid_transform_system, step_between, BorderMode, GridPosition, PreviousGridPosition, DIRECTIONS,
+    GRID_HEIGHT, GRID_WIDTH,
+};
+use crate::level::Obstacle;
+use crate::score::{Combo, Score};
+use crate::theme::{Palette, Theme};
+use crate::{GameState, StepSet};
+
+const MOVE_INTERVAL: f32 = 0.05;
+const MIN_MOVE_INTERVAL: f32 = 0.025;
+const SPEED_STEP: f32 = 0.02;
+const INPUT_BUFFER_SIZE: usize = 2;
+/// How far a gamepad stick has to lean before it steers.
+const STICK_THRESHOLD: f32 = 0.5;
+/// Shortest drag across a touch screen that counts as a swipe, in logical pixels.
+const SWIPE_MIN_DISTANCE: f32 = 30.;
+
+const SNAKE_SIZE: Vec2 = Vec2::new(10., 10.);
+const SNAKE_TEXTURE: &str = "textures/snake.png";
+const SNAKE_TILE_SIZE: UVec2 = UVec2::splat(16);
+pub const SNAKE_START_POSITION: IVec2 = IVec2::new(GRID_WIDTH / 2, GRID_HEIGHT / 2);
+/// Where each player's head starts in a two-player game, and the way it faces.
+pub const VERSUS_START_POSITIONS: [(IVec2, IVec2); 2] = [
+    (IVec2::new(GRID_WIDTH / 4, GRID_HEIGHT * 2 / 3), IVec2::X),
+    (IVec2::new(GRID_WIDTH * 3 / 4, GRID_HEIGHT / 3), IVec2::NEG_X),
+];
+const SNAKE_LENGTH: i32 = 3;
+
+const DEATH_FLASH_DURATION: f32 = 0.5;
+/// Times per second a dead snake's head switches between flashing and its own color.
+const DEATH_FLASH_RATE: f32 = 12.;
+const DEATH_FLASH_COLOR: Color = Color::WHITE;
+/// How long the longest dead snake takes to start fading from tail to head.
+const DEATH_FADE_DURATION: f32 = 1.;
+const SEGMENT_FADE_DURATION: f32 = 0.25;
+/// The head's scale right after it eats, along and across its heading.
+const HEAD_SQUASH: Vec3 = Vec3::new(0.8, 1.2, 1.);
+/// How much darker a [`SnakeColoring::Gradient`] snake's tail is than its head.
+const GRADIENT_TAIL_DARKENING: f32 = 0.3;
+/// Degrees the hue turns from one segment of a [`SnakeColoring::Rainbow`] snake to the next.
+const RAINBOW_HUE_STEP: f32 = 15.;
+
+#[derive(Component)]
+pub struct SnakeSegment;
+
+/// Tiles of the snake texture, in atlas order. Each is drawn for a snake heading right, and the
+/// corner joins the right and top edges.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SegmentTile {
+    Head,
+    Straight,
+    Corner,
+    Tail,
+}
+
+/// How the snakes are shaded from head to tail, picked in the settings. Each starts from its
+/// player's color at the head, so the head always stands out.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum SnakeColoring {
+    Solid,
+    /// Darkening towards the tail, so a longer snake shows a longer fade.
+    #[default]
+    Gradient,
+    /// Running through the hues along the body; unlocked by an achievement.
+    Rainbow,
+}
+
+impl SnakeColoring {
+    pub fn name(self) -> &'static str {
+        match self {
+            SnakeColoring::Solid => "Solid",
+            SnakeColoring::Gradient => "Gradient",
+            SnakeColoring::Rainbow => "Rainbow",
+        }
+    }
+
+    pub fn next(self, rainbow_unlocked: bool) -> Self {
+        match self {
+            SnakeColoring::Solid => SnakeColoring::Gradient,
+            SnakeColoring::Gradient if rainbow_unlocked => SnakeColoring::Rainbow,
+            SnakeColoring::Gradient | SnakeColoring::Rainbow => SnakeColoring::Solid,
+        }
+    }
+
+    /// The color of the segment `index` places back from the head of a snake `length` long.
+    fn segment_color(self, head: Color, index: usize, length: usize) -> Color {
+        match self {
+            SnakeColoring::Solid => head,
+            SnakeColoring::Gradient => {
+                let toward_tail = index as f32 / length.saturating_sub(1).max(1) as f32;
+                head.darker(GRADIENT_TAIL_DARKENING * toward_tail)
+            }
+            SnakeColoring::Rainbow => head.rotate_hue(index as f32 * RAINBOW_HUE_STEP),
+        }
+    }
+}
+
+/// The snake texture, shared by every segment and tinted per player.
+#[derive(Resource)]
+pub struct SnakeSprites {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+}
+
+/// Plays out the deaths that ended a round before the game over screen shows: the heads flash,
+/// then the segments fade out one by one from the tail.
+#[derive(Resource)]
+struct DeathAnimation {
+    flash: Timer,
+    /// Starts one more segment of every dead snake fading each time it finishes.
+    fade_step: Timer,
+}
+
+/// A segment of a dead snake fading away. It stays on the board, so the snake keeps its length
+/// for the results screen.
+#[derive(Component)]
+struct FadingSegment {
+    timer: Timer,
+    color: Color,
+}
+
+/// Eases an entity's scale from `from` back to normal size over `timer`: a freshly grown segment
+/// swelling up from nothing, or a head springing back from a squash.
+#[derive(Component)]
+struct ScaleTween {
+    from: Vec3,
+    timer: Timer,
+}
+
+/// The direction the snake last stepped in, and the turns buffered for the next steps.
+#[derive(Component)]
+pub struct DirectionQueue {
+    pub heading: IVec2,
+    pub queued: VecDeque<IVec2>,
+}
+
+impl DirectionQueue {
+    pub fn new(heading: IVec2) -> Self {
+        Self { heading, queued: VecDeque::with_capacity(INPUT_BUFFER_SIZE) }
+    }
+
+    /// The way the snake will be heading once every buffered turn is taken.
+    fn last(&self) -> IVec2 {
+        self.queued.back().copied().unwrap_or(self.heading)
+    }
+
+    /// Buffers a turn unless the queue is full or it would repeat or reverse the direction before it.
+    pub fn push(&mut self, direction: IVec2) {
+        let last = self.last();
+        if self.queued.len() < INPUT_BUFFER_SIZE && direction != last && direction != -last {
+            self.queued.push_back(direction);
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct MoveTimer(pub Timer);
+
+/// How fast the snake moves; every food eaten raises the level until the interval bottoms out.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct GameSpeed {
+    pub level: u32,
+    /// Seconds per step at level zero.
+    pub base_interval: f32,
+    /// Fraction of the interval shaved off per level.
+    pub step: f32,
+    /// Shortest interval the curve can reach.
+    pub min_interval: f32,
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self {
+            level: 0,
+            base_interval: MOVE_INTERVAL,
+            step: SPEED_STEP,
+            min_interval: MIN_MOVE_INTERVAL,
+        }
+    }
+}
+
+impl GameSpeed {
+    pub fn interval(&self) -> f32 {
+        (self.base_interval * (1. - self.step).powi(self.level as i32)).max(self.min_interval)
+    }
+
+    pub fn speed_up(&mut self) {
+        if self.interval() > self.min_interval {
+            self.level += 1;
+        }
+    }
+}
+
+/// A snake's segments from head to tail. The same entity carries its [`Player`], [`Score`]
+/// and [`DirectionQueue`].
+#[derive(Component)]
+pub struct Snake {
+    pub segments: Vec<Entity>,
+    /// Cleared by [`death_system`]; the round ends once any snake dies.
+    pub alive: bool,
+}
+
+impl Snake {
+    pub fn head(&self) -> Entity {
+        self.segments[0]
+    }
+
+    /// Despawns up to `count` segments from the tail, never the head.
+    pub fn drop_tail(&mut self, commands: &mut Commands, count: usize) {
+        let keep = self.segments.len().saturating_sub(count).max(1);
+        for segment in self.segments.drain(keep..) {
+            commands.entity(segment).despawn();
+        }
+    }
+}
+
+/// Who steers a snake, and with which keys.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub fn name(self) -> &'static str {
+        match self {
+            Player::One => "Player 1",
+            Player::Two => "Player 2",
+        }
+    }
+
+    pub fn color(self, palette: &Palette) -> Color {
+        palette.snakes[self as usize]
+    }
+
+    pub fn bindings(self) -> [(KeyCode, IVec2); 4] {
+        match self {
+            Player::One => [
+                (KeyCode::ArrowUp, IVec2::Y),
+                (KeyCode::ArrowDown, IVec2::NEG_Y),
+                (KeyCode::ArrowLeft, IVec2::NEG_X),
+                (KeyCode::ArrowRight, IVec2::X),
+            ],
+            Player::Two => [
+                (KeyCode::KeyW, IVec2::Y),
+                (KeyCode::KeyS, IVec2::NEG_Y),
+                (KeyCode::KeyA, IVec2::NEG_X),
+                (KeyCode::KeyD, IVec2::X),
+            ],
+        }
+    }
+}
+
+/// Marks a snake steered by [`ai_steering_system`] instead of the keyboard.
+#[derive(Component)]
+pub struct AiControlled;
+
+/// Marks the co-op snake, which player one turns left and right and player two turns up and down.
+#[derive(Component)]
+pub struct SharedControl;
+
+impl SharedControl {
+    /// The axis `player` steers a shared snake along.
+    fn axis(player: Player) -> IVec2 {
+        match player {
+            Player::One => IVec2::X,
+            Player::Two => IVec2::Y,
+        }
+    }
+
+    fn steers(player: Player, direction: IVec2) -> bool {
+        direction.dot(Self::axis(player)) != 0
+    }
+}
+
+/// Asks a snake to turn. Keyboard, gamepad, touch and the computer player all steer by sending
+/// these, and [`turn_command_system`] buffers them for the next steps.
+#[derive(Event)]
+struct TurnCommand {
+    snake: Entity,
+    direction: IVec2,
+}
+
+/// A snake grew by a segment.
+#[derive(Event)]
+struct GrowthEvent {
+    snake: Entity,
+    segment: Entity,
+}
+
+/// A snake ran into something deadly: the border, an obstacle, a body, or poison it was too short
+/// to survive.
+#[derive(Event)]
+pub struct CollisionEvent {
+    pub snake: Entity,
+}
+
+/// The snakes: steering them, stepping them across the grid, growing them and killing them off.
+pub struct SnakePlugin;
+
+impl Plugin for SnakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameSpeed>()
+            .init_resource::<SnakeColoring>()
+            .add_event::<TurnCommand>()
+            .add_event::<GrowthEvent>()
+            .add_event::<CollisionEvent>()
+            .add_systems(
+                Update,
+                (keyboard_turn_system, gamepad_turn_system, swipe_turn_system).run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (move_timer_system, ai_steering_system.run_if(step_due), turn_command_system)
+                    .chain()
+                    .in_set(StepSet::Input),
+            )
+            .add_systems(
+                FixedUpdate,
+                (snake_movement_system.run_if(step_due), border_system).chain().in_set(StepSet::Move),
+            )
+            .add_systems(FixedUpdate, (obstacle_collision_system, body_collision_system).in_set(StepSet::Collide))
+            .add_systems(
+                FixedUpdate,
+                (growth_system, (speed_up_system, growth_tween_system)).chain().in_set(StepSet::Grow),
+            )
+            .add_systems(FixedUpdate, death_system.in_set(StepSet::Resolve))
+            .add_systems(
+                Update,
+                (
+                    segment_sprite_system,
+                    segment_color_system,
+                    scale_tween_system,
+                    // Overrides the snapped translation of segments that just moved.
+                    segment_interpolation_system.after(grid_transform_system),
+                    game_speed_system.run_if(resource_changed::<GameSpeed>.or(resource_changed::<ActiveEffects>)),
+                ),
+            )
+            .add_systems(OnEnter(GameState::Dying), start_death_animation)
+            .add_systems(
+                Update,
+                (death_animation_system, segment_fade_system).run_if(in_state(GameState::Dying)),
+            );
+    }
+}
+
+/// Loads the segment tiles; left to the windowed app, as headless games have nothing to draw them with.
+pub fn load_snake_sprites(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    commands.insert_resource(SnakeSprites {
+        image: asset_server.load(SNAKE_TEXTURE),
+        layout: layouts.add(TextureAtlasLayout::from_grid(SNAKE_TILE_SIZE, 4, 1, None, None)),
+    });
+}
+
+/// Spawns a snake stretched out behind `position`, about to move towards `heading`.
+pub fn spawn_snake(
+    commands: &mut Commands,
+    sprites: &SnakeSprites,
+    palette: &Palette,
+    player: Player,
+    position: IVec2,
+    heading: IVec2,
+) -> Entity {
+    let body: Vec<IVec2> = (0..SNAKE_LENGTH).map(|i| position - heading * i).collect();
+    spawn_snake_along(commands, sprites, palette, player, &body, heading)
+}
+
+/// Spawns a snake over `body`, head first, about to move towards `heading`.
+pub fn spawn_snake_along(
+    commands: &mut Commands,
+    sprites: &SnakeSprites,
+    palette: &Palette,
+    player: Player,
+    body: &[IVec2],
+    heading: IVec2,
+) -> Entity {
+    let segments = body
+        .iter()
+        .map(|&position| spawn_segment(commands, sprites, palette, player, position))
+        .collect();
+
+    commands
+        .spawn((
+            Snake { segments, alive: true },
+            player,
+            Score::default(),
+            Combo::default(),
+            DirectionQueue::new(heading),
+        ))
+        .id()
+}
+
+fn spawn_segment(
+    commands: &mut Commands,
+    sprites: &SnakeSprites,
+    palette: &Palette,
+    player: Player,
+    position: IVec2,
+) -> Entity {
+    let atlas = TextureAtlas {
+        layout: sprites.layout.clone(),
+        index: SegmentTile::Straight as usize,
+    };
+
+    commands
+        .spawn((
+            Sprite {
+                color: player.color(palette),
+                custom_size: Some(SNAKE_SIZE),
+                ..Sprite::from_atlas_image(sprites.image.clone(), atlas)
+            },
+            Transform::default(),
+            GridPosition(position),
+            PreviousGridPosition(position),
+            SnakeSegment,
+        ))
+        .id()
+}
+
+fn keyboard_turn_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    snake_query: Query<(Entity, &Player, Has<SharedControl>), Without<AiControlled>>,
+    mut turn_commands: EventWriter<TurnCommand>,
+) {
+    // Ctrl+S saves rather than steering player two.
+    if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    for (snake, &player, shared) in snake_query.iter() {
+        let controllers = if shared { vec![Player::One, Player::Two] } else { vec![player] };
+        for controller in controllers {
+            for (key, direction) in controller.bindings() {
+                if keys.just_pressed(key) && (!shared || SharedControl::steers(controller, direction)) {
+                    turn_commands.send(TurnCommand { snake, direction });
+                }
+            }
+        }
+    }
+}
+
+/// Steers with the d-pad or left stick. The first gamepad connected plays as player one and the
+/// second as player two.
+fn gamepad_turn_system(
+    gamepad_query: Query<(Entity, &Gamepad)>,
+    snake_query: Query<(Entity, &Player, Has<SharedControl>), Without<AiControlled>>,
+    // The way each stick leaned last frame, so holding it turns only once.
+    mut stick_directions: Local<HashMap<Entity, IVec2>>,
+    mut turn_commands: EventWriter<TurnCommand>,
+) {
+    const DPAD: [(GamepadButton, IVec2); 4] = [
+        (GamepadButton::DPadUp, IVec2::Y),
+        (GamepadButton::DPadDown, IVec2::NEG_Y),
+        (GamepadButton::DPadLeft, IVec2::NEG_X),
+        (GamepadButton::DPadRight, IVec2::X),
+    ];
+
+    let mut gamepads: Vec<_> = gamepad_query.iter().collect();
+    gamepads.sort_by_key(|(entity, _)| *entity);
+
+    for ((gamepad_entity, gamepad), player) in gamepads.into_iter().zip([Player::One, Player::Two]) {
+        let Some((snake, _, shared)) =
+            snake_query.iter().find(|&(_, &snake_player, shared)| shared || snake_player == player)
+        else {
+            continue;
+        };
+
+        for (button, direction) in DPAD {
+            if gamepad.just_pressed(button) && (!shared || SharedControl::steers(player, direction)) {
+                turn_commands.send(TurnCommand { snake, direction });
+            }
+        }
+
+        let mut stick = gamepad.left_stick();
+        if shared {
+            stick *= SharedControl::axis(player).as_vec2();
+        }
+        let leaning = (stick.length() > STICK_THRESHOLD).then(|| dominant_direction(stick));
+        let previous = match leaning {
+            Some(direction) => stick_directions.insert(gamepad_entity, direction),
+            None => stick_directions.remove(&gamepad_entity),
+        };
+        if let Some(direction) = leaning.filter(|&direction| previous != Some(direction)) {
+            turn_commands.send(TurnCommand { snake, direction });
+        }
+    }
+}
+
+/// Turns player one's snake towards where a finger swiped across a touch screen.
+fn swipe_turn_system(
+    touches: Res<Touches>,
+    snake_query: Query<(Entity, &Player), Without<AiControlled>>,
+    mut turn_commands: EventWriter<TurnCommand>,
+) {
+    let Some((snake, _)) = snake_query.iter().find(|(_, &player)| player == Player::One) else {
+        return;
+    };
+
+    for touch in touches.iter_just_released() {
+        // Screen coordinates grow downwards, the grid grows upwards.
+        let swipe = (touch.position() - touch.start_position()) * Vec2::new(1., -1.);
+        if swipe.length() >= SWIPE_MIN_DISTANCE {
+            turn_commands.send(TurnCommand { snake, direction: dominant_direction(swipe) });
+        }
+    }
+}
+
+/// The grid direction closest to `vector`.
+fn dominant_direction(vector: Vec2) -> IVec2 {
+    if vector.x.abs() > vector.y.abs() {
+        IVec2::new(vector.x.signum() as i32, 0)
+    } else {
+        IVec2::new(0, vector.y.signum() as i32)
+    }
+}
+
+/// Buffers each snake's turns. A shared snake's are merged first: each player's latest turn this
+/// frame stands, and when both turned, whichever can be taken from the way the snake is heading
+/// goes first so the other can follow it.
+fn turn_command_system(
+    mut turn_commands: EventReader<TurnCommand>,
+    mut snake_query: Query<(&mut DirectionQueue, Has<SharedControl>)>,
+) {
+    let mut shared_turns: HashMap<Entity, Vec<IVec2>> = HashMap::default();
+
+    for command in turn_commands.read() {
+        let Ok((mut directions, shared)) = snake_query.get_mut(command.snake) else {
+            continue;
+        };
+
+        if shared {
+            let turns = shared_turns.entry(command.snake).or_default();
+            turns.retain(|turn| turn.dot(command.direction) == 0);
+            turns.push(command.direction);
+        } else {
+            directions.push(command.direction);
+        }
+    }
+
+    for (snake, mut turns) in shared_turns {
+        let Ok((mut directions, _)) = snake_query.get_mut(snake) else {
+            continue;
+        };
+
+        let last = directions.last();
+        turns.sort_by_key(|turn| turn.dot(last) != 0);
+        for turn in turns {
+            directions.push(turn);
+        }
+    }
+}
+
+fn move_timer_system(time: Res<Time>, mut timer: ResMut<MoveTimer>) {
+    timer.0.tick(time.delta());
+}
+
+/// Whether the snakes take a grid step this frame.
+pub fn step_due(timer: Res<MoveTimer>) -> bool {
+    timer.0.just_finished()
+}
+
+/// Picks each computer snake's next turn, once per grid step so it moves at the player's pace.
+#[allow(clippy::type_complexity)]
+fn ai_steering_system(
+    border_mode: Res<BorderMode>,
+    ai_query: Query<(Entity, &Snake, &DirectionQueue), With<AiControlled>>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+    obstacle_query: Query<&GridPosition, With<Obstacle>>,
+    food_query: Query<(&GridPosition, &Food)>,
+    target_query: Query<&GridPosition, Or<(With<PowerUp>, With<Mouse>)>>,
+    mut turn_commands: EventWriter<TurnCommand>,
+) {
+    let (harmful, edible): (Vec<_>, Vec<_>) = food_query.iter().partition(|(_, Food(kind))| kind.is_harmful());
+    let blocked: HashSet<IVec2> = segment_query
+        .iter()
+        .chain(obstacle_query.iter())
+        .chain(harmful.into_iter().map(|(position, _)| position))
+        .map(|position| position.0)
+        .collect();
+    let targets: HashSet<IVec2> = target_query
+        .iter()
+        .chain(edible.into_iter().map(|(position, _)| position))
+        .map(|position| position.0)
+        .collect();
+
+    for (entity, snake, directions) in ai_query.iter() {
+        let Ok(head_position) = segment_query.get(snake.head()) else {
+            continue;
+        };
+
+        // One turn per step means nothing is ever left queued from the previous plan.
+        let turn = plan_ai_turn(head_position.0, directions.heading, &blocked, &targets, *border_mode);
+        if let Some(direction) = turn {
+            turn_commands.send(TurnCommand { snake: entity, direction });
+        }
+    }
+}
+
+/// Searches breadth-first from `head` and returns the first step of the shortest path to a
+/// target. With no target in reach it heads for the biggest open area instead, which keeps the
+/// snake out of dead ends. `None` means every neighboring cell is blocked.
+fn plan_ai_turn(
+    head: IVec2,
+    heading: IVec2,
+    blocked: &HashSet<IVec2>,
+    targets: &HashSet<IVec2>,
+    border_mode: BorderMode,
+) -> Option<IVec2> {
+    let grid_size = IVec2::new(GRID_WIDTH, GRID_HEIGHT);
+    let neighbor = |cell: IVec2, direction: IVec2| {
+        let next = cell + direction;
+        match border_mode {
+            BorderMode::Wrap => Some(next.rem_euclid(grid_size)),
+            BorderMode::Solid => (next.cmpge(IVec2::ZERO).all() && next.cmplt(grid_size).all()).then_some(next),
+        }
+    };
+
+    // Going straight comes first, so ties between equally short paths don't make it weave.
+    let directions = std::iter::once(heading)
+        .chain(DIRECTIONS.into_iter().filter(|&direction| direction != heading));
+
+    // Every reached cell remembers the first step that led to it.
+    let mut first_steps: HashMap<IVec2, IVec2> = HashMap::new();
+    let mut frontier = VecDeque::new();
+    for direction in directions.clone() {
+        if let Some(cell) = neighbor(head, direction).filter(|cell| !blocked.contains(cell)) {
+            first_steps.insert(cell, direction);
+            frontier.push_back(cell);
+        }
+    }
+
+    while let Some(cell) = frontier.pop_front() {
+        let first_step = first_steps[&cell];
+        if targets.contains(&cell) {
+            return Some(first_step);
+        }
+
+        for direction in directions.clone() {
+            if let Some(next) = neighbor(cell, direction) {
+                if !blocked.contains(&next) && !first_steps.contains_key(&next) {
+                    first_steps.insert(next, first_step);
+                    frontier.push_back(next);
+                }
+            }
+        }
+    }
+
+    let mut area: HashMap<IVec2, usize> = HashMap::new();
+    for &first_step in first_steps.values() {
+        *area.entry(first_step).or_default() += 1;
+    }
+    directions
+        .filter(|direction| area.contains_key(direction))
+        .min_by_key(|direction| Reverse(area[direction]))
+}
+
+fn snake_movement_system(
+    mut snake_query: Query<(&Snake, &mut DirectionQueue)>,
+    mut segment_query: Query<(&mut GridPosition, &mut PreviousGridPosition), With<SnakeSegment>>,
+) {
+    for (snake, mut directions) in snake_query.iter_mut() {
+        if let Some(direction) = directions.queued.pop_front() {
+            directions.heading = direction;
+        }
+
+        let previous_positions: Vec<IVec2> = snake.segments
+            .iter()
+            .filter_map(|&entity| segment_query.get(entity).ok())
+            .map(|(position, _)| position.0)
+            .collect();
+
+        for (i, &entity) in snake.segments.iter().enumerate() {
+            if let Ok((mut position, mut previous)) = segment_query.get_mut(entity) {
+                previous.0 = position.0;
+                position.0 = match i {
+                    0 => position.0 + directions.heading,
+                    _ => previous_positions[i - 1],
+                };
+            }
+        }
+    }
+}
+
+pub fn border_system(
+    border_mode: Res<BorderMode>,
+    snake_query: Query<(Entity, &Snake)>,
+    mut segment_query: Query<&mut GridPosition, With<SnakeSegment>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+) {
+    let grid_size = IVec2::new(GRID_WIDTH, GRID_HEIGHT);
+
+    for (entity, snake) in snake_query.iter() {
+        let Ok(mut head_position) = segment_query.get_mut(snake.head()) else {
+            continue;
+        };
+
+        if head_position.0.cmpge(IVec2::ZERO).all() && head_position.0.cmplt(grid_size).all() {
+            continue;
+        }
+
+        match *border_mode {
+            BorderMode::Solid => {
+                collision_events.send(CollisionEvent { snake: entity });
+            }
+            BorderMode::Wrap => head_position.0 = head_position.0.rem_euclid(grid_size),
+        }
+    }
+}
+
+fn obstacle_collision_system(
+    snake_query: Query<(Entity, &Snake)>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+    obstacle_query: Query<&GridPosition, With<Obstacle>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+) {
+    for (entity, snake) in snake_query.iter() {
+        let Ok(head_position) = segment_query.get(snake.head()) else {
+            continue;
+        };
+
+        if obstacle_query.iter().any(|position| position == head_position) {
+            collision_events.send(CollisionEvent { snake: entity });
+        }
+    }
+}
+
+/// Adds a segment to every snake that ate. The new segment waits on the tail's cell and separates
+/// from it on the next step.
+fn growth_system(
+    mut commands: Commands,
+    mut eat_events: EventReader<EatEvent>,
+    mut growth_events: EventWriter<GrowthEvent>,
+    sprites: Res<SnakeSprites>,
+    theme: Res<Theme>,
+    mut snake_query: Query<(&Player, &mut Snake)>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+) {
+    for event in eat_events.read() {
+        let Ok((&player, mut snake)) = snake_query.get_mut(event.snake) else {
+            continue;
+        };
+
+        if let Some(tail_position) = snake.segments.last().and_then(|&tail| segment_query.get(tail).ok()) {
+            let new_segment = spawn_segment(&mut commands, &sprites, theme.palette(), player, tail_position.0);
+            snake.segments.push(new_segment);
+            growth_events.send(GrowthEvent { snake: event.snake, segment: new_segment });
+        }
+    }
+}
+
+fn speed_up_system(mut growth_events: EventReader<GrowthEvent>, mut speed: ResMut<GameSpeed>) {
+    for _ in growth_events.read() {
+        speed.speed_up();
+    }
+}
+
+/// Swells each new segment up from nothing over the next step, rather than popping it in on the
+/// tail, and squashes the head that ate.
+fn growth_tween_system(
+    mut commands: Commands,
+    mut growth_events: EventReader<GrowthEvent>,
+    timer: Option<Res<MoveTimer>>,
+    snake_query: Query<&Snake>,
+) {
+    let duration = timer.map_or(MOVE_INTERVAL, |timer| timer.0.duration().as_secs_f32());
+
+    for event in growth_events.read() {
+        commands.entity(event.segment).insert(ScaleTween {
+            from: Vec3::ZERO,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+        });
+        if let Ok(snake) = snake_query.get(event.snake) {
+            commands.entity(snake.head()).insert(ScaleTween {
+                from: HEAD_SQUASH,
+                timer: Timer::from_seconds(duration, TimerMode::Once),
+            });
+        }
+    }
+}
+
+fn scale_tween_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut ScaleTween, &mut Transform)>,
+) {
+    for (entity, mut tween, mut transform) in query.iter_mut() {
+        if tween.timer.tick(time.delta()).finished() {
+            transform.scale = Vec3::ONE;
+            commands.entity(entity).remove::<ScaleTween>();
+            continue;
+        }
+
+        transform.scale = tween.from.lerp(Vec3::ONE, tween.timer.fraction());
+    }
+}
+
+/// Reports a snake whose head runs into any segment, its own or another snake's. Two heads meeting
+/// collide both. A ghost snake passes through its own body.
+fn body_collision_system(
+    effects: Res<ActiveEffects>,
+    snake_query: Query<(Entity, &Player, &Snake)>,
+    segment_query: Query<(Entity, &GridPosition), With<SnakeSegment>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+) {
+    for (entity, &player, snake) in snake_query.iter() {
+        let head = snake.head();
+        let Ok((_, head_position)) = segment_query.get(head) else {
+            continue;
+        };
+
+        let ghost = effects.is_ghost(player);
+        let hit = segment_query.iter().any(|(segment, position)| {
+            segment != head && position == head_position && !(ghost && snake.segments.contains(&segment))
+        });
+        if hit {
+            collision_events.send(CollisionEvent { snake: entity });
+        }
+    }
+}
+
+pub fn death_system(mut collision_events: EventReader<CollisionEvent>, mut snake_query: Query<&mut Snake>) {
+    for event in collision_events.read() {
+        if let Ok(mut snake) = snake_query.get_mut(event.snake) {
+            snake.alive = false;
+        }
+    }
+}
+
+fn start_death_animation(mut commands: Commands, snake_query: Query<&Snake>) {
+    let longest = snake_query
+        .iter()
+        .filter(|snake| !snake.alive)
+        .map(|snake| snake.segments.len())
+        .max()
+        .unwrap_or(1);
+
+    commands.insert_resource(DeathAnimation {
+        flash: Timer::from_seconds(DEATH_FLASH_DURATION, TimerMode::Once),
+        fade_step: Timer::from_seconds(DEATH_FADE_DURATION / longest as f32, TimerMode::Repeating),
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn death_animation_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    theme: Res<Theme>,
+    mut animation: ResMut<DeathAnimation>,
+    snake_query: Query<(&Player, &Snake)>,
+    // Segments that haven't started fading yet.
+    mut sprite_query: Query<&mut Sprite, (With<SnakeSegment>, Without<FadingSegment>)>,
+    fading_query: Query<&FadingSegment>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let dead_snakes = || snake_query.iter().filter(|(_, snake)| !snake.alive);
+
+    if !animation.flash.tick(time.delta()).finished() {
+        let lit = ((animation.flash.elapsed_secs() * DEATH_FLASH_RATE) as u32).is_multiple_of(2);
+        for (player, snake) in dead_snakes() {
+            if let Ok(mut sprite) = sprite_query.get_mut(snake.head()) {
+                sprite.color = if lit { DEATH_FLASH_COLOR } else { player.color(theme.palette()) };
+            }
+        }
+        return;
+    }
+
+    let steps = animation.fade_step.tick(time.delta()).times_finished_this_tick() as usize;
+    for (player, snake) in dead_snakes() {
+        let waiting = snake.segments.iter().rev().filter(|&&segment| sprite_query.contains(segment));
+        for &segment in waiting.take(steps) {
+            let color = sprite_query.get(segment).map_or(player.color(theme.palette()), |sprite| sprite.color);
+            commands.entity(segment).insert(FadingSegment {
+                timer: Timer::from_seconds(SEGMENT_FADE_DURATION, TimerMode::Once),
+                color,
+            });
+        }
+    }
+
+    let all_started = dead_snakes()
+        .all(|(_, snake)| !snake.segments.iter().any(|&segment| sprite_query.contains(segment)));
+    if all_started && fading_query.iter().all(|fading| fading.timer.finished()) {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn segment_fade_system(time: Res<Time>, mut query: Query<(&mut FadingSegment, &mut Sprite)>) {
+    for (mut fading, mut sprite) in query.iter_mut() {
+        fading.timer.tick(time.delta());
+        sprite.color = fading.color.with_alpha(fading.timer.fraction_remaining());
+    }
+}
+
+/// Keeps the step timer in line with the speed curve and slow time, including after the curve
+/// itself is tuned.
+fn game_speed_system(speed: Res<GameSpeed>, effects: Res<ActiveEffects>, timer: Option<ResMut<MoveTimer>>) {
+    if let Some(mut timer) = timer {
+        timer.0.set_duration(Duration::from_secs_f32(speed.interval() * effects.interval_scale()));
+    }
+}
+
+/// Shades every segment for its place along its snake, whenever a snake grows or shrinks.
+fn segment_color_system(
+    theme: Res<Theme>,
+    coloring: Res<SnakeColoring>,
+    snake_query: Query<(&Player, Ref<Snake>)>,
+    mut sprite_query: Query<&mut Sprite, (With<SnakeSegment>, Without<FadingSegment>)>,
+) {
+    let repaint_all = theme.is_changed() || coloring.is_changed();
+
+    for (player, snake) in snake_query.iter() {
+        if !repaint_all && !snake.is_changed() {
+            continue;
+        }
+
+        let head = player.color(theme.palette());
+        let length = snake.segments.len();
+        for (index, &segment) in snake.segments.iter().enumerate() {
+            if let Ok(mut sprite) = sprite_query.get_mut(segment) {
+                sprite.color = coloring.segment_color(head, index, length);
+            }
+        }
+    }
+}
+
+/// Picks every segment's tile and rotation from where its neighbors are, so the head faces its
+/// heading and the body bends around corners.
+fn segment_sprite_system(
+    snake_query: Query<&Snake>,
+    moved_query: Query<(), (With<SnakeSegment>, Changed<GridPosition>)>,
+    mut segment_query: Query<(&GridPosition, &mut Sprite, &mut Transform), With<SnakeSegment>>,
+) {
+    if moved_query.is_empty() {
+        return;
+    }
+
+    for snake in snake_query.iter() {
+        let positions: Vec<IVec2> = snake.segments
+            .iter()
+            .filter_map(|&entity| segment_query.get(entity).ok())
+            .map(|(position, ..)| position.0)
+            .collect();
+        if positions.len() != snake.segments.len() {
+            continue;
+        }
+
+        for (i, &entity) in snake.segments.iter().enumerate() {
+            // A freshly grown segment shares its cell with the tail, so look past repeated cells.
+            let cell = positions[i];
+            let toward_head = positions[..i].iter().rev().find(|&&other| other != cell);
+            let toward_tail = positions[i + 1..].iter().find(|&&other| other != cell);
+            let head_step = toward_head.map(|&other| step_between(cell, other));
+            let tail_step = toward_tail.map(|&other| step_between(cell, other));
+            // A neighbor on the far side of a portal isn't next to this cell, so draw the snake
+            // carrying straight on into it.
+            let (tile, facing) = segment_tile(
+                head_step.map(|step| step.or(tail_step.flatten().map(|tail| -tail)).unwrap_or(IVec2::X)),
+                tail_step.map(|step| step.or(head_step.flatten().map(|head| -head)).unwrap_or(IVec2::NEG_X)),
+            );
+
+            let Ok((_, mut sprite, mut transform)) = segment_query.get_mut(entity) else {
+                continue;
+            };
+            if let Some(atlas) = sprite.texture_atlas.as_mut() {
+                atlas.index = tile as usize;
+            }
+            transform.rotation = Quat::from_rotation_z(facing.as_vec2().to_angle());
+        }
+    }
+}
+
+/// The tile for a segment joined to its neighbors in the given directions, and the direction its
+/// rightward-drawn tile has to be turned to.
+fn segment_tile(toward_head: Option<IVec2>, toward_tail: Option<IVec2>) -> (SegmentTile, IVec2) {
+    match (toward_head, toward_tail) {
+        (None, Some(tail)) => (SegmentTile::Head, -tail),
+        (None, None) => (SegmentTile::Head, IVec2::X),
+        (Some(head), None) => (SegmentTile::Tail, head),
+        (Some(head), Some(tail)) if head == -tail => (SegmentTile::Straight, head),
+        // Turning the corner tile to face `head` carries its top edge to `head.perp()`.
+        (Some(head), Some(tail)) if head.perp() == tail => (SegmentTile::Corner, head),
+        (Some(_), Some(tail)) => (SegmentTile::Corner, tail),
+    }
+}
+
+/// Slides segments from their previous cell to their current one over the step, so the snake
+/// looks fluid at any speed while the game logic stays on the grid.
+pub fn segment_interpolation_system(
+    timer: Option<Res<MoveTimer>>,
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&GridPosition, &PreviousGridPosition, &mut Transform)>,
+) {
+    // The step timer only moves on fixed ticks, so the time since the last tick is added to keep the slide smooth.
+    let progress = timer.map_or(1., |timer| {
+        ((timer.0.elapsed() + fixed_time.overstep()).as_secs_f32() / timer.0.duration().as_secs_f32()).min(1.)
+    });
+
+    for (position, previous, mut transform) in query.iter_mut() {
+        // A step across a wrapping edge starts just outside the board, not on the far side of it,
+        // and a segment coming through a portal just appears at the exit.
+        let from = step_between(position.0, previous.0).map_or(position.0, |step| position.0 + step);
+        transform.translation = grid_to_world(from).lerp(grid_to_world(position.0), progress).extend(0.);
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Somewhere to keep small named values between runs.
+pub trait Storage {
+    fn read(&self, key: &str) -> Option<String>;
+    fn write(&self, key: &str, value: &str);
+    fn remove(&self, key: &str);
+}
+
+/// A file per key in the working directory, on the desktop.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn read(&self, key: &str) -> Option<String> {
+        std::fs::read_to_string(key).ok()
+    }
+
+    fn write(&self, key: &str, value: &str) {
+        if let Err(error) = std::fs::write(key, value) {
+            warn!("could not save {key}: {error}");
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if let Err(error) = std::fs::remove_file(key) {
+            warn!("could not remove {key}: {error}");
+        }
+    }
+}
+
+/// The page's `localStorage`, in the browser.
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn read(&self, key: &str) -> Option<String> {
+        Self::storage()?.get_item(key).ok()?
+    }
+
+    fn write(&self, key: &str, value: &str) {
+        let saved = Self::storage().is_some_and(|storage| storage.set_item(key, value).is_ok());
+        if !saved {
+            warn!("could not save {key} to localStorage");
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let removed = Self::storage().is_some_and(|storage| storage.remove_item(key).is_ok());
+        if !removed {
+            warn!("could not remove {key} from localStorage");
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const PLATFORM_STORAGE: FileStorage = FileStorage;
+#[cfg(target_arch = "wasm32")]
+const PLATFORM_STORAGE: LocalStorage = LocalStorage;
+
+/// Reads `key` as RON; `None` when it is missing or unreadable.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    ron::from_str(&PLATFORM_STORAGE.read(key)?)
+        .inspect_err(|error| warn!("could not parse {key}: {error}"))
+        .ok()
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    match ron::to_string(value) {
+        Ok(contents) => PLATFORM_STORAGE.write(key, &contents),
+        Err(error) => warn!("could not serialize {key}: {error}"),
+    }
+}
+
+pub fn remove(key: &str) {
+    PLATFORM_STORAGE.remove(key);
+}
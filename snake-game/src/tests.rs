@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use bevy::input::InputPlugin;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+
+use super::*;
+use crate::food::{Food, FoodColor, FoodCount, FoodEffect, FoodKind};
+use crate::grid::GridPosition;
+use crate::snake::{spawn_snake, DirectionQueue, GameSpeed, MoveTimer, Snake, SnakeSprites};
+use crate::theme::Theme;
+
+fn test_app() -> App {
+    let mut app = App::new();
+    let interval = GameSpeed::default().interval();
+
+    app.add_plugins((MinimalPlugins, StatesPlugin, InputPlugin, AssetPlugin::default(), GameplayPlugin))
+        // Every update is one fixed tick and at least one step, however much the snake has sped up.
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(interval)))
+        .insert_resource(Time::<Fixed>::from_seconds(interval as f64))
+        .insert_resource(Theme::Classic)
+        .insert_resource(GameRng::from_seed(0))
+        // Only the food a test places is on the board.
+        .insert_resource(FoodCount(0))
+        .insert_resource(MoveTimer(Timer::from_seconds(interval, TimerMode::Repeating)))
+        .insert_resource(SnakeSprites { image: Handle::default(), layout: Handle::default() });
+
+    // The first update doesn't advance time, so it takes no step.
+    app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::Playing);
+    app.update();
+
+    app
+}
+
+fn spawn_test_snake(app: &mut App, position: IVec2, heading: IVec2) -> Entity {
+    let sprites = SnakeSprites { image: Handle::default(), layout: Handle::default() };
+    let world = app.world_mut();
+    let snake = spawn_snake(&mut world.commands(), &sprites, Theme::Classic.palette(), Player::One, position, heading);
+    world.flush();
+    snake
+}
+
+fn spawn_test_food(app: &mut App, position: IVec2) -> Entity {
+    let kind = FoodKind {
+        name: "Test".to_string(),
+        weight: 1,
+        points: 1,
+        color: FoodColor::Food,
+        lifetime: None,
+        effect: FoodEffect::Grow,
+    };
+    app.world_mut().spawn((GridPosition(position), Food(kind))).id()
+}
+
+fn turn(app: &mut App, snake: Entity, direction: IVec2) {
+    app.world_mut().get_mut::<DirectionQueue>(snake).unwrap().push(direction);
+}
+
+/// The snake's cells from head to tail.
+fn segment_positions(app: &App, snake: Entity) -> Vec<IVec2> {
+    let world = app.world();
+    world.get::<Snake>(snake)
+        .unwrap()
+        .segments
+        .iter()
+        .map(|&segment| world.get::<GridPosition>(segment).unwrap().0)
+        .collect()
+}
+
+fn run_steps(app: &mut App, steps: usize) {
+    for _ in 0..steps {
+        app.update();
+    }
+}
+
+#[test]
+fn eating_food_grows_the_snake_by_one_segment() {
+    let mut app = test_app();
+    let snake = spawn_test_snake(&mut app, IVec2::new(10, 10), IVec2::X);
+    let food = spawn_test_food(&mut app, IVec2::new(11, 10));
+    let length = segment_positions(&app, snake).len();
+
+    run_steps(&mut app, 1);
+
+    assert!(app.world().get::<Food>(food).is_none());
+    assert_eq!(segment_positions(&app, snake).len(), length + 1);
+
+    run_steps(&mut app, 3);
+
+    assert_eq!(segment_positions(&app, snake).len(), length + 1);
+}
+
+#[test]
+fn tail_follows_the_heads_path() {
+    let mut app = test_app();
+    let snake = spawn_test_snake(&mut app, IVec2::new(10, 10), IVec2::X);
+    let mut head_path = Vec::new();
+
+    for direction in [IVec2::X, IVec2::Y, IVec2::Y, IVec2::NEG_X, IVec2::NEG_X, IVec2::Y, IVec2::X] {
+        turn(&mut app, snake, direction);
+        run_steps(&mut app, 1);
+        head_path.push(segment_positions(&app, snake)[0]);
+    }
+
+    let segments = segment_positions(&app, snake);
+    let trail: Vec<IVec2> = head_path.iter().rev().take(segments.len()).copied().collect();
+    assert_eq!(segments, trail);
+}
+
+#[test]
+fn running_into_its_own_body_ends_the_game() {
+    let mut app = test_app();
+    let snake = spawn_test_snake(&mut app, IVec2::new(10, 10), IVec2::X);
+    // Long enough to turn back on itself.
+    spawn_test_food(&mut app, IVec2::new(11, 10));
+    spawn_test_food(&mut app, IVec2::new(12, 10));
+    run_steps(&mut app, 2);
+
+    for direction in [IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y] {
+        turn(&mut app, snake, direction);
+        run_steps(&mut app, 1);
+    }
+    app.update();
+
+    assert!(!app.world().get::<Snake>(snake).unwrap().alive);
+    assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Dying);
+}
@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::grid::BoardCell;
+use crate::storage;
+use crate::ui::ComboBar;
+
+const THEME_KEY: &str = "snake-game.theme";
+
+const CLASSIC_PALETTE: Palette = Palette {
+    snakes: [Color::srgb(0.3, 0.3, 0.7), Color::srgb(0.3, 0.6, 0.3)],
+    food: Color::srgb(0.7, 0.3, 0.3),
+    bonus_food: Color::srgb(0.9, 0.7, 0.1),
+    poison_food: Color::srgb(0.45, 0.6, 0.1),
+    obstacle: Color::srgb(0.35, 0.35, 0.35),
+    board_light: Color::srgb(0.9, 0.9, 0.9),
+    board_dark: Color::srgb(0.86, 0.86, 0.86),
+};
+/// Saturated pieces on a near-white board, far apart in brightness as well as hue.
+const HIGH_CONTRAST_PALETTE: Palette = Palette {
+    snakes: [Color::srgb(0., 0., 0.), Color::srgb(0., 0.25, 1.)],
+    food: Color::srgb(0.9, 0., 0.),
+    bonus_food: Color::srgb(1., 0.6, 0.),
+    poison_food: Color::srgb(0.55, 0., 0.65),
+    obstacle: Color::srgb(0.3, 0.3, 0.3),
+    board_light: Color::srgb(1., 1., 1.),
+    board_dark: Color::srgb(0.92, 0.92, 0.92),
+};
+/// Picked from the Okabe-Ito set so nothing relies on telling red from green.
+const DEUTERANOPIA_PALETTE: Palette = Palette {
+    snakes: [Color::srgb(0., 0.45, 0.7), Color::srgb(0.9, 0.6, 0.)],
+    food: Color::srgb(0.8, 0.4, 0.65),
+    bonus_food: Color::srgb(0.95, 0.9, 0.25),
+    poison_food: Color::srgb(0.1, 0.1, 0.1),
+    obstacle: Color::srgb(0.45, 0.45, 0.45),
+    board_light: Color::srgb(0.9, 0.9, 0.9),
+    board_dark: Color::srgb(0.85, 0.85, 0.85),
+};
+
+/// The colors of everything a [`Theme`] paints.
+pub struct Palette {
+    /// Indexed by [`Player`](crate::snake::Player).
+    pub snakes: [Color; 2],
+    pub food: Color,
+    pub bonus_food: Color,
+    pub poison_food: Color,
+    pub obstacle: Color,
+    /// Shows through as the clear color between the dark cells.
+    pub board_light: Color,
+    pub board_dark: Color,
+}
+
+/// The color scheme picked in the settings, kept in [`THEME_KEY`].
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Theme {
+    #[default]
+    Classic,
+    HighContrast,
+    /// Safe for red-green color blindness.
+    Deuteranopia,
+}
+
+impl Theme {
+    fn load() -> Self {
+        storage::load(THEME_KEY).unwrap_or_default()
+    }
+
+    pub fn save(self) {
+        storage::save(THEME_KEY, &self);
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Classic => "Classic",
+            Theme::HighContrast => "High contrast",
+            Theme::Deuteranopia => "Deuteranopia",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Theme::Classic => Theme::HighContrast,
+            Theme::HighContrast => Theme::Deuteranopia,
+            Theme::Deuteranopia => Theme::Classic,
+        }
+    }
+
+    pub fn palette(self) -> &'static Palette {
+        match self {
+            Theme::Classic => &CLASSIC_PALETTE,
+            Theme::HighContrast => &HIGH_CONTRAST_PALETTE,
+            Theme::Deuteranopia => &DEUTERANOPIA_PALETTE,
+        }
+    }
+}
+
+/// Loads the saved [`Theme`] and keeps the board behind the game in its colors.
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(CLASSIC_PALETTE.board_light))
+            .insert_resource(Theme::load())
+            .add_systems(Update, theme_system.run_if(resource_changed::<Theme>));
+    }
+}
+
+/// Repaints what is on screen outside a game when another [`Theme`] is picked; everything else
+/// takes its colors from the theme as it is spawned.
+fn theme_system(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut cell_query: Query<&mut Sprite, With<BoardCell>>,
+    mut combo_bar_query: Query<(&ComboBar, &mut BackgroundColor)>,
+) {
+    let palette = theme.palette();
+    clear_color.0 = palette.board_light;
+    for mut sprite in cell_query.iter_mut() {
+        sprite.color = palette.board_dark;
+    }
+    for (bar, mut background) in combo_bar_query.iter_mut() {
+        background.0 = bar.0.color(palette);
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::food::EatEvent;
+use crate::round::round_end_system;
+use crate::score::Score;
+use crate::snake::death_system;
+use crate::{storage, GameMode, GameState, StepSet};
+
+const TIMED_MATCH_DURATION: f32 = 120.;
+/// Seconds added to the match clock for every regular food eaten.
+const TIMED_EAT_BONUS: f32 = 3.;
+const BEST_TIMED_SCORE_KEY: &str = "snake-game.timed";
+
+/// Time left in a timed challenge; the round ends when it runs out.
+#[derive(Resource)]
+pub struct MatchTimer(pub Timer);
+
+impl Default for MatchTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(TIMED_MATCH_DURATION, TimerMode::Once))
+    }
+}
+
+/// The best single-player timed challenge score, kept in [`BEST_TIMED_SCORE_KEY`].
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct BestTimedScore(pub u32);
+
+impl BestTimedScore {
+    fn load() -> Self {
+        storage::load(BEST_TIMED_SCORE_KEY).unwrap_or_default()
+    }
+
+    fn save(&self) {
+        storage::save(BEST_TIMED_SCORE_KEY, self);
+    }
+}
+
+/// How a finished single-player timed challenge compares against the best before it.
+#[derive(Resource)]
+pub struct TimedResult {
+    pub score: u32,
+    pub previous_best: u32,
+    pub time_up: bool,
+}
+
+/// The clock of a [`Ruleset::Timed`](crate::Ruleset::Timed) game, which every food eaten winds
+/// back, and the best score played against it.
+pub struct TimedPlugin;
+
+impl Plugin for TimedPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BestTimedScore::load())
+            .add_systems(FixedUpdate, time_bonus_system.run_if(resource_exists::<MatchTimer>).in_set(StepSet::Grow))
+            .add_systems(
+                FixedUpdate,
+                match_timer_system
+                    .run_if(resource_exists::<MatchTimer>)
+                    .after(death_system)
+                    .before(round_end_system)
+                    .in_set(StepSet::Resolve),
+            )
+            .add_systems(OnEnter(GameState::GameOver), record_timed_score.run_if(resource_exists::<MatchTimer>));
+    }
+}
+
+fn time_bonus_system(mut eat_events: EventReader<EatEvent>, mut match_timer: ResMut<MatchTimer>) {
+    for _ in eat_events.read() {
+        let duration = match_timer.0.duration() + Duration::from_secs_f32(TIMED_EAT_BONUS);
+        match_timer.0.set_duration(duration);
+    }
+}
+
+fn match_timer_system(time: Res<Time>, mut match_timer: ResMut<MatchTimer>) {
+    match_timer.0.tick(time.delta());
+}
+
+/// Compares a finished single-player timed challenge against the best one, keeping it if it beat it.
+fn record_timed_score(
+    mut commands: Commands,
+    game_mode: Res<GameMode>,
+    match_timer: Res<MatchTimer>,
+    mut best: ResMut<BestTimedScore>,
+    snake_query: Query<&Score>,
+) {
+    if *game_mode != GameMode::Single {
+        return;
+    }
+
+    let Ok(score) = snake_query.get_single() else {
+        return;
+    };
+
+    commands.insert_resource(TimedResult {
+        score: score.0,
+        previous_best: best.0,
+        time_up: match_timer.0.finished(),
+    });
+
+    if score.0 > best.0 {
+        best.0 = score.0;
+        best.save();
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{grid_to_world, GridPosition};
+use crate::score::Score;
+use crate::snake::{step_due, Player, Snake, SnakeSegment};
+use crate::theme::Theme;
+use crate::{storage, GameMode, GameState, StepSet};
+
+const BEST_RUN_KEY: &str = "snake-game.best-run";
+
+const TRAIL_CELL_SIZE: Vec2 = Vec2::new(10., 10.);
+const TRAIL_ALPHA: f32 = 0.12;
+/// Above the board's cells and below everything on them.
+const TRAIL_DEPTH: f32 = -0.5;
+
+/// The best single-player run so far and every cell its head passed through, kept in
+/// [`BEST_RUN_KEY`].
+#[derive(Resource, Serialize, Deserialize, Default)]
+struct BestRun {
+    score: u32,
+    path: Vec<IVec2>,
+}
+
+/// The cells the head has passed through this run, in order.
+#[derive(Resource, Default)]
+struct RunPath(Vec<IVec2>);
+
+/// Whether the best run's trail is drawn under the game, picked in the settings.
+#[derive(Resource)]
+pub struct BestRunTrail(pub bool);
+
+impl Default for BestRunTrail {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// A faint cell of the best run's trail.
+#[derive(Component)]
+pub struct TrailCell;
+
+/// Records the path of every single-player run, keeps the best one, and draws it faintly under
+/// the next games so its route can be studied.
+pub struct TrailPlugin;
+
+impl Plugin for TrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(storage::load::<BestRun>(BEST_RUN_KEY).unwrap_or_default())
+            .init_resource::<RunPath>()
+            .init_resource::<BestRunTrail>()
+            .add_systems(
+                FixedUpdate,
+                record_path_system.run_if(step_due).run_if(resource_equals(GameMode::Single)).in_set(StepSet::Collide),
+            )
+            .add_systems(OnEnter(GameState::GameOver), record_best_run);
+
+        // Every way into a new or continued game.
+        for exited in [GameState::LevelSelect, GameState::GameOver, GameState::Menu] {
+            app.add_systems(OnTransition { exited, entered: GameState::Playing }, (reset_path, spawn_trail));
+        }
+    }
+}
+
+fn reset_path(mut path: ResMut<RunPath>) {
+    path.0.clear();
+}
+
+fn spawn_trail(mut commands: Commands, trail: Res<BestRunTrail>, best: Res<BestRun>, theme: Res<Theme>) {
+    if !trail.0 {
+        return;
+    }
+
+    let color = Player::One.color(theme.palette()).with_alpha(TRAIL_ALPHA);
+    let cells: HashSet<IVec2> = best.path.iter().copied().collect();
+
+    // Deliberately without a GridPosition, so the trail never counts as taking up its cells.
+    for cell in cells {
+        commands.spawn((
+            Sprite {
+                color,
+                custom_size: Some(TRAIL_CELL_SIZE),
+                ..default()
+            },
+            Transform::from_translation(grid_to_world(cell).extend(TRAIL_DEPTH)),
+            TrailCell,
+        ));
+    }
+}
+
+fn record_path_system(
+    mut path: ResMut<RunPath>,
+    snake_query: Query<&Snake>,
+    segment_query: Query<&GridPosition, With<SnakeSegment>>,
+) {
+    let Ok(snake) = snake_query.get_single() else {
+        return;
+    };
+
+    if let Ok(head) = segment_query.get(snake.head()) {
+        if path.0.last() != Some(&head.0) {
+            path.0.push(head.0);
+        }
+    }
+}
+
+fn record_best_run(
+    game_mode: Res<GameMode>,
+    path: Res<RunPath>,
+    mut best: ResMut<BestRun>,
+    snake_query: Query<&Score, With<Snake>>,
+) {
+    if *game_mode != GameMode::Single {
+        return;
+    }
+
+    let Ok(score) = snake_query.get_single() else {
+        return;
+    };
+
+    if score.0 > best.score {
+        *best = BestRun { score: score.0, path: path.0.clone() };
+        storage::save(BEST_RUN_KEY, &*best);
+    }
+}
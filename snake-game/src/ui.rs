@@ -0,0 +1,690 @@
+use bevy::prelude::*;
+
+use crate::achievements::LifetimeStats;
+use crate::difficulty::DifficultySettings;
+use crate::food::{ActiveEffects, FoodCount, Mice, FOOD_COUNT_OPTIONS};
+use crate::grid::{grid_to_world, BorderMode};
+use crate::high_scores::{HighScores, NameEntry};
+use crate::level::Arena;
+use crate::round::Winner;
+use crate::save::SavedGame;
+use crate::score::{Combo, Score, ScoreEvent};
+use crate::snake::{GameSpeed, Player, Snake, SnakeColoring};
+use crate::theme::Theme;
+use crate::timed::{BestTimedScore, MatchTimer, TimedResult};
+use crate::trail::BestRunTrail;
+use crate::{GameMode, GameState, Ruleset, StepSet};
+
+const COMBO_BAR_SIZE: Vec2 = Vec2::new(120., 6.);
+const FLOATING_TEXT_DURATION: f32 = 0.5;
+const FLOATING_TEXT_RISE_SPEED: f32 = 60.;
+/// How far above the points their combo is announced.
+const COMBO_TEXT_OFFSET: f32 = 20.;
+const COMBO_TEXT_COLOR: Color = Color::srgb(0.85, 0.4, 0.1);
+const RECORD_TEXT_COLOR: Color = Color::srgb(0.85, 0.65, 0.);
+
+pub const HUD_FONT_SIZE: f32 = 20.;
+pub const HUD_COLOR: Color = Color::srgb(0.2, 0.2, 0.2);
+
+const BUTTON_SIZE: Vec2 = Vec2::new(260., 44.);
+const BUTTON_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
+const BUTTON_PRESSED_COLOR: Color = Color::srgb(0.2, 0.2, 0.6);
+const BUTTON_TEXT_COLOR: Color = Color::srgb(0.95, 0.95, 0.95);
+/// Choices the settings screen cycles through for [`GameSpeed::step`].
+const SPEED_STEP_OPTIONS: [f32; 4] = [0., 0.01, 0.02, 0.04];
+
+/// HUD bar showing how long a player's combo window has left.
+#[derive(Component)]
+pub struct ComboBar(pub Player);
+
+/// Text in the world that rises and fades out, such as points floating up from where they were
+/// scored. Spawned with [`spawn_floating_text`].
+#[derive(Component)]
+pub struct FloatingText {
+    timer: Timer,
+    color: Color,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct GameOverText;
+
+#[derive(Component)]
+struct HighScoreText;
+
+/// What a button on the menu or settings screen does when clicked.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum MenuButton {
+    Continue,
+    CycleDifficulty,
+    Play,
+    PlayVersus,
+    PlayVersusAi,
+    PlayCoop,
+    Settings,
+    Stats,
+    Quit,
+    ToggleWalls,
+    CycleSpeedStep,
+    CycleRuleset,
+    CycleFoodCount,
+    ToggleMice,
+    ToggleTrail,
+    CycleTheme,
+    CycleColoring,
+    Back,
+}
+
+impl MenuButton {
+    #[allow(clippy::too_many_arguments)]
+    fn label(
+        self,
+        difficulty: &DifficultySettings,
+        border_mode: BorderMode,
+        speed: &GameSpeed,
+        ruleset: Ruleset,
+        food_count: &FoodCount,
+        mice: &Mice,
+        trail: &BestRunTrail,
+        theme: Theme,
+        coloring: SnakeColoring,
+    ) -> String {
+        match self {
+            MenuButton::Continue => "Continue".to_string(),
+            MenuButton::CycleDifficulty => format!("Difficulty: {}", difficulty.difficulty.name()),
+            MenuButton::Play => "Play".to_string(),
+            MenuButton::PlayVersus => "Two Players".to_string(),
+            MenuButton::PlayVersusAi => "Play vs Computer".to_string(),
+            MenuButton::PlayCoop => "Co-op".to_string(),
+            MenuButton::Settings => "Settings".to_string(),
+            MenuButton::Stats => "Stats".to_string(),
+            MenuButton::Quit => "Quit".to_string(),
+            MenuButton::ToggleWalls => format!("Walls: {border_mode:?}"),
+            MenuButton::CycleSpeedStep => format!("Speed-up per food: {:.0}%", speed.step * 100.),
+            MenuButton::CycleRuleset => format!("Rules: {}", ruleset.name()),
+            MenuButton::CycleFoodCount => format!("Food on board: {}", food_count.0),
+            MenuButton::ToggleMice => format!("Mice: {}", if mice.0 { "On" } else { "Off" }),
+            MenuButton::ToggleTrail => format!("Best run trail: {}", if trail.0 { "On" } else { "Off" }),
+            MenuButton::CycleTheme => format!("Colors: {}", theme.name()),
+            MenuButton::CycleColoring => format!("Snake colors: {}", coloring.name()),
+            MenuButton::Back => "Back".to_string(),
+        }
+    }
+}
+
+/// Menus, the HUD and the pause and game over screens.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hud)
+            .add_systems(OnEnter(GameState::Menu), spawn_main_menu)
+            .add_systems(OnEnter(GameState::Settings), spawn_settings_menu)
+            .add_systems(
+                Update,
+                (
+                    menu_button_system,
+                    menu_button_label_system,
+                    back_to_menu_system.run_if(
+                        in_state(GameState::Settings)
+                            .or(in_state(GameState::Stats))
+                            .or(in_state(GameState::LevelSelect)),
+                    ),
+                ),
+            )
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_screen)
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+            .add_systems(
+                Update,
+                (game_over_text_system, high_score_text_system).run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(Update, (combo_bar_system, (score_text_system, floating_text_system).chain(), hud_system))
+            .add_systems(FixedUpdate, new_record_system.in_set(StepSet::Resolve));
+    }
+}
+
+fn spawn_hud(mut commands: Commands, theme: Res<Theme>) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: HUD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(HUD_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.),
+            left: Val::Px(12.),
+            ..default()
+        },
+        HudText,
+    ));
+
+    for (row, player) in [Player::One, Player::Two].into_iter().enumerate() {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(12. + row as f32 * (COMBO_BAR_SIZE.y + 6.)),
+                right: Val::Px(12.),
+                width: Val::Px(0.),
+                height: Val::Px(COMBO_BAR_SIZE.y),
+                ..default()
+            },
+            BackgroundColor(player.color(theme.palette())),
+            ComboBar(player),
+        ));
+    }
+}
+
+fn spawn_main_menu(mut commands: Commands, saved_game: Option<Res<SavedGame>>) {
+    let mut buttons = Vec::new();
+    if saved_game.is_some() {
+        buttons.push(MenuButton::Continue);
+    }
+    buttons.extend([
+        MenuButton::CycleDifficulty,
+        MenuButton::Play,
+        MenuButton::PlayVersus,
+        MenuButton::PlayVersusAi,
+        MenuButton::PlayCoop,
+        MenuButton::Settings,
+        MenuButton::Stats,
+        MenuButton::Quit,
+    ]);
+
+    spawn_menu_screen(&mut commands, GameState::Menu, "Snake", &buttons);
+}
+
+fn spawn_settings_menu(mut commands: Commands) {
+    spawn_menu_screen(
+        &mut commands,
+        GameState::Settings,
+        "Settings",
+        &[
+            MenuButton::ToggleWalls,
+            MenuButton::CycleSpeedStep,
+            MenuButton::CycleRuleset,
+            MenuButton::CycleFoodCount,
+            MenuButton::ToggleMice,
+            MenuButton::ToggleTrail,
+            MenuButton::CycleTheme,
+            MenuButton::CycleColoring,
+            MenuButton::Back,
+        ],
+    );
+}
+
+/// A centered title over a column of buttons, torn down when `state` is left.
+fn spawn_menu_screen(commands: &mut Commands, state: GameState, title: &str, buttons: &[MenuButton]) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.),
+                ..default()
+            },
+            StateScoped(state),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(title),
+                TextFont {
+                    font_size: HUD_FONT_SIZE * 3.,
+                    ..default()
+                },
+                TextColor(HUD_COLOR),
+            ));
+
+            for &button in buttons {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(BUTTON_SIZE.x),
+                            height: Val::Px(BUTTON_SIZE.y),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BackgroundColor(BUTTON_COLOR),
+                        button,
+                    ))
+                    .with_child((
+                        Text::default(),
+                        TextFont {
+                            font_size: HUD_FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(BUTTON_TEXT_COLOR),
+                    ));
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_button_system(
+    mut query: Query<(&Interaction, &MenuButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut difficulty: ResMut<DifficultySettings>,
+    mut border_mode: ResMut<BorderMode>,
+    mut speed: ResMut<GameSpeed>,
+    mut ruleset: ResMut<Ruleset>,
+    mut food_count: ResMut<FoodCount>,
+    mut mice: ResMut<Mice>,
+    mut trail: ResMut<BestRunTrail>,
+    mut theme: ResMut<Theme>,
+    mut coloring: ResMut<SnakeColoring>,
+    stats: Res<LifetimeStats>,
+    mut game_mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button, mut background) in query.iter_mut() {
+        *background = match interaction {
+            Interaction::Pressed => BUTTON_PRESSED_COLOR,
+            Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        }
+        .into();
+
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            MenuButton::Continue => next_state.set(GameState::Playing),
+            MenuButton::CycleDifficulty => *difficulty = DifficultySettings::new(difficulty.difficulty.next()),
+            MenuButton::Play => {
+                *game_mode = GameMode::Single;
+                next_state.set(GameState::LevelSelect);
+            }
+            MenuButton::PlayVersus => {
+                *game_mode = GameMode::Versus;
+                next_state.set(GameState::LevelSelect);
+            }
+            MenuButton::PlayVersusAi => {
+                *game_mode = GameMode::VersusAi;
+                next_state.set(GameState::LevelSelect);
+            }
+            MenuButton::PlayCoop => {
+                *game_mode = GameMode::Coop;
+                next_state.set(GameState::LevelSelect);
+            }
+            MenuButton::Settings => next_state.set(GameState::Settings),
+            MenuButton::Stats => next_state.set(GameState::Stats),
+            MenuButton::Quit => {
+                exit.send(AppExit::Success);
+            }
+            MenuButton::ToggleWalls => {
+                *border_mode = match *border_mode {
+                    BorderMode::Solid => BorderMode::Wrap,
+                    BorderMode::Wrap => BorderMode::Solid,
+                };
+            }
+            MenuButton::CycleSpeedStep => {
+                let current = SPEED_STEP_OPTIONS.iter().position(|&step| step == speed.step).unwrap_or(0);
+                speed.step = SPEED_STEP_OPTIONS[(current + 1) % SPEED_STEP_OPTIONS.len()];
+            }
+            MenuButton::CycleRuleset => *ruleset = ruleset.next(),
+            MenuButton::CycleFoodCount => {
+                let current = FOOD_COUNT_OPTIONS.iter().position(|&count| count == food_count.0).unwrap_or(0);
+                food_count.0 = FOOD_COUNT_OPTIONS[(current + 1) % FOOD_COUNT_OPTIONS.len()];
+            }
+            MenuButton::ToggleMice => mice.0 = !mice.0,
+            MenuButton::ToggleTrail => trail.0 = !trail.0,
+            MenuButton::CycleTheme => {
+                *theme = theme.next();
+                theme.save();
+            }
+            MenuButton::CycleColoring => *coloring = coloring.next(stats.rainbow_unlocked()),
+            MenuButton::Back => next_state.set(GameState::Menu),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_button_label_system(
+    difficulty: Res<DifficultySettings>,
+    border_mode: Res<BorderMode>,
+    speed: Res<GameSpeed>,
+    ruleset: Res<Ruleset>,
+    food_count: Res<FoodCount>,
+    mice: Res<Mice>,
+    trail: Res<BestRunTrail>,
+    theme: Res<Theme>,
+    coloring: Res<SnakeColoring>,
+    button_query: Query<(Ref<MenuButton>, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let settings_changed = difficulty.is_changed()
+        || border_mode.is_changed()
+        || speed.is_changed()
+        || ruleset.is_changed()
+        || food_count.is_changed()
+        || mice.is_changed()
+        || trail.is_changed()
+        || theme.is_changed()
+        || coloring.is_changed();
+
+    for (button, children) in button_query.iter() {
+        if !button.is_added() && !settings_changed {
+            continue;
+        }
+
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0 = button.label(
+                &difficulty,
+                *border_mode,
+                &speed,
+                *ruleset,
+                &food_count,
+                &mice,
+                &trail,
+                *theme,
+                *coloring,
+            );
+        }
+    }
+}
+
+fn back_to_menu_system(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+fn combo_bar_system(combo_query: Query<(&Player, &Combo)>, mut bar_query: Query<(&ComboBar, &mut Node)>) {
+    for (bar, mut node) in bar_query.iter_mut() {
+        let remaining = combo_query
+            .iter()
+            .find(|(&player, _)| player == bar.0)
+            .and_then(|(_, combo)| combo.window.as_ref())
+            .map_or(0., Timer::fraction_remaining);
+        let width = Val::Px(COMBO_BAR_SIZE.x * remaining);
+        if node.width != width {
+            node.width = width;
+        }
+    }
+}
+
+/// Puts `text` at `position` in the world, from where it rises and fades out.
+pub fn spawn_floating_text(commands: &mut Commands, text: impl Into<String>, position: Vec2, size: f32, color: Color) {
+    commands.spawn((
+        Text2d::new(text),
+        TextFont {
+            font_size: size,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_translation(position.extend(1.)),
+        FloatingText { timer: Timer::from_seconds(FLOATING_TEXT_DURATION, TimerMode::Once), color },
+    ));
+}
+
+fn floating_text_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut text_query: Query<(Entity, &mut FloatingText, &mut Transform, &mut TextColor)>,
+) {
+    for (entity, mut floating, mut transform, mut color) in text_query.iter_mut() {
+        if floating.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += FLOATING_TEXT_RISE_SPEED * time.delta_secs();
+        color.0 = floating.color.with_alpha(floating.timer.fraction_remaining());
+    }
+}
+
+/// Floats the points up from wherever they were scored, announcing the combo they were scored at.
+fn score_text_system(mut commands: Commands, mut score_events: EventReader<ScoreEvent>) {
+    for event in score_events.read() {
+        let position = grid_to_world(event.position);
+        spawn_floating_text(&mut commands, format!("{:+}", event.points), position, HUD_FONT_SIZE, HUD_COLOR);
+        if event.combo > 1 {
+            spawn_floating_text(
+                &mut commands,
+                format!("COMBO x{}!", event.combo),
+                position + Vec2::Y * COMBO_TEXT_OFFSET,
+                HUD_FONT_SIZE,
+                COMBO_TEXT_COLOR,
+            );
+        }
+    }
+}
+
+/// Announces when a single-player run first goes past the best score on record, once per run.
+#[allow(clippy::too_many_arguments)]
+fn new_record_system(
+    mut commands: Commands,
+    mut score_events: EventReader<ScoreEvent>,
+    game_mode: Res<GameMode>,
+    high_scores: Res<HighScores>,
+    best_timed: Res<BestTimedScore>,
+    match_timer: Option<Res<MatchTimer>>,
+    score_query: Query<&Score>,
+    // The snake the record was last announced for.
+    mut announced: Local<Option<Entity>>,
+) {
+    let record = match match_timer {
+        Some(_) => best_timed.0,
+        None => high_scores.0.first().map_or(0, |entry| entry.score),
+    };
+
+    for event in score_events.read() {
+        if *game_mode != GameMode::Single || record == 0 || *announced == Some(event.snake) {
+            continue;
+        }
+
+        if score_query.get(event.snake).is_ok_and(|score| score.0 > record) {
+            *announced = Some(event.snake);
+            let position = grid_to_world(event.position) + Vec2::Y * COMBO_TEXT_OFFSET * 2.;
+            spawn_floating_text(&mut commands, "NEW RECORD!", position, HUD_FONT_SIZE * 1.5, RECORD_TEXT_COLOR);
+        }
+    }
+}
+
+fn spawn_game_over_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            StateScoped(GameState::GameOver),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: HUD_FONT_SIZE * 2.,
+                    ..default()
+                },
+                TextColor(HUD_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+                GameOverText,
+            ));
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: HUD_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(HUD_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+                HighScoreText,
+            ));
+        });
+}
+
+fn spawn_pause_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            StateScoped(GameState::Paused),
+        ))
+        .with_child((
+            Text::new("Paused\nPress Escape to resume\nPress M for the menu\nPress V to mute"),
+            TextFont {
+                font_size: HUD_FONT_SIZE * 2.,
+                ..default()
+            },
+            TextColor(HUD_COLOR),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+}
+
+fn game_over_text_system(
+    border_mode: Res<BorderMode>,
+    game_mode: Res<GameMode>,
+    winner: Res<Winner>,
+    name_entry: Option<Res<NameEntry>>,
+    timed_result: Option<Res<TimedResult>>,
+    snake_query: Query<(&Player, &Score)>,
+    mut query: Query<&mut Text, With<GameOverText>>,
+) {
+    let mut scores: Vec<(Player, u32)> = snake_query.iter().map(|(&player, score)| (player, score.0)).collect();
+    scores.sort();
+
+    for mut text in query.iter_mut() {
+        let result = match *game_mode {
+            GameMode::Single => match &timed_result {
+                Some(timed_result) => {
+                    let headline = if timed_result.time_up { "Time's up!" } else { "Game Over" };
+                    let comparison = if timed_result.score > timed_result.previous_best {
+                        "New best timed score!".to_string()
+                    } else {
+                        format!("Best timed score: {}", timed_result.previous_best)
+                    };
+                    format!("{headline}\nFinal score: {}\n{comparison}", timed_result.score)
+                }
+                None => format!("Game Over\nFinal score: {}", scores.first().map_or(0, |&(_, score)| score)),
+            },
+            GameMode::Coop => format!("Game Over\nTeam score: {}", scores.first().map_or(0, |&(_, score)| score)),
+            GameMode::Versus | GameMode::VersusAi => {
+                let headline = match winner.0 {
+                    Some(player) => format!("{} wins!", game_mode.player_name(player)),
+                    None => "Draw!".to_string(),
+                };
+                let totals: Vec<String> = scores
+                    .iter()
+                    .map(|&(player, score)| format!("{}: {score}", game_mode.player_name(player)))
+                    .collect();
+                format!("{headline}\n{}", totals.join("   "))
+            }
+        };
+
+        let content = match &name_entry {
+            Some(name_entry) => format!(
+                "{result}\nNew high score!\nType your name: {}_\nPress Enter to save",
+                name_entry.0.name,
+            ),
+            None => format!(
+                "{result}\nPress R to restart\nPress L to pick another level\nPress M for the menu\nPress B to change walls: {:?}",
+                *border_mode,
+            ),
+        };
+
+        if text.0 != content {
+            text.0 = content;
+        }
+    }
+}
+
+fn high_score_text_system(high_scores: Res<HighScores>, mut query: Query<(&mut Text, Ref<HighScoreText>)>) {
+    for (mut text, marker) in query.iter_mut() {
+        if !marker.is_added() && !high_scores.is_changed() {
+            continue;
+        }
+
+        let mut content = String::from("\nHigh scores");
+        for (rank, entry) in high_scores.0.iter().enumerate() {
+            content += &format!("\n{}. {}   {} points, length {}", rank + 1, entry.name, entry.score, entry.length);
+        }
+        text.0 = content;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn hud_system(
+    speed: Res<GameSpeed>,
+    effects: Res<ActiveEffects>,
+    arena: Option<Res<Arena>>,
+    match_timer: Option<Res<MatchTimer>>,
+    game_mode: Res<GameMode>,
+    snake_query: Query<(&Player, Ref<Score>, Ref<Snake>, Ref<Combo>)>,
+    mut query: Query<&mut Text, With<HudText>>,
+) {
+    let snake_changed = snake_query
+        .iter()
+        .any(|(_, score, snake, combo)| score.is_changed() || snake.is_changed() || combo.is_changed());
+    let arena_changed = arena.as_ref().is_some_and(|arena| arena.is_changed());
+    let clock_changed = match_timer.as_ref().is_some_and(|match_timer| match_timer.is_changed());
+    if !speed.is_changed() && !effects.is_changed() && !arena_changed && !clock_changed && !snake_changed {
+        return;
+    }
+
+    let mut snakes: Vec<_> = snake_query.iter().collect();
+    snakes.sort_by_key(|(&player, ..)| player);
+
+    let mut lines: Vec<String> = snakes
+        .iter()
+        .map(|(player, score, snake, combo)| {
+            let mut stats = format!("Score: {}   Length: {}", score.0, snake.segments.len());
+            if combo.multiplier > 1 {
+                stats += &format!("   Combo x{}", combo.multiplier);
+            }
+            match *game_mode {
+                GameMode::Single | GameMode::Coop => stats,
+                GameMode::Versus | GameMode::VersusAi => format!("{} - {stats}", game_mode.player_name(**player)),
+            }
+        })
+        .collect();
+    lines.push(format!("Speed: {}", speed.level + 1));
+
+    let mut ghosts: Vec<_> = effects.ghost.iter().collect();
+    ghosts.sort_by_key(|(&player, _)| player);
+    for (&player, timer) in ghosts {
+        let label = match *game_mode {
+            GameMode::Single | GameMode::Coop => "Ghost".to_string(),
+            GameMode::Versus | GameMode::VersusAi => format!("{} ghost", game_mode.player_name(player)),
+        };
+        lines.push(format!("{label}: {:.1}s", timer.remaining_secs()));
+    }
+    if let Some(timer) = &effects.slow {
+        lines.push(format!("Slow: {:.1}s", timer.remaining_secs()));
+    }
+    if let Some(arena) = arena.filter(|arena| arena.can_shrink()) {
+        lines.push(format!("Arena shrinks in {:.0}s", arena.timer.remaining_secs().ceil()));
+    }
+    if let Some(match_timer) = match_timer {
+        let seconds = match_timer.0.remaining_secs().ceil() as u32;
+        lines.push(format!("Time: {}:{:02}", seconds / 60, seconds % 60));
+    }
+
+    let separator = match *game_mode {
+        GameMode::Single | GameMode::Coop => "   ",
+        GameMode::Versus | GameMode::VersusAi => "\n",
+    };
+
+    for mut text in query.iter_mut() {
+        text.0 = lines.join(separator);
+    }
+}